@@ -0,0 +1,544 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    str::FromStr,
+    sync::{Arc, RwLock},
+};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    XChaCha20Poly1305, XNonce,
+};
+use crypto_box::ChaChaBox;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::UniqueUserId;
+
+const KEY_SIZE: usize = 32;
+
+/// Encryption shared between the app and a network backend, allowing it to be changed at runtime.
+/// `None` means we have no keys, so statuses are published in plaintext and encrypted statuses
+/// can't be read.
+pub type SharedEncryption = Arc<RwLock<Option<PayloadEncryption>>>;
+
+#[derive(Error, Debug)]
+pub enum EncryptionError {
+    #[error("The key was not valid base64-encoded {KEY_SIZE}-byte data")]
+    InvalidKey,
+    #[error("The payload is not addressed to any of our keys")]
+    NotARecipient,
+    #[error("We don't have a trusted key for {0}")]
+    NoKeyForUser(UniqueUserId),
+    #[error("Failed to encrypt or decrypt the payload")]
+    Cryptography,
+    #[error("The decrypted payload was not valid UTF-8")]
+    InvalidUtf8,
+    #[error("Failed to (de)serialize the encrypted envelope: {0}")]
+    Envelope(#[from] serde_json::Error),
+}
+
+/// A user's own X25519 key pair. Only the public half should ever leave the machine.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct KeyPair {
+    secret_key: crypto_box::SecretKey,
+}
+
+/// The public half of a [`KeyPair`], shared out-of-band with friends so they can encrypt to us.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct PublicKey([u8; KEY_SIZE]);
+
+/// A symmetric key shared by everyone in a group, as an alternative to encrypting per recipient.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
+pub struct GroupKey([u8; KEY_SIZE]);
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize, Default)]
+pub enum EncryptionMode {
+    /// Statuses are encrypted separately for each trusted public key
+    #[default]
+    PerRecipient,
+    /// Statuses are encrypted with a single key shared by the whole group
+    Group,
+}
+
+/// Everything needed to encrypt our outgoing statuses and decrypt incoming ones.
+#[derive(Clone)]
+pub struct PayloadEncryption {
+    pub key_pair: KeyPair,
+    pub recipients: Vec<PublicKey>,
+    /// The trusted keys we know the owners of, used for statuses meant for one user only
+    pub keys_by_user: HashMap<UniqueUserId, PublicKey>,
+    pub group_key: Option<GroupKey>,
+    pub mode: EncryptionMode,
+    /// Whether the statuses we publish are encrypted. Our keys are still used to read statuses
+    /// others encrypted to us either way.
+    pub encrypt_published: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "encryption")]
+enum EncryptedEnvelope {
+    PerRecipient {
+        sender: PublicKey,
+        nonce: String,
+        ciphertext: String,
+        keys: Vec<WrappedKey>,
+    },
+    Group {
+        key_id: String,
+        nonce: String,
+        ciphertext: String,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+struct WrappedKey {
+    recipient: PublicKey,
+    nonce: String,
+    wrapped: String,
+}
+
+impl KeyPair {
+    pub fn generate() -> Self {
+        Self {
+            secret_key: crypto_box::SecretKey::generate(&mut OsRng),
+        }
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.secret_key.public_key().to_bytes())
+    }
+}
+
+impl GroupKey {
+    pub fn generate() -> Self {
+        Self(XChaCha20Poly1305::generate_key(&mut OsRng).into())
+    }
+
+    /// A short identifier for the key that can be transmitted without revealing the key itself
+    pub fn id(&self) -> String {
+        let key_id_cipher = XChaCha20Poly1305::new(&self.0.into());
+        let id = key_id_cipher
+            .encrypt(&XNonce::default(), &[][..])
+            .expect("encrypting an empty buffer cannot fail");
+        BASE64.encode(&id[..8])
+    }
+}
+
+impl PayloadEncryption {
+    pub fn new(key_pair: KeyPair) -> Self {
+        Self {
+            key_pair,
+            recipients: Vec::new(),
+            keys_by_user: HashMap::new(),
+            group_key: None,
+            mode: Default::default(),
+            encrypt_published: true,
+        }
+    }
+
+    /// Encrypts `plaintext` into an envelope that can be sent over the wire as a string
+    pub fn seal(&self, plaintext: &str) -> Result<String, EncryptionError> {
+        let envelope = match (self.mode, self.group_key.as_ref()) {
+            (EncryptionMode::Group, Some(group_key)) => {
+                let (nonce, ciphertext) = encrypt_symmetric(&group_key.0, plaintext.as_bytes())?;
+                EncryptedEnvelope::Group {
+                    key_id: group_key.id(),
+                    nonce,
+                    ciphertext,
+                }
+            }
+            _ => self.seal_per_recipient(plaintext, &self.recipients)?,
+        };
+
+        Ok(serde_json::to_string(&envelope)?)
+    }
+
+    /// Encrypts `plaintext` so that only `user_id` and ourselves can read it, whatever the mode.
+    /// Fails if we don't know which trusted key is theirs.
+    pub fn seal_for_user(
+        &self,
+        user_id: &UniqueUserId,
+        plaintext: &str,
+    ) -> Result<String, EncryptionError> {
+        let recipient = self
+            .keys_by_user
+            .get(user_id)
+            .ok_or_else(|| EncryptionError::NoKeyForUser(user_id.clone()))?;
+        let envelope = self.seal_per_recipient(plaintext, std::slice::from_ref(recipient))?;
+        Ok(serde_json::to_string(&envelope)?)
+    }
+
+    /// Decrypts a payload previously produced by [`PayloadEncryption::seal`]
+    pub fn open(&self, payload: &str) -> Result<String, EncryptionError> {
+        let envelope: EncryptedEnvelope = serde_json::from_str(payload)?;
+        self.open_envelope(envelope)
+    }
+
+    fn seal_per_recipient(
+        &self,
+        plaintext: &str,
+        recipients: &[PublicKey],
+    ) -> Result<EncryptedEnvelope, EncryptionError> {
+        let content_key: [u8; KEY_SIZE] = XChaCha20Poly1305::generate_key(&mut OsRng).into();
+        let (nonce, ciphertext) = encrypt_symmetric(&content_key, plaintext.as_bytes())?;
+
+        // We always include ourselves so our own status round-trips back to us
+        let own_public_key = self.key_pair.public_key();
+        let recipients = std::iter::once(&own_public_key)
+            .chain(recipients.iter().filter(|r| **r != own_public_key));
+
+        let keys = recipients
+            .map(|recipient| self.wrap_key(&content_key, recipient))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(EncryptedEnvelope::PerRecipient {
+            sender: own_public_key,
+            nonce,
+            ciphertext,
+            keys,
+        })
+    }
+
+    fn open_envelope(&self, envelope: EncryptedEnvelope) -> Result<String, EncryptionError> {
+        let plaintext = match envelope {
+            EncryptedEnvelope::PerRecipient {
+                sender,
+                nonce,
+                ciphertext,
+                keys,
+            } => {
+                let own_public_key = self.key_pair.public_key();
+                let wrapped_key = keys
+                    .iter()
+                    .find(|k| k.recipient == own_public_key)
+                    .ok_or(EncryptionError::NotARecipient)?;
+                let content_key = self.unwrap_key(wrapped_key, &sender)?;
+                decrypt_symmetric(&content_key, &nonce, &ciphertext)?
+            }
+            EncryptedEnvelope::Group {
+                key_id,
+                nonce,
+                ciphertext,
+            } => {
+                let group_key = self
+                    .group_key
+                    .as_ref()
+                    .filter(|k| k.id() == key_id)
+                    .ok_or(EncryptionError::NotARecipient)?;
+                decrypt_symmetric(&group_key.0, &nonce, &ciphertext)?
+            }
+        };
+
+        String::from_utf8(plaintext).map_err(|_| EncryptionError::InvalidUtf8)
+    }
+
+    fn wrap_key(
+        &self,
+        content_key: &[u8; KEY_SIZE],
+        recipient: &PublicKey,
+    ) -> Result<WrappedKey, EncryptionError> {
+        let crypto_box = ChaChaBox::new(&recipient.into(), &self.key_pair.secret_key);
+        let nonce = ChaChaBox::generate_nonce(&mut OsRng);
+        let wrapped = crypto_box
+            .encrypt(&nonce, &content_key[..])
+            .map_err(|_| EncryptionError::Cryptography)?;
+        Ok(WrappedKey {
+            recipient: recipient.clone(),
+            nonce: BASE64.encode(nonce),
+            wrapped: BASE64.encode(wrapped),
+        })
+    }
+
+    fn unwrap_key(
+        &self,
+        wrapped_key: &WrappedKey,
+        sender: &PublicKey,
+    ) -> Result<[u8; KEY_SIZE], EncryptionError> {
+        let crypto_box = ChaChaBox::new(&sender.into(), &self.key_pair.secret_key);
+        let nonce = decode_nonce(&wrapped_key.nonce)?;
+        let wrapped = BASE64
+            .decode(&wrapped_key.wrapped)
+            .map_err(|_| EncryptionError::Cryptography)?;
+        crypto_box
+            .decrypt(&nonce, &wrapped[..])
+            .map_err(|_| EncryptionError::Cryptography)?
+            .try_into()
+            .map_err(|_| EncryptionError::Cryptography)
+    }
+}
+
+/// Prepares a serialized status for the wire, encrypting it if encryption is enabled
+pub fn encode_payload(
+    encryption: &SharedEncryption,
+    plaintext: String,
+) -> Result<String, EncryptionError> {
+    match encryption.read().unwrap().as_ref() {
+        Some(encryption) if encryption.encrypt_published => encryption.seal(&plaintext),
+        _ => Ok(plaintext),
+    }
+}
+
+/// Prepares a serialized status meant for `user_id` alone. Unlike [`encode_payload`], this never
+/// falls back to the group key or plaintext, so it fails if we don't have a key for them.
+pub fn encode_payload_for_user(
+    encryption: &SharedEncryption,
+    user_id: &UniqueUserId,
+    plaintext: String,
+) -> Result<String, EncryptionError> {
+    match encryption.read().unwrap().as_ref() {
+        Some(encryption) => encryption.seal_for_user(user_id, &plaintext),
+        None => Err(EncryptionError::NoKeyForUser(user_id.clone())),
+    }
+}
+
+/// Turns a payload received over the wire back into a serialized status. Plaintext payloads are
+/// passed through as-is, while encrypted payloads we can't decrypt are an error.
+pub fn decode_payload(
+    encryption: &SharedEncryption,
+    payload: &str,
+) -> Result<String, EncryptionError> {
+    let Ok(envelope) = serde_json::from_str::<EncryptedEnvelope>(payload) else {
+        return Ok(payload.to_string());
+    };
+
+    match encryption.read().unwrap().as_ref() {
+        Some(encryption) => encryption.open_envelope(envelope),
+        None => Err(EncryptionError::NotARecipient),
+    }
+}
+
+fn encrypt_symmetric(
+    key: &[u8; KEY_SIZE],
+    plaintext: &[u8],
+) -> Result<(String, String), EncryptionError> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| EncryptionError::Cryptography)?;
+    Ok((BASE64.encode(nonce), BASE64.encode(ciphertext)))
+}
+
+fn decrypt_symmetric(
+    key: &[u8; KEY_SIZE],
+    nonce: &str,
+    ciphertext: &str,
+) -> Result<Vec<u8>, EncryptionError> {
+    let cipher = XChaCha20Poly1305::new(key.into());
+    let nonce = decode_nonce(nonce)?;
+    let ciphertext = BASE64
+        .decode(ciphertext)
+        .map_err(|_| EncryptionError::Cryptography)?;
+    cipher
+        .decrypt(&nonce, &ciphertext[..])
+        .map_err(|_| EncryptionError::Cryptography)
+}
+
+fn decode_nonce(nonce: &str) -> Result<XNonce, EncryptionError> {
+    let bytes = BASE64
+        .decode(nonce)
+        .map_err(|_| EncryptionError::Cryptography)?;
+    if bytes.len() != 24 {
+        return Err(EncryptionError::Cryptography);
+    }
+
+    Ok(*XNonce::from_slice(&bytes))
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; KEY_SIZE], EncryptionError> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(EncryptionError::InvalidKey)
+}
+
+impl From<&PublicKey> for crypto_box::PublicKey {
+    fn from(value: &PublicKey) -> Self {
+        crypto_box::PublicKey::from_bytes(value.0)
+    }
+}
+
+impl FromStr for PublicKey {
+    type Err = EncryptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode_key(s).map(PublicKey)
+    }
+}
+
+impl Display for PublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", BASE64.encode(self.0))
+    }
+}
+
+impl From<PublicKey> for String {
+    fn from(value: PublicKey) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for PublicKey {
+    type Error = EncryptionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl FromStr for GroupKey {
+    type Err = EncryptionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        decode_key(s).map(GroupKey)
+    }
+}
+
+impl Display for GroupKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", BASE64.encode(self.0))
+    }
+}
+
+impl From<GroupKey> for String {
+    fn from(value: GroupKey) -> Self {
+        value.to_string()
+    }
+}
+
+impl TryFrom<String> for GroupKey {
+    type Error = EncryptionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<KeyPair> for String {
+    fn from(value: KeyPair) -> Self {
+        BASE64.encode(value.secret_key.to_bytes())
+    }
+}
+
+impl TryFrom<String> for KeyPair {
+    type Error = EncryptionError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Ok(KeyPair {
+            secret_key: crypto_box::SecretKey::from_bytes(decode_key(&value)?),
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    const PLAINTEXT: &str = "{\"outputs\":[]}";
+
+    fn shared(encryption: Option<PayloadEncryption>) -> SharedEncryption {
+        Arc::new(RwLock::new(encryption))
+    }
+
+    #[test]
+    pub fn per_recipient_payload_can_be_opened_by_recipient_and_sender() {
+        let sender = PayloadEncryption::new(KeyPair::generate());
+        let recipient = PayloadEncryption::new(KeyPair::generate());
+        let sender = PayloadEncryption {
+            recipients: vec![recipient.key_pair.public_key()],
+            ..sender
+        };
+
+        let sealed = sender.seal(PLAINTEXT).unwrap();
+
+        assert_eq!(PLAINTEXT, recipient.open(&sealed).unwrap());
+        assert_eq!(PLAINTEXT, sender.open(&sealed).unwrap());
+    }
+
+    #[test]
+    pub fn per_recipient_payload_cannot_be_opened_by_others() {
+        let sender = PayloadEncryption::new(KeyPair::generate());
+        let outsider = PayloadEncryption::new(KeyPair::generate());
+
+        let sealed = sender.seal(PLAINTEXT).unwrap();
+
+        assert!(matches!(
+            outsider.open(&sealed),
+            Err(EncryptionError::NotARecipient)
+        ));
+    }
+
+    #[test]
+    pub fn group_payload_requires_matching_group_key() {
+        let group_key = GroupKey::generate();
+        let with_key = |key: GroupKey| PayloadEncryption {
+            group_key: Some(key),
+            mode: EncryptionMode::Group,
+            ..PayloadEncryption::new(KeyPair::generate())
+        };
+        let sender = with_key(group_key.clone());
+        let member = with_key(group_key);
+        let outsider = with_key(GroupKey::generate());
+
+        let sealed = sender.seal(PLAINTEXT).unwrap();
+
+        assert_eq!(PLAINTEXT, member.open(&sealed).unwrap());
+        assert!(outsider.open(&sealed).is_err());
+    }
+
+    #[test]
+    pub fn decode_payload_passes_plaintext_through() {
+        let encryption = shared(Some(PayloadEncryption::new(KeyPair::generate())));
+
+        assert_eq!(PLAINTEXT, decode_payload(&encryption, PLAINTEXT).unwrap());
+        assert_eq!(PLAINTEXT, decode_payload(&shared(None), PLAINTEXT).unwrap());
+    }
+
+    #[test]
+    pub fn decode_payload_without_encryption_cannot_read_encrypted_payload() {
+        let encryption = shared(Some(PayloadEncryption::new(KeyPair::generate())));
+        let encoded = encode_payload(&encryption, PLAINTEXT.to_string()).unwrap();
+
+        assert_ne!(PLAINTEXT, encoded);
+        assert!(decode_payload(&shared(None), &encoded).is_err());
+        assert_eq!(PLAINTEXT, decode_payload(&encryption, &encoded).unwrap());
+    }
+
+    #[test]
+    pub fn publishing_unencrypted_still_reads_encrypted_payloads() {
+        let reader = PayloadEncryption::new(KeyPair::generate());
+        let sender = PayloadEncryption {
+            recipients: vec![reader.key_pair.public_key()],
+            ..PayloadEncryption::new(KeyPair::generate())
+        };
+        let encoded = encode_payload(&shared(Some(sender)), PLAINTEXT.to_string()).unwrap();
+        let reader = shared(Some(PayloadEncryption {
+            encrypt_published: false,
+            ..reader
+        }));
+
+        assert_eq!(
+            PLAINTEXT,
+            encode_payload(&reader, PLAINTEXT.to_string()).unwrap()
+        );
+        assert_eq!(PLAINTEXT, decode_payload(&reader, &encoded).unwrap());
+    }
+
+    #[test]
+    pub fn keys_round_trip_through_strings() {
+        let key_pair = KeyPair::generate();
+        let public_key = key_pair.public_key();
+
+        let restored: KeyPair = String::from(key_pair).try_into().unwrap();
+
+        assert_eq!(public_key, restored.public_key());
+        assert_eq!(public_key, public_key.to_string().parse().unwrap());
+        assert!("not a key".parse::<PublicKey>().is_err());
+    }
+}
@@ -1,9 +1,11 @@
+pub mod encryption;
 pub mod periodic_checker;
 
-use std::fmt::Display;
+use std::{collections::HashSet, fmt::Display};
 
 use chrono::{DateTime, Utc};
 use nutype::nutype;
+use serde::{Deserialize, Serialize};
 
 pub use chrono;
 
@@ -35,6 +37,15 @@ pub struct Username(String);
 
 pub enum RemoteUpdate<T> {
     UserStatusUpdated(UserStatus<T>),
+    /// Only sent by networks where users have to agree to see each other. Statuses are only
+    /// received from friends, and any other users' statuses should be forgotten.
+    FriendshipsUpdated(Box<Friendships>),
+    /// A status from before we connected, sent to fill in what was missed. It's older than the
+    /// user's current status, so it shouldn't replace it.
+    PastStatusReceived(UserStatus<T>),
+    /// The network refused something we sent, e.g. a name it doesn't allow. Holds a description
+    /// to show the user.
+    UpdateRejected(String),
 }
 
 #[derive(Clone, Debug)]
@@ -55,14 +66,49 @@ impl<T> UserStatus<T> {
     }
 }
 
+/// Who we've agreed to see each other with, and who's still waiting on an answer
+#[derive(Clone, Debug, Default)]
+pub struct Friendships {
+    pub friends: HashSet<UniqueUserId>,
+    /// Requests from other users that we haven't answered yet
+    pub incoming_requests: Vec<FriendRequest>,
+    /// Requests we've sent that haven't been answered yet
+    pub outgoing_requests: HashSet<UniqueUserId>,
+    pub blocked: HashSet<UniqueUserId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FriendRequest {
+    pub user_id: UniqueUserId,
+    pub username: Username,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FriendshipAction {
+    /// Asks to see each other, or agrees if they already asked
+    Request,
+    Accept,
+    /// Withdraws or declines a request, ends a friendship, or lifts a block
+    Remove,
+    Block,
+}
+
+impl Friendships {
+    pub fn is_friend(&self, user_id: &UniqueUserId) -> bool {
+        self.friends.contains(user_id)
+    }
+}
+
 pub trait AcceptsOnlineStatus {
     fn set_online_status(&mut self, online: bool);
 }
 
-#[derive(enum_display_derive::Display, PartialEq, Clone)]
+#[derive(enum_display_derive::Display, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum NetworkType {
     Offline,
     SpacetimeDB,
+    PeerToPeer,
+    Relay,
 }
 
 pub trait NetworkInterfaceCreator<T, NI, P>
@@ -78,7 +124,13 @@ where
 
 pub trait NetworkInterface<T> {
     fn publish_update(&self, sensor_outputs: T);
+    /// Publishes statuses that only the given users should see instead of the one published to
+    /// everyone, replacing those previously published for other users. Networks that can't
+    /// address individual users ignore these, leaving everyone with the same status.
+    fn publish_recipient_updates(&self, _updates: Vec<(UniqueUserId, T)>) {}
     fn set_username(&self, name: String);
+    /// Networks that don't send [`RemoteUpdate::FriendshipsUpdated`] ignore this
+    fn update_friendship(&self, _user_id: &UniqueUserId, _action: FriendshipAction) {}
     fn get_current_user_id(&self) -> Option<UniqueUserId>;
     fn get_network_type(&self) -> NetworkType;
     fn is_connected(&self) -> bool;
@@ -1,5 +1,6 @@
 use crate::{
-    networking::network_manager::NetworkManager,
+    history::{ActivityHistory, HistorySettings, HistoryUser, HISTORY_FILE_NAME},
+    networking::{circle::CircleId, network_manager::NetworkManager},
     notification::{NotificationDispatch, OSNotificationDispatch},
     periodic_repaint_thread::create_periodic_repaint_thread,
     persistence::{Persistence, PersistenceV1, VersionedPersistence},
@@ -8,17 +9,32 @@ use crate::{
     sensors::{
         lock_status_sensor::{init_lock_status_sensor, EventLoopRegisteredLockStatusSensorBuilder},
         outputs::{
-            online_status::OnlineStatus, sensor_output::SensorWidget, sensor_outputs::SensorOutputs,
+            online_status::OnlineStatus,
+            sensor_output::{SensorOutput, SensorOutputKind, SensorWidget},
+            sensor_outputs::SensorOutputs,
         },
+        ActivitySensorType,
     },
     triggers::{ui::TriggersWindow, BehaviorOnTrigger, TriggerManager, Update},
     ui::{
+        activity_settings_window::ActivitySettingsWindow,
         add_fake_user_window::AddFakeUserWindow,
+        app_rules_window::AppRulesWindow,
+        encryption_window::EncryptionWindow,
+        friends_window::FriendsWindow,
+        manual_status_menu::ManualStatusMenu,
         network_window::NetworkWindow,
-        raw_data_window::{RawDataWindow, TimestampedData},
+        overrides_window::OverridesWindow,
+        raw_data_window::RawDataWindow,
+        sent_data_window::SentDataWindow,
+        sharing_window::SharingWindow,
         time_formatting::nicely_formatted_datetime,
+        timeline_window::TimelineWindow,
         ui_extension_methods::UIExtensionMethods,
-        widgets::auto_launch_checkbox::AutoLaunchCheckboxUiExtension,
+        widgets::{
+            auto_launch_checkbox::AutoLaunchCheckboxUiExtension,
+            availability_badge::AvailabilityBadge,
+        },
     },
     user_summaries::UserSummaries,
 };
@@ -26,11 +42,10 @@ use chrono_humanize::HumanTime;
 use egui::{Color32, RichText, ScrollArea, ViewportCommand};
 use gwaihir_client_lib::{
     chrono::{Local, Utc},
-    NetworkInterface, RemoteUpdate, UniqueUserId, UserStatus, APP_ID,
+    FriendshipAction, Friendships, RemoteUpdate, UniqueUserId, UserStatus, APP_ID,
 };
 use log::{debug, info, warn};
 use log_err::LogErrResult;
-use networking_spacetimedb::{SpacetimeDBCreationParameters, SpacetimeDBInterface};
 use std::{
     cell::RefCell,
     cmp::Ordering,
@@ -38,7 +53,10 @@ use std::{
     ffi::OsStr,
     path::PathBuf,
     rc::Rc,
-    sync::mpsc::{Receiver, Sender, TryRecvError},
+    sync::{
+        mpsc::{Receiver, Sender, TryRecvError},
+        Arc, RwLock,
+    },
     thread::JoinHandle,
     time::Duration,
 };
@@ -53,22 +71,38 @@ pub struct GwaihirApp {
     sensor_monitor_thread_join_handle: Option<JoinHandle<()>>,
     tx_to_monitor_thread: Sender<MainToMonitorMessages>,
     rx_from_monitor_thread: Receiver<MonitorToMainMessages>,
-    current_status: HashMap<UniqueUserId, UserStatus<SensorOutputs>>,
+    current_status: HashMap<CircleId, HashMap<UniqueUserId, UserStatus<SensorOutputs>>>,
+    /// The most recent outputs from the sensor monitor, before any overrides or the sharing policy were applied
+    latest_sensor_outputs: Option<SensorOutputs>,
+    /// Why each sensor that couldn't be started is missing
+    unavailable_sensors: HashMap<SensorOutputKind, String>,
     user_summaries: UserSummaries,
 
     _periodic_repaint_thread_join_handle: JoinHandle<()>,
 
     network: NetworkManager,
-    current_user_id: Option<UniqueUserId>,
+    current_user_ids: HashMap<CircleId, UniqueUserId>,
+    friendships: HashMap<CircleId, Friendships>,
+    /// The last reason each circle's network gave for refusing something we sent
+    rejected_updates: HashMap<CircleId, String>,
 
     persistence: Persistence,
+    activity_history: Option<ActivityHistory>,
     log_file_location: PathBuf,
 
     network_window: NetworkWindow,
-    transmission_spy: RawDataWindow,
+    transmission_spy: SentDataWindow,
     received_data_viewer: RawDataWindow,
     add_fake_user_window: AddFakeUserWindow,
     triggers_window: TriggersWindow,
+    encryption_window: EncryptionWindow,
+    friends_window: FriendsWindow,
+    sharing_window: SharingWindow,
+    app_rules_window: AppRulesWindow,
+    activity_settings_window: ActivitySettingsWindow,
+    timeline_window: TimelineWindow,
+    overrides_window: OverridesWindow,
+    manual_status_menu: ManualStatusMenu,
 }
 
 impl GwaihirApp {
@@ -91,64 +125,91 @@ impl GwaihirApp {
         }
 
         let persistence = load_and_migrate_persistence(cc, &log_file_location);
+        // Before the activity sensor, so the keyboard/mouse sensor starts with the right buckets
+        tx_to_monitor_thread
+            .send(MainToMonitorMessages::SetActivitySettings(
+                persistence.activity_settings,
+            ))
+            .unwrap();
+        tx_to_monitor_thread
+            .send(MainToMonitorMessages::SetActivitySensor(
+                persistence.activity_sensor,
+            ))
+            .unwrap();
+        tx_to_monitor_thread
+            .send(MainToMonitorMessages::SetAppRules(
+                persistence.app_rules.clone(),
+            ))
+            .unwrap();
 
         let periodic_repaint_thread_join_handle =
             create_periodic_repaint_thread(cc.egui_ctx.clone(), Duration::from_secs(10));
 
-        let creation_params = SpacetimeDBCreationParameters {
-            db_name: persistence.spacetimedb_db_name.clone(),
-        };
-        let network =
-            NetworkManager::new::<SpacetimeDBInterface, _>(cc.egui_ctx.clone(), creation_params);
+        let encryption = Arc::new(RwLock::new(Some(
+            persistence.encryption.to_payload_encryption(),
+        )));
+        let network = NetworkManager::new(cc.egui_ctx.clone(), encryption, &persistence.circles);
+        let activity_history = open_activity_history(&persistence.history_settings);
 
         GwaihirApp {
             tx_to_monitor_thread,
             rx_from_monitor_thread,
             current_status: HashMap::new(),
+            latest_sensor_outputs: None,
+            unavailable_sensors: HashMap::new(),
             user_summaries: UserSummaries::new(),
 
-            network_window: NetworkWindow::new(&network),
-            transmission_spy: RawDataWindow::new("Last Sent Data".to_string()),
+            network_window: NetworkWindow::new(),
+            transmission_spy: SentDataWindow::new(),
             received_data_viewer: RawDataWindow::new("Raw Data".to_string()),
 
             sensor_monitor_thread_join_handle: Some(sensor_monitor_thread_join_handle),
             network,
-            current_user_id: None,
+            current_user_ids: HashMap::new(),
+            friendships: HashMap::new(),
+            rejected_updates: HashMap::new(),
 
             _periodic_repaint_thread_join_handle: periodic_repaint_thread_join_handle,
 
             persistence,
+            activity_history,
             log_file_location,
 
             add_fake_user_window: AddFakeUserWindow::new(),
             triggers_window: TriggersWindow::new(),
+            encryption_window: EncryptionWindow::new(),
+            friends_window: FriendsWindow::new(),
+            sharing_window: SharingWindow::new(),
+            app_rules_window: AppRulesWindow::new(),
+            activity_settings_window: ActivitySettingsWindow::new(),
+            timeline_window: TimelineWindow::new(),
+            overrides_window: OverridesWindow::new(),
+            manual_status_menu: ManualStatusMenu::new(),
 
             #[cfg(feature = "hide_to_tray")]
             tray_icon_data: None,
         }
     }
 
-    fn get_filtered_sorted_user_statuses(&self) -> Vec<(UniqueUserId, UserStatus<SensorOutputs>)> {
+    fn get_filtered_sorted_user_statuses(
+        &self,
+        circle_id: &CircleId,
+    ) -> Vec<(UniqueUserId, UserStatus<SensorOutputs>)> {
         let mut user_statuses = self
             .current_status
-            .iter()
+            .get(circle_id)
+            .into_iter()
+            .flatten()
             .filter(|(id, _)| self.subscribed_to_user(id))
             .map(|(id, status)| (id.clone(), status.clone()))
             .collect::<Vec<_>>();
 
         // Sort to ensure current user is on top
+        let current_user_id = self.current_user_ids.get(circle_id);
         user_statuses.sort_by(|(id_a, _), (id_b, _)| {
-            if self
-                .current_user_id
-                .as_ref()
-                .is_some_and(|own_id| own_id == id_a)
-            {
+            if current_user_id.is_some_and(|own_id| own_id == id_a) {
                 Ordering::Less
-            } else if self
-                .current_user_id
-                .as_ref()
-                .is_some_and(|own_id| own_id == id_b)
-            {
+            } else if current_user_id.is_some_and(|own_id| own_id == id_b) {
                 Ordering::Equal
             } else {
                 id_a.cmp(id_b)
@@ -187,23 +248,44 @@ impl GwaihirApp {
 
     fn show_user_context_menu(
         &mut self,
+        circle_id: &CircleId,
         target_user_id: &UniqueUserId,
         ui: &mut egui::Ui,
         user_status: &UserStatus<SensorOutputs>,
     ) {
-        match &self.current_user_id {
+        match self.current_user_ids.get(circle_id) {
             Some(current_user_id) => {
                 if target_user_id == current_user_id {
                     ui.name_input("Set Username", "set_username_input", |name| {
-                        self.network.set_username(name)
+                        self.network.set_username(circle_id, name)
                     });
                 }
 
-                if ui.button("Ignore").clicked() {
-                    self.persistence
-                        .ignored_users
-                        .insert(target_user_id.clone());
-                    ui.close_menu();
+                if !self.friendships.contains_key(circle_id) {
+                    if ui.button("Ignore").clicked() {
+                        self.persistence
+                            .ignored_users
+                            .insert(target_user_id.clone());
+                        ui.close_menu();
+                    }
+                } else if target_user_id != current_user_id {
+                    if ui.button("Remove Friend").clicked() {
+                        self.network.update_friendship(
+                            circle_id,
+                            target_user_id,
+                            FriendshipAction::Remove,
+                        );
+                        ui.close_menu();
+                    }
+
+                    if ui.button("Block").clicked() {
+                        self.network.update_friendship(
+                            circle_id,
+                            target_user_id,
+                            FriendshipAction::Block,
+                        );
+                        ui.close_menu();
+                    }
                 }
 
                 ui.menu_button("Triggers", |ui| {
@@ -274,8 +356,15 @@ impl GwaihirApp {
         }
     }
 
-    fn get_user_display_name(&self, user_id: &UniqueUserId) -> Option<String> {
-        self.current_status.get(user_id).map(|s| s.display_name())
+    fn get_user_display_name(
+        &self,
+        circle_id: &CircleId,
+        user_id: &UniqueUserId,
+    ) -> Option<String> {
+        self.current_status
+            .get(circle_id)?
+            .get(user_id)
+            .map(|s| s.display_name())
     }
 
     fn trigger_manager(&mut self) -> &mut TriggerManager {
@@ -305,6 +394,17 @@ fn load_and_migrate_persistence(
     }
 }
 
+fn open_activity_history(settings: &HistorySettings) -> Option<ActivityHistory> {
+    if !settings.enabled {
+        return None;
+    }
+
+    let path = project_dirs().data_dir().join(HISTORY_FILE_NAME);
+    ActivityHistory::open(&path, settings.retention())
+        .map_err(|e| warn!("Failed to open activity history at {:?}: {}", path, e))
+        .ok()
+}
+
 fn open_log_file(log_file_location: impl AsRef<OsStr>) {
     opener::open(log_file_location).log_expect("Failed to open file using default OS handler");
 }
@@ -332,23 +432,38 @@ impl eframe::App for GwaihirApp {
                 panic!("The background thread unexpected disconnected!");
             }
             Ok(MonitorToMainMessages::UpdatedSensorOutputs(sensor_outputs)) => {
-                debug!("Publishing update: {:#?}", &sensor_outputs);
-                self.transmission_spy
-                    .set_data(TimestampedData::now(sensor_outputs.clone()));
-                self.network.publish_update(sensor_outputs);
+                self.publish_sensor_outputs(&sensor_outputs);
+                self.latest_sensor_outputs = Some(sensor_outputs);
+            }
+            Ok(MonitorToMainMessages::SensorUnavailable(kind, reason)) => {
+                self.unavailable_sensors.insert(kind, reason);
             }
         }
 
+        let overrides_expired = self.persistence.sensor_overrides.remove_expired(Utc::now());
+        let manual_status_expired = self
+            .persistence
+            .manual_status
+            .as_ref()
+            .is_some_and(|s| s.is_expired(Utc::now()));
+        if manual_status_expired {
+            self.persistence.manual_status = None;
+        }
+        if overrides_expired || manual_status_expired {
+            self.republish_latest_sensor_outputs();
+        }
+
         self.network.try_reconnect_if_needed();
-        while let Ok(update) = self.network.try_recv() {
+        while let Ok((circle_id, update)) = self.network.try_recv() {
             match update {
                 RemoteUpdate::UserStatusUpdated(status) => {
                     if self.subscribed_to_user(&status.user_id) {
                         debug!("Got user update from DB: {:#?}", &status);
-                        let current = self.current_status.get(&status.user_id).unwrap_or(&status);
                         let display_name = self
-                            .get_user_display_name(&status.user_id)
+                            .get_user_display_name(&circle_id, &status.user_id)
                             .unwrap_or_else(|| "Unknown".to_string());
+                        let circle_statuses = self.current_status.entry(circle_id).or_default();
+                        let current = circle_statuses.get(&status.user_id).unwrap_or(&status);
                         self.persistence.trigger_manager.execute_triggers(
                             &status.user_id,
                             display_name,
@@ -356,16 +471,56 @@ impl eframe::App for GwaihirApp {
                             &OSNotificationDispatch,
                             &mut self.user_summaries,
                         );
-                        self.current_status.insert(status.user_id.clone(), status);
+                        if let Some(history) = self.activity_history.as_mut() {
+                            history
+                                .record(
+                                    &HistoryUser::Other(status.user_id.clone()),
+                                    status.username.as_ref(),
+                                    status.last_update,
+                                    &status.sensor_outputs,
+                                )
+                                .unwrap_or_else(|e| warn!("Failed to record status: {}", e));
+                        }
+                        circle_statuses.insert(status.user_id.clone(), status);
                     }
                 }
+                RemoteUpdate::FriendshipsUpdated(friendships) => {
+                    let own_id = self.current_user_ids.get(&circle_id);
+                    if let Some(circle_statuses) = self.current_status.get_mut(&circle_id) {
+                        circle_statuses
+                            .retain(|id, _| own_id == Some(id) || friendships.is_friend(id));
+                    }
+                    self.friendships.insert(circle_id, *friendships);
+                }
+                RemoteUpdate::PastStatusReceived(status) => {
+                    // Only fills in the timeline, so there's nothing to do without one
+                    let subscribed = self.subscribed_to_user(&status.user_id);
+                    if let Some(history) = self.activity_history.as_mut().filter(|_| subscribed) {
+                        history
+                            .record(
+                                &HistoryUser::Other(status.user_id.clone()),
+                                status.username.as_ref(),
+                                status.last_update,
+                                &status.sensor_outputs,
+                            )
+                            .unwrap_or_else(|e| warn!("Failed to record past status: {}", e));
+                    }
+                }
+                RemoteUpdate::UpdateRejected(reason) => {
+                    warn!("Update rejected by circle {}: {}", circle_id, reason);
+                    self.rejected_updates.insert(circle_id, reason);
+                }
             };
         }
 
-        if self.current_user_id.is_none() {
-            self.current_user_id = self.network.get_current_user_id();
-            self.transmission_spy
-                .set_user_id(self.current_user_id.clone());
+        self.sample_availability();
+
+        for circle in self.persistence.circles.iter() {
+            if !self.current_user_ids.contains_key(&circle.id) {
+                if let Some(user_id) = self.network.get_current_user_id(&circle.id) {
+                    self.current_user_ids.insert(circle.id, user_id);
+                }
+            }
         }
 
         #[cfg(feature = "hide_to_try")]
@@ -403,7 +558,7 @@ impl eframe::App for GwaihirApp {
                     });
 
                     ui.menu_button("Manage", |ui| {
-                        if ui.button("Network").clicked() {
+                        if ui.button("Circles").clicked() {
                             self.network_window.set_shown(true);
                             ui.close_menu();
                         }
@@ -412,6 +567,55 @@ impl eframe::App for GwaihirApp {
                             self.triggers_window.set_shown(true);
                             ui.close_menu();
                         }
+
+                        if ui.button("Encryption").clicked() {
+                            self.encryption_window.set_shown(true);
+                            ui.close_menu();
+                        }
+
+                        let sharing_text = if self.unavailable_sensors.is_empty() {
+                            "Sensor Sharing"
+                        } else {
+                            "Sensor Sharing ⚠"
+                        };
+                        if ui.button(sharing_text).clicked() {
+                            self.sharing_window.set_shown(true);
+                            ui.close_menu();
+                        }
+
+                        if ui.button("Sensor Overrides").clicked() {
+                            self.overrides_window.set_shown(true);
+                            ui.close_menu();
+                        }
+
+                        if ui.button("App Rules").clicked() {
+                            self.app_rules_window.set_shown(true);
+                            ui.close_menu();
+                        }
+                    });
+
+                    ui.menu_button("Activity Sensor", |ui| {
+                        for activity_sensor in enum_iterator::all::<ActivitySensorType>() {
+                            if ui
+                                .radio_value(
+                                    &mut self.persistence.activity_sensor,
+                                    activity_sensor,
+                                    activity_sensor.to_string(),
+                                )
+                                .clicked()
+                            {
+                                self.tx_to_monitor_thread
+                                    .send(MainToMonitorMessages::SetActivitySensor(activity_sensor))
+                                    .log_expect("Failed to change activity sensor");
+                                ui.close_menu();
+                            }
+                        }
+
+                        ui.separator();
+                        if ui.button("History Settings").clicked() {
+                            self.activity_settings_window.set_shown(true);
+                            ui.close_menu();
+                        }
                     });
 
                     ui.menu_button("Users", |ui| {
@@ -420,6 +624,17 @@ impl eframe::App for GwaihirApp {
                             ui.close_menu();
                         }
 
+                        let pending = FriendsWindow::num_pending_requests(&self.friendships);
+                        let friends_title = if pending > 0 {
+                            format!("Friends ({})", pending)
+                        } else {
+                            "Friends".to_string()
+                        };
+                        if ui.button(friends_title).clicked() {
+                            self.friends_window.set_shown(true);
+                            ui.close_menu();
+                        }
+
                         if ui.button("Clear ignored users").clicked() {
                             self.persistence.ignored_users.clear();
                             ui.close_menu();
@@ -429,6 +644,11 @@ impl eframe::App for GwaihirApp {
                             self.add_fake_user_window.set_shown(true);
                             ui.close_menu();
                         }
+
+                        if ui.button("Activity Timeline").clicked() {
+                            self.timeline_window.set_shown(true);
+                            ui.close_menu();
+                        }
                     });
 
                     if ui.button("Quit").clicked() {
@@ -436,6 +656,18 @@ impl eframe::App for GwaihirApp {
                     }
                 });
 
+                let status_title = ManualStatusMenu::title(self.persistence.manual_status.as_ref());
+                let status_changed = ui
+                    .menu_button(status_title, |ui| {
+                        self.manual_status_menu
+                            .show(ui, &mut self.persistence.manual_status)
+                    })
+                    .inner
+                    .unwrap_or(false);
+                if status_changed {
+                    self.republish_latest_sensor_outputs();
+                }
+
                 if cfg!(debug_assertions) {
                     ui.separator();
                     ui.label(format!("Frame: {}", ctx.frame_nr()));
@@ -444,50 +676,41 @@ impl eframe::App for GwaihirApp {
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            if self.network.is_offline() {
-                ui.label(RichText::new("⚠⚠ OFFLINE ⚠⚠").heading().color(Color32::RED));
-            }
-
-            let user_status_list = self.get_filtered_sorted_user_statuses();
+            let circles = self
+                .persistence
+                .circles
+                .iter()
+                .map(|c| (c.id, c.name.clone()))
+                .collect::<Vec<_>>();
             ScrollArea::vertical().show(ui, |ui| {
-                for (id, status) in user_status_list.iter() {
-                    let summary = self.user_summaries.get(id);
-                    ui.horizontal(|ui| {
-                        ui.spacing_mut().item_spacing.x = 2.0;
-                        if summary.is_some() {
-                            let online = Utc::now()
-                                .signed_duration_since(status.last_update)
-                                .num_minutes()
-                                < 6;
-                            OnlineStatus { online }
-                                .show(ui, id)
-                                .on_hover_text_at_pointer(last_updated_text(status));
-                        } else if let Some(s) = status.sensor_outputs.find_online_status() {
-                            s.show(ui, id);
-                        }
-                        ui.heading(status.display_name())
-                            .on_hover_text_at_pointer("Right click for options")
-                            .context_menu(|ui| {
-                                self.show_user_context_menu(id, ui, status);
-                            });
-                        if summary.is_none() {
-                            ui.label(RichText::new(format!(
-                                " {} ",
-                                HumanTime::from(status.last_update)
-                            )))
-                            .on_hover_text_at_pointer(last_updated_text(status));
-                        }
-                    });
+                for (circle_id, circle_name) in circles.iter() {
+                    egui::CollapsingHeader::new(RichText::new(circle_name).size(20.0).strong())
+                        .id_source(circle_id)
+                        .default_open(true)
+                        .show(ui, |ui| {
+                            if self.network.is_offline(circle_id) {
+                                ui.label(
+                                    RichText::new("⚠⚠ OFFLINE ⚠⚠").heading().color(Color32::RED),
+                                );
+                            }
 
-                    if let Some(summary) = summary {
-                        egui::CollapsingHeader::new(RichText::new(summary).size(15.0))
-                            .id_source(format!("{}_details", id))
-                            .show(ui, |ui| {
-                                show_sensor_status(status, ui, id);
-                            });
-                    } else {
-                        show_sensor_status(status, ui, id);
-                    }
+                            if let Some(reason) = self.rejected_updates.get(circle_id) {
+                                let dismissed = ui
+                                    .horizontal(|ui| {
+                                        ui.label(
+                                            RichText::new(format!("⚠ {}", reason))
+                                                .color(Color32::RED),
+                                        );
+                                        ui.button("Dismiss").clicked()
+                                    })
+                                    .inner;
+                                if dismissed {
+                                    self.rejected_updates.remove(circle_id);
+                                }
+                            }
+
+                            self.show_circle_users(ui, circle_id);
+                        });
                 }
             });
 
@@ -495,27 +718,215 @@ impl eframe::App for GwaihirApp {
         });
 
         self.network_window
-            .show(ctx, &mut self.network, &mut self.persistence, || {
-                self.current_status.clear();
+            .show(ctx, &mut self.network, &mut self.persistence, |circle_id| {
+                self.current_status.remove(circle_id);
+                self.current_user_ids.remove(circle_id);
+                self.friendships.remove(circle_id);
+                self.rejected_updates.remove(circle_id);
             });
-        self.transmission_spy.show(ctx);
+        let circles = self
+            .persistence
+            .circles
+            .iter()
+            .map(|c| (c.id, c.name.clone()))
+            .collect::<Vec<_>>();
+        let current_status = &self.current_status;
+        self.friends_window.show(
+            ctx,
+            &circles,
+            &self.friendships,
+            &self.network,
+            |circle_id, user_id| {
+                current_status
+                    .get(circle_id)?
+                    .get(user_id)
+                    .map(|s| s.display_name())
+            },
+        );
+        self.encryption_window
+            .show(ctx, &mut self.persistence.encryption, &mut self.network);
+        let sharing_changed = self.sharing_window.show(
+            ctx,
+            &mut self.persistence.sharing_policy,
+            &mut self.persistence.privacy_level,
+            &self.unavailable_sensors,
+        );
+        let overrides_changed = self.overrides_window.show(
+            ctx,
+            &mut self.persistence.sensor_overrides,
+            self.latest_sensor_outputs.as_ref(),
+        );
+        if sharing_changed || overrides_changed {
+            self.republish_latest_sensor_outputs();
+        }
+        if self
+            .app_rules_window
+            .show(ctx, &mut self.persistence.app_rules)
+        {
+            self.tx_to_monitor_thread
+                .send(MainToMonitorMessages::SetAppRules(
+                    self.persistence.app_rules.clone(),
+                ))
+                .log_expect("Failed to update app rules");
+        }
+        if self
+            .activity_settings_window
+            .show(ctx, &mut self.persistence.activity_settings)
+        {
+            self.tx_to_monitor_thread
+                .send(MainToMonitorMessages::SetActivitySettings(
+                    self.persistence.activity_settings,
+                ))
+                .log_expect("Failed to update activity settings");
+        }
+        if self.timeline_window.show(
+            ctx,
+            self.activity_history.as_mut(),
+            &mut self.persistence.history_settings,
+        ) {
+            self.update_activity_history();
+        }
+        self.transmission_spy
+            .show(ctx, &self.persistence.circles, &self.current_user_ids);
         self.received_data_viewer.show(ctx);
-        self.add_fake_user_window.show(ctx, |user_status| {
-            self.network
-                .queue_fake_update(RemoteUpdate::UserStatusUpdated(user_status))
-                .log_expect("Failed to queue fake user update");
-        });
+        let first_circle_id = self.persistence.circles.first().map(|c| c.id);
+        self.add_fake_user_window
+            .show(ctx, |user_status| match first_circle_id {
+                Some(circle_id) => self
+                    .network
+                    .queue_fake_update(circle_id, RemoteUpdate::UserStatusUpdated(user_status))
+                    .log_expect("Failed to queue fake user update"),
+                None => warn!("Not adding fake user since we aren't in any circles"),
+            });
         self.triggers_window
             .show(ctx, &mut self.persistence.trigger_manager, || {
-                for status in self.current_status.values() {
-                    self.network
-                        .queue_fake_update(RemoteUpdate::UserStatusUpdated(status.clone()))
-                        .log_expect("Failed to queue fake user updates following trigger update");
+                for (circle_id, statuses) in self.current_status.iter() {
+                    for status in statuses.values() {
+                        self.network
+                            .queue_fake_update(
+                                *circle_id,
+                                RemoteUpdate::UserStatusUpdated(status.clone()),
+                            )
+                            .log_expect(
+                                "Failed to queue fake user updates following trigger update",
+                            );
+                    }
                 }
             });
     }
 }
 
+impl GwaihirApp {
+    fn publish_sensor_outputs(&mut self, sensor_outputs: &SensorOutputs) {
+        let mut sensor_outputs = sensor_outputs.clone();
+        if let Some(manual_status) = self.persistence.manual_status.as_ref() {
+            sensor_outputs
+                .outputs
+                .push(SensorOutput::ManualStatus(manual_status.clone()));
+        }
+        let overridden_outputs = self
+            .persistence
+            .sensor_overrides
+            .apply(&sensor_outputs, Utc::now());
+        let shared_outputs = self.persistence.sharing_policy.apply(&overridden_outputs);
+        debug!("Publishing update: {:#?}", &shared_outputs);
+        let sent = self
+            .network
+            .publish_update(&shared_outputs, self.persistence.privacy_level);
+        if let Some(history) = self.activity_history.as_mut() {
+            history
+                .record(&HistoryUser::Me, "", Utc::now(), &shared_outputs)
+                .unwrap_or_else(|e| warn!("Failed to record own status: {}", e));
+        }
+        self.transmission_spy.set_sent(sent);
+    }
+
+    /// Learns when everyone else is usually available
+    fn sample_availability(&mut self) {
+        let own_ids = self.current_user_ids.values().collect::<Vec<_>>();
+        let statuses = self
+            .current_status
+            .values()
+            .flat_map(|statuses| statuses.values())
+            .filter(|s| !own_ids.contains(&&s.user_id))
+            .filter(|s| !self.persistence.ignored_users.contains(&s.user_id));
+        self.persistence
+            .availability_patterns
+            .sample_if_due(Utc::now(), statuses);
+    }
+
+    fn update_activity_history(&mut self) {
+        let settings = &self.persistence.history_settings;
+        match self.activity_history.as_mut() {
+            Some(_) if !settings.enabled => self.activity_history = None,
+            Some(history) => history.set_retention(settings.retention()),
+            None => self.activity_history = open_activity_history(settings),
+        }
+    }
+
+    fn republish_latest_sensor_outputs(&mut self) {
+        if let Some(sensor_outputs) = self.latest_sensor_outputs.clone() {
+            self.publish_sensor_outputs(&sensor_outputs);
+        }
+    }
+
+    fn show_circle_users(&mut self, ui: &mut egui::Ui, circle_id: &CircleId) {
+        let user_status_list = self.get_filtered_sorted_user_statuses(circle_id);
+        for (id, status) in user_status_list.iter() {
+            let summary = self.user_summaries.get(id);
+            ui.horizontal(|ui| {
+                ui.spacing_mut().item_spacing.x = 2.0;
+                if summary.is_some() {
+                    let online = Utc::now()
+                        .signed_duration_since(status.last_update)
+                        .num_minutes()
+                        < 6;
+                    OnlineStatus { online }
+                        .show(ui, id)
+                        .on_hover_text_at_pointer(last_updated_text(status));
+                } else if let Some(s) = status.sensor_outputs.find_online_status() {
+                    s.show(ui, id);
+                }
+                ui.heading(status.display_name())
+                    .on_hover_text_at_pointer("Right click for options")
+                    .context_menu(|ui| {
+                        self.show_user_context_menu(circle_id, id, ui, status);
+                    });
+                ui.add(AvailabilityBadge::new(
+                    status.sensor_outputs.get_availability_score(),
+                    self.persistence.availability_patterns.get(id),
+                ));
+                if let Some(manual_status) = status.sensor_outputs.find_manual_status() {
+                    manual_status.show(ui, id);
+                }
+                if let Some(screen_sharing) = status.sensor_outputs.find_screen_sharing() {
+                    screen_sharing.show(ui, id);
+                }
+                if let Some(call_status) = status.sensor_outputs.find_call_status() {
+                    call_status.show(ui, id);
+                }
+                if summary.is_none() {
+                    ui.label(RichText::new(format!(
+                        " {} ",
+                        HumanTime::from(status.last_update)
+                    )))
+                    .on_hover_text_at_pointer(last_updated_text(status));
+                }
+            });
+
+            if let Some(summary) = summary {
+                egui::CollapsingHeader::new(RichText::new(summary).size(15.0))
+                    .id_source(format!("{}_details", id))
+                    .show(ui, |ui| {
+                        show_sensor_status(status, ui, id);
+                    });
+            } else {
+                show_sensor_status(status, ui, id);
+            }
+        }
+    }
+}
+
 fn last_updated_text(status: &UserStatus<SensorOutputs>) -> String {
     format!(
         "Last updated: {}",
@@ -533,9 +944,17 @@ fn show_sensor_status(status: &UserStatus<SensorOutputs>, ui: &mut egui::Ui, id:
         a.show(ui, id);
     }
 
+    if let Some(i) = sensor_outputs.find_idle_time() {
+        i.show(ui, id);
+    }
+
     if let Some(u) = sensor_outputs.find_microphone_usage() {
         u.show(ui, id);
     }
+
+    if let Some(u) = sensor_outputs.find_camera_usage() {
+        u.show(ui, id);
+    }
 }
 
 impl GwaihirApp {
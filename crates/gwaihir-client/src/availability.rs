@@ -0,0 +1,238 @@
+use crate::{
+    sensor_monitor_thread::MAX_STATUS_AGE_MINUTES, sensors::outputs::sensor_outputs::SensorOutputs,
+};
+use gwaihir_client_lib::{
+    chrono::{DateTime, Datelike, Duration, Local, NaiveDateTime, Timelike, Utc, Weekday},
+    UniqueUserId, UserStatus,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+const SLOTS_PER_WEEK: usize = 7 * 24;
+/// Statuses are sampled this often, so every sample counts for the same amount of time
+const SAMPLE_INTERVAL_SECONDS: i64 = 60;
+/// An hour of the week isn't trusted until it's been sampled for at least an hour in total
+const MIN_SAMPLES_PER_SLOT: u32 = 60;
+/// The fraction of an hour's samples that must be in meetings for it to "usually" have one
+const USUAL_MEETING_FRACTION: f64 = 0.5;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default)]
+struct PatternSlot {
+    samples: u32,
+    total_score: f64,
+    meeting_samples: u32,
+}
+
+/// What a user's availability usually is during each hour of the week, in local time
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct WeeklyPattern {
+    /// Monday 00:00-01:00 first
+    slots: Vec<PatternSlot>,
+}
+
+/// The learned `WeeklyPattern` of everyone we've seen online
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct AvailabilityPatterns {
+    patterns: HashMap<UniqueUserId, WeeklyPattern>,
+    #[serde(skip)]
+    last_sampled: Option<DateTime<Utc>>,
+}
+
+impl Default for WeeklyPattern {
+    fn default() -> Self {
+        Self {
+            slots: vec![PatternSlot::default(); SLOTS_PER_WEEK],
+        }
+    }
+}
+
+impl WeeklyPattern {
+    pub fn observe(&mut self, at: NaiveDateTime, sensor_outputs: &SensorOutputs) {
+        // Guards against a pattern saved with a different shape
+        self.slots.resize(SLOTS_PER_WEEK, PatternSlot::default());
+        let slot = &mut self.slots[slot_index(at)];
+        slot.samples += 1;
+        slot.total_score += sensor_outputs.get_availability_score();
+        if sensor_outputs.is_in_meeting() {
+            slot.meeting_samples += 1;
+        }
+    }
+
+    /// *Returns:* The average availability score seen at this hour of the week, once there's
+    /// enough data to trust it
+    pub fn expected_score(&self, at: NaiveDateTime) -> Option<f64> {
+        self.slots
+            .get(slot_index(at))
+            .filter(|s| s.samples >= MIN_SAMPLES_PER_SLOT)
+            .map(|s| s.total_score / s.samples as f64)
+    }
+
+    /// *Returns:* Descriptions of the hours usually spent in meetings, like "Tue 10:00-11:00"
+    pub fn usual_meetings(&self) -> Vec<String> {
+        let is_usual_meeting = |slot: &PatternSlot| {
+            slot.samples >= MIN_SAMPLES_PER_SLOT
+                && slot.meeting_samples as f64 / slot.samples as f64 >= USUAL_MEETING_FRACTION
+        };
+
+        let mut meetings = Vec::new();
+        for (day_index, day) in self.slots.chunks(24).enumerate() {
+            let mut hour = 0;
+            while hour < day.len() {
+                if !is_usual_meeting(&day[hour]) {
+                    hour += 1;
+                    continue;
+                }
+
+                let start = hour;
+                while hour < day.len() && is_usual_meeting(&day[hour]) {
+                    hour += 1;
+                }
+                meetings.push(format!(
+                    "{} {:02}:00-{:02}:00",
+                    weekday_from_index(day_index),
+                    start,
+                    hour
+                ));
+            }
+        }
+        meetings
+    }
+}
+
+impl AvailabilityPatterns {
+    pub fn get(&self, user_id: &UniqueUserId) -> Option<&WeeklyPattern> {
+        self.patterns.get(user_id)
+    }
+
+    /// Learns from everyone's current status, at most once per sample interval
+    pub fn sample_if_due<'a>(
+        &mut self,
+        now: DateTime<Utc>,
+        statuses: impl Iterator<Item = &'a UserStatus<SensorOutputs>>,
+    ) {
+        let due = self.last_sampled.map_or(true, |t| {
+            now - t >= Duration::seconds(SAMPLE_INTERVAL_SECONDS)
+        });
+        if !due {
+            return;
+        }
+        self.last_sampled = Some(now);
+
+        let local_now = now.with_timezone(&Local).naive_local();
+        // The same user may be in several circles
+        let mut sampled = HashSet::new();
+        for status in statuses {
+            // Nothing is learned about users while they're offline
+            let online = now - status.last_update < Duration::minutes(MAX_STATUS_AGE_MINUTES);
+            if online && sampled.insert(status.user_id.clone()) {
+                self.patterns
+                    .entry(status.user_id.clone())
+                    .or_default()
+                    .observe(local_now, &status.sensor_outputs);
+            }
+        }
+    }
+}
+
+fn slot_index(at: NaiveDateTime) -> usize {
+    at.weekday().num_days_from_monday() as usize * 24 + at.hour() as usize
+}
+
+fn weekday_from_index(day_index: usize) -> Weekday {
+    (0..day_index).fold(Weekday::Mon, |day, _| day.succ())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{
+        microphone_usage::MicrophoneUsage, online_status::OnlineStatus, sensor_output::SensorOutput,
+    };
+    use gwaihir_client_lib::{
+        chrono::{NaiveDate, TimeZone},
+        Username,
+    };
+
+    fn outputs(using_microphone: bool) -> SensorOutputs {
+        let usage = if using_microphone {
+            vec!["Zoom".to_string().into()]
+        } else {
+            Vec::new()
+        };
+        SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage {
+                    usage,
+                    ..Default::default()
+                }),
+            ],
+        }
+    }
+
+    /// Every minute of `weeks` Tuesdays from 09:00 to 12:00, in a meeting from 10:00 to 11:00
+    fn tuesday_mornings(weeks: u64) -> WeeklyPattern {
+        let mut pattern = WeeklyPattern::default();
+        let first_tuesday = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        for week in 0..weeks {
+            let day = first_tuesday + Duration::weeks(week as i64);
+            for minute in (9 * 60)..(12 * 60) {
+                let at = day.and_hms_opt(minute / 60, minute % 60, 0).unwrap();
+                pattern.observe(at, &outputs((10..11).contains(&(minute / 60))));
+            }
+        }
+        pattern
+    }
+
+    #[test]
+    pub fn learns_usual_meetings() {
+        let pattern = tuesday_mornings(2);
+
+        assert_eq!(
+            pattern.usual_meetings(),
+            vec!["Tue 10:00-11:00".to_string()]
+        );
+        let tuesday = NaiveDate::from_ymd_opt(2024, 1, 16).unwrap();
+        let expected = |hour| pattern.expected_score(tuesday.and_hms_opt(hour, 30, 0).unwrap());
+        assert!(expected(10).unwrap() < expected(9).unwrap());
+        assert_eq!(expected(13), None);
+    }
+
+    #[test]
+    pub fn untrusted_until_enough_samples() {
+        let mut pattern = WeeklyPattern::default();
+        let at = NaiveDate::from_ymd_opt(2024, 1, 2)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        pattern.observe(at, &outputs(true));
+
+        assert!(pattern.usual_meetings().is_empty());
+        assert_eq!(pattern.expected_score(at), None);
+    }
+
+    #[test]
+    pub fn samples_each_online_user_once_per_interval() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 2, 10, 0, 0).unwrap();
+        let status = |id: &str, last_update| UserStatus {
+            user_id: UniqueUserId::new(id),
+            username: Username::new(id),
+            last_update,
+            sensor_outputs: outputs(false),
+        };
+        let statuses = [
+            status("alice", now),
+            // Alice again, from another circle
+            status("alice", now),
+            status("offline", now - Duration::hours(1)),
+        ];
+        let mut patterns = AvailabilityPatterns::default();
+
+        patterns.sample_if_due(now, statuses.iter());
+        patterns.sample_if_due(now + Duration::seconds(1), statuses.iter());
+
+        let alice = patterns.get(&UniqueUserId::new("alice")).unwrap();
+        assert_eq!(alice.slots.iter().map(|s| s.samples).sum::<u32>(), 1);
+        assert!(patterns.get(&UniqueUserId::new("offline")).is_none());
+    }
+}
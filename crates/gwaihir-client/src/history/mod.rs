@@ -0,0 +1,304 @@
+use crate::sensors::outputs::sensor_outputs::SensorOutputs;
+use gwaihir_client_lib::{
+    chrono::{DateTime, Duration, TimeZone, Utc},
+    UniqueUserId,
+};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use thiserror::Error;
+
+pub mod timeline;
+
+pub const HISTORY_FILE_NAME: &str = "history.sqlite3";
+
+/// How often old statuses are looked for and deleted
+const PRUNE_INTERVAL_MINUTES: i64 = 60;
+
+#[derive(Error, Debug)]
+pub enum HistoryError {
+    #[error("Activity history database error: {0}")]
+    Database(#[from] rusqlite::Error),
+    #[error("Failed to (de)serialize a recorded status: {0}")]
+    Serialization(#[from] serde_json::Error),
+    #[error("Failed to create the activity history directory: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// Recording history is opt-in, and nothing recorded ever leaves this machine
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct HistorySettings {
+    pub enabled: bool,
+    pub retention_days: u32,
+}
+
+impl HistorySettings {
+    pub fn retention(&self) -> Duration {
+        Duration::days(self.retention_days.into())
+    }
+}
+
+impl Default for HistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            retention_days: 30,
+        }
+    }
+}
+
+/// Whose status was recorded
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum HistoryUser {
+    /// What we published ourselves, which is the same for every circle
+    Me,
+    Other(UniqueUserId),
+}
+
+pub struct HistoryEntry {
+    pub recorded_at: DateTime<Utc>,
+    pub sensor_outputs: SensorOutputs,
+}
+
+/// A local SQLite store of the statuses we've received and published
+pub struct ActivityHistory {
+    conn: Connection,
+    retention: Duration,
+    last_pruned: Option<DateTime<Utc>>,
+}
+
+impl ActivityHistory {
+    pub fn open(path: &Path, retention: Duration) -> Result<Self, HistoryError> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        Self::init(Connection::open(path)?, retention)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory(retention: Duration) -> Result<Self, HistoryError> {
+        Self::init(Connection::open_in_memory()?, retention)
+    }
+
+    fn init(conn: Connection, retention: Duration) -> Result<Self, HistoryError> {
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+            PRAGMA synchronous = NORMAL;
+            CREATE TABLE IF NOT EXISTS status_history (
+                id INTEGER PRIMARY KEY,
+                -- NULL for statuses we published ourselves
+                user_id TEXT,
+                username TEXT NOT NULL,
+                recorded_at INTEGER NOT NULL,
+                sensor_outputs TEXT NOT NULL
+            );
+            -- Statuses are resent on reconnecting, which shouldn't record them twice
+            CREATE UNIQUE INDEX IF NOT EXISTS status_history_user_time
+                ON status_history (user_id, recorded_at);",
+        )?;
+        Ok(Self {
+            conn,
+            retention,
+            last_pruned: None,
+        })
+    }
+
+    pub fn set_retention(&mut self, retention: Duration) {
+        self.retention = retention;
+        self.last_pruned = None;
+    }
+
+    pub fn record(
+        &mut self,
+        user: &HistoryUser,
+        username: &str,
+        recorded_at: DateTime<Utc>,
+        sensor_outputs: &SensorOutputs,
+    ) -> Result<(), HistoryError> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO status_history (user_id, username, recorded_at, sensor_outputs)
+            VALUES (?1, ?2, ?3, ?4)",
+            params![
+                user_id_column(user),
+                username,
+                recorded_at.timestamp_millis(),
+                serde_json::to_string(sensor_outputs)?
+            ],
+        )?;
+        self.prune_if_due(Utc::now())
+    }
+
+    /// Everyone with recorded history, with the last name they were recorded under
+    pub fn users(&self) -> Result<Vec<(HistoryUser, String)>, HistoryError> {
+        let mut statement = self.conn.prepare(
+            "SELECT user_id, username FROM status_history
+            WHERE id IN (SELECT MAX(id) FROM status_history GROUP BY user_id)
+            ORDER BY user_id IS NOT NULL, username",
+        )?;
+        let users = statement
+            .query_map([], |row| {
+                let user = match row.get::<_, Option<String>>(0)? {
+                    Some(user_id) => HistoryUser::Other(UniqueUserId::new(user_id)),
+                    None => HistoryUser::Me,
+                };
+                Ok((user, row.get(1)?))
+            })?
+            .collect::<Result<_, _>>()?;
+        Ok(users)
+    }
+
+    /// *Returns:* The user's statuses recorded between `from` and `to`, oldest first. The last
+    /// status before `from` is included too, since it may still have applied at `from`.
+    pub fn entries(
+        &self,
+        user: &HistoryUser,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<HistoryEntry>, HistoryError> {
+        let user_id = user_id_column(user);
+        let to_entry = |(recorded_at, sensor_outputs): (i64, String)| -> Result<_, HistoryError> {
+            Ok(HistoryEntry {
+                recorded_at: Utc
+                    .timestamp_millis_opt(recorded_at)
+                    .single()
+                    .unwrap_or_default(),
+                sensor_outputs: serde_json::from_str(&sensor_outputs)?,
+            })
+        };
+        let read_row = |row: &rusqlite::Row<'_>| Ok((row.get(0)?, row.get(1)?));
+
+        let previous = self
+            .conn
+            .query_row(
+                "SELECT recorded_at, sensor_outputs FROM status_history
+                WHERE user_id IS ?1 AND recorded_at < ?2
+                ORDER BY recorded_at DESC LIMIT 1",
+                params![user_id, from.timestamp_millis()],
+                read_row,
+            )
+            .optional()?;
+
+        let mut statement = self.conn.prepare(
+            "SELECT recorded_at, sensor_outputs FROM status_history
+            WHERE user_id IS ?1 AND recorded_at >= ?2 AND recorded_at < ?3
+            ORDER BY recorded_at",
+        )?;
+        let rows = statement
+            .query_map(
+                params![user_id, from.timestamp_millis(), to.timestamp_millis()],
+                read_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        previous.into_iter().chain(rows).map(to_entry).collect()
+    }
+
+    pub fn clear(&mut self) -> Result<(), HistoryError> {
+        self.conn.execute("DELETE FROM status_history", [])?;
+        Ok(())
+    }
+
+    fn prune_if_due(&mut self, now: DateTime<Utc>) -> Result<(), HistoryError> {
+        let due = self.last_pruned.map_or(true, |t| {
+            now - t > Duration::minutes(PRUNE_INTERVAL_MINUTES)
+        });
+        if due {
+            self.prune(now - self.retention)?;
+            self.last_pruned = Some(now);
+        }
+        Ok(())
+    }
+
+    fn prune(&mut self, cutoff: DateTime<Utc>) -> Result<usize, HistoryError> {
+        Ok(self.conn.execute(
+            "DELETE FROM status_history WHERE recorded_at < ?1",
+            params![cutoff.timestamp_millis()],
+        )?)
+    }
+}
+
+fn user_id_column(user: &HistoryUser) -> Option<String> {
+    match user {
+        HistoryUser::Me => None,
+        HistoryUser::Other(user_id) => Some(user_id.clone().into()),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{online_status::OnlineStatus, sensor_output::SensorOutput};
+
+    fn outputs(online: bool) -> SensorOutputs {
+        SensorOutputs {
+            outputs: vec![SensorOutput::OnlineStatus(OnlineStatus { online })],
+        }
+    }
+
+    #[test]
+    pub fn entries_include_the_status_in_effect_at_the_start() {
+        let mut history = ActivityHistory::open_in_memory(Duration::days(30)).unwrap();
+        let alice = HistoryUser::Other(UniqueUserId::new("alice"));
+        let start = Utc::now() - Duration::hours(2);
+
+        history
+            .record(&alice, "Alice", start, &outputs(true))
+            .unwrap();
+        history
+            .record(&alice, "Alice", start + Duration::hours(1), &outputs(false))
+            .unwrap();
+        // Resent on reconnecting
+        history
+            .record(&alice, "Alice", start + Duration::hours(1), &outputs(false))
+            .unwrap();
+        history
+            .record(&HistoryUser::Me, "", start, &outputs(true))
+            .unwrap();
+
+        let entries = history
+            .entries(&alice, start + Duration::minutes(30), Utc::now())
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].sensor_outputs, outputs(true));
+        assert_eq!(
+            entries[1].recorded_at.timestamp_millis(),
+            (start + Duration::hours(1)).timestamp_millis()
+        );
+
+        assert_eq!(
+            history.users().unwrap(),
+            vec![
+                (HistoryUser::Me, String::new()),
+                (alice, "Alice".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    pub fn old_entries_are_pruned() {
+        let mut history = ActivityHistory::open_in_memory(Duration::days(1)).unwrap();
+        let now = Utc::now();
+
+        history
+            .record(
+                &HistoryUser::Me,
+                "",
+                now - Duration::days(2),
+                &outputs(true),
+            )
+            .unwrap();
+        history.set_retention(Duration::days(1));
+        history
+            .record(&HistoryUser::Me, "", now, &outputs(true))
+            .unwrap();
+
+        let entries = history
+            .entries(
+                &HistoryUser::Me,
+                now - Duration::days(3),
+                now + Duration::minutes(1),
+            )
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+    }
+}
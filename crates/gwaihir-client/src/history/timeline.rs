@@ -0,0 +1,139 @@
+use super::HistoryEntry;
+use crate::{
+    sensor_monitor_thread::MAX_STATUS_AGE_MINUTES,
+    sensors::outputs::{sensor_outputs::SensorOutputs, window_activity::WindowName},
+};
+use gwaihir_client_lib::chrono::{DateTime, Duration, Utc};
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimelineState {
+    pub locked: bool,
+    pub app: Option<String>,
+    /// `None` when no activity sensor was shared
+    pub active: Option<bool>,
+}
+
+/// A stretch of time a user was online and their status didn't change
+#[derive(Clone, PartialEq, Debug)]
+pub struct TimelineSpan {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub state: TimelineState,
+}
+
+impl TimelineState {
+    /// `recorded_at` is when `outputs` were current
+    pub fn new(outputs: &SensorOutputs, recorded_at: DateTime<Utc>) -> Self {
+        let locked = outputs.is_locked().unwrap_or(false);
+        let app = outputs.find_summarized_window_activity().and_then(|a| {
+            match &a.current_window.window_name {
+                WindowName::Normal(name) => Some(name.clone()),
+                WindowName::Locked => None,
+            }
+        });
+        let keyboard_mouse_active = outputs
+            .find_keyboard_mouse_activity()
+            .map(|a| a.most_recent_usage() > 0.0);
+        let idle_time_active = outputs.find_idle_time().map(|i| !i.is_idle(recorded_at));
+        TimelineState {
+            locked,
+            app,
+            active: keyboard_mouse_active.or(idle_time_active),
+        }
+    }
+}
+
+/// *Returns:* When the user was online between `from` and `to` and what they were up to, oldest
+/// first. `entries` must be sorted oldest first.
+pub fn build_timeline(
+    entries: &[HistoryEntry],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Vec<TimelineSpan> {
+    let mut spans: Vec<TimelineSpan> = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let online = entry
+            .sensor_outputs
+            .find_online_status()
+            .map_or(true, |s| s.online);
+        if !online {
+            continue;
+        }
+
+        let max_end = entry.recorded_at + Duration::minutes(MAX_STATUS_AGE_MINUTES);
+        let end = entries
+            .get(i + 1)
+            .map_or(max_end, |next| next.recorded_at.min(max_end))
+            .min(to);
+        let start = entry.recorded_at.max(from);
+        if start >= end {
+            continue;
+        }
+
+        let state = TimelineState::new(&entry.sensor_outputs, entry.recorded_at);
+        match spans.last_mut() {
+            Some(last) if last.end == start && last.state == state => last.end = end,
+            _ => spans.push(TimelineSpan { start, end, state }),
+        }
+    }
+
+    spans
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{
+        online_status::OnlineStatus, sensor_output::SensorOutput,
+        summarized_window_activity::SummarizedWindowActivity, window_activity::ActiveWindow,
+    };
+
+    fn entry(at: DateTime<Utc>, online: bool, window_name: WindowName) -> HistoryEntry {
+        HistoryEntry {
+            recorded_at: at,
+            sensor_outputs: SensorOutputs {
+                outputs: vec![
+                    SensorOutput::OnlineStatus(OnlineStatus { online }),
+                    SensorOutput::SummarizedWindowActivity(SummarizedWindowActivity {
+                        current_window: ActiveWindow {
+                            window_name,
+                            started_using: at,
+                        },
+                        current_category: None,
+                        recent_usage: Vec::new(),
+                        window_length: Duration::minutes(10),
+                    }),
+                ],
+            },
+        }
+    }
+
+    #[test]
+    pub fn spans_merge_unchanged_statuses_and_end_when_offline() {
+        let from = Utc::now() - Duration::hours(1);
+        let to = from + Duration::hours(1);
+        let code = || WindowName::Normal("VS Code".to_string());
+        let entries = vec![
+            // Started before the timeline, and still applied at its start
+            entry(from - Duration::minutes(2), true, code()),
+            entry(from + Duration::minutes(2), true, code()),
+            entry(from + Duration::minutes(4), true, WindowName::Locked),
+            entry(from + Duration::minutes(5), false, WindowName::Locked),
+            // Never resent, so it only applies for a few minutes
+            entry(from + Duration::minutes(30), true, code()),
+        ];
+
+        let spans = build_timeline(&entries, from, to);
+
+        assert_eq!(spans.len(), 3);
+        assert_eq!(spans[0].start, from);
+        assert_eq!(spans[0].end, from + Duration::minutes(4));
+        assert_eq!(spans[0].state.app, Some("VS Code".to_string()));
+        assert!(spans[1].state.locked);
+        assert_eq!(spans[1].end, from + Duration::minutes(5));
+        assert_eq!(
+            spans[2].end,
+            from + Duration::minutes(30 + MAX_STATUS_AGE_MINUTES)
+        );
+    }
+}
@@ -12,6 +12,8 @@ use sensors::lock_status_sensor::LockStatusSensorBuilder;
 use std::path::PathBuf;
 
 mod app;
+mod availability;
+mod history;
 mod networking;
 pub mod notification;
 mod periodic_repaint_thread;
@@ -0,0 +1,194 @@
+use super::peer_to_peer_settings::PeerToPeerSettings;
+use crate::sensors::{outputs::sensor_output::SensorOutputKind, privacy_level::PrivacyLevel};
+use gwaihir_client_lib::{encryption::SharedEncryption, NetworkType, UniqueUserId};
+use networking_relay::{default_credentials_dir, RelayCreationParameters, DEFAULT_PORT};
+use networking_spacetimedb::{
+    default_creds_dir, SpacetimeDBCreationParameters, DEFAULT_SPACETIMEDB_URI,
+    DEFAULT_STATUS_BACKFILL_MINUTES,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+pub type CircleId = Uuid;
+
+/// A group of users we share our status with over a single network
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Circle {
+    pub id: CircleId,
+    pub name: String,
+    pub network_type: NetworkType,
+    pub spacetimedb_db_name: String,
+    pub spacetimedb_host_uri: String,
+    pub spacetimedb_creds_dir: String,
+    /// How many minutes of our friends' past statuses are fetched on connecting
+    #[serde(default = "default_status_backfill_minutes")]
+    pub spacetimedb_backfill_minutes: u32,
+    pub peer_to_peer: PeerToPeerSettings,
+    pub relay_uri: String,
+    /// Outputs of these kinds are left out of what this circle receives. Stored as the disabled
+    /// kinds so that newly added sensors are shared unless turned off.
+    #[serde(default)]
+    pub disabled_sensors: HashSet<SensorOutputKind>,
+    /// Overrides the global privacy level for this circle
+    #[serde(default)]
+    pub privacy_level: Option<PrivacyLevel>,
+    /// Sensors shared with particular users on top of `shared_sensors()`. Only networks that can
+    /// address individual users send these.
+    #[serde(default)]
+    pub extra_sensors_by_user: HashMap<UniqueUserId, HashSet<SensorOutputKind>>,
+}
+
+/// A circle as saved before it stored its disabled sensors rather than its shared ones
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CircleV1 {
+    pub id: CircleId,
+    pub name: String,
+    pub network_type: NetworkType,
+    pub spacetimedb_db_name: String,
+    pub spacetimedb_host_uri: String,
+    pub spacetimedb_creds_dir: String,
+    pub peer_to_peer: PeerToPeerSettings,
+    pub relay_uri: String,
+    pub shared_sensors: HashSet<SensorOutputKind>,
+    #[serde(default)]
+    pub privacy_level: Option<PrivacyLevel>,
+    #[serde(default)]
+    pub extra_sensors_by_user: HashMap<UniqueUserId, HashSet<SensorOutputKind>>,
+}
+
+impl From<CircleV1> for Circle {
+    fn from(value: CircleV1) -> Self {
+        Circle {
+            id: value.id,
+            name: value.name,
+            network_type: value.network_type,
+            spacetimedb_db_name: value.spacetimedb_db_name,
+            spacetimedb_host_uri: value.spacetimedb_host_uri,
+            spacetimedb_creds_dir: value.spacetimedb_creds_dir,
+            spacetimedb_backfill_minutes: DEFAULT_STATUS_BACKFILL_MINUTES,
+            peer_to_peer: value.peer_to_peer,
+            relay_uri: value.relay_uri,
+            disabled_sensors: SensorOutputKind::all_shareable()
+                .filter(|kind| !value.shared_sensors.contains(kind))
+                .collect(),
+            privacy_level: value.privacy_level,
+            extra_sensors_by_user: value.extra_sensors_by_user,
+        }
+    }
+}
+
+impl Circle {
+    pub fn new(name: String, network_type: NetworkType) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name,
+            network_type,
+            spacetimedb_db_name: "gwaihir-test".to_string(),
+            spacetimedb_host_uri: DEFAULT_SPACETIMEDB_URI.to_string(),
+            spacetimedb_creds_dir: default_creds_dir(),
+            spacetimedb_backfill_minutes: DEFAULT_STATUS_BACKFILL_MINUTES,
+            peer_to_peer: Default::default(),
+            relay_uri: default_relay_uri(),
+            disabled_sensors: HashSet::new(),
+            privacy_level: None,
+            extra_sensors_by_user: HashMap::new(),
+        }
+    }
+
+    pub fn is_shared(&self, kind: SensorOutputKind) -> bool {
+        kind.should_send_to_remote() && !self.disabled_sensors.contains(&kind)
+    }
+
+    pub fn set_shared(&mut self, kind: SensorOutputKind, shared: bool) {
+        if shared {
+            self.disabled_sensors.remove(&kind);
+        } else {
+            self.disabled_sensors.insert(kind);
+        }
+    }
+
+    /// Every kind of output this circle receives
+    pub fn shared_sensors(&self) -> HashSet<SensorOutputKind> {
+        SensorOutputKind::all_shareable()
+            .filter(|kind| self.is_shared(*kind))
+            .collect()
+    }
+
+    pub fn spacetimedb_creation_parameters(
+        &self,
+        encryption: SharedEncryption,
+    ) -> SpacetimeDBCreationParameters {
+        SpacetimeDBCreationParameters {
+            host_uri: self.spacetimedb_host_uri.clone(),
+            db_name: self.spacetimedb_db_name.clone(),
+            creds_dir: self.spacetimedb_creds_dir.clone(),
+            encryption,
+            status_backfill_minutes: self.spacetimedb_backfill_minutes,
+        }
+    }
+
+    pub fn relay_creation_parameters(
+        &self,
+        encryption: SharedEncryption,
+    ) -> RelayCreationParameters {
+        RelayCreationParameters {
+            uri: self.relay_uri.clone(),
+            credentials_dir: default_credentials_dir(),
+            encryption,
+        }
+    }
+}
+
+impl Default for Circle {
+    fn default() -> Self {
+        Self::new("Default".to_string(), NetworkType::SpacetimeDB)
+    }
+}
+
+pub fn default_status_backfill_minutes() -> u32 {
+    DEFAULT_STATUS_BACKFILL_MINUTES
+}
+
+pub fn default_spacetimedb_host_uri() -> String {
+    DEFAULT_SPACETIMEDB_URI.to_string()
+}
+
+pub fn default_relay_uri() -> String {
+    format!("ws://localhost:{}", DEFAULT_PORT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn sensors_not_shared_by_old_circles_stay_disabled() {
+        let circle = Circle::default();
+        let old = CircleV1 {
+            id: circle.id,
+            name: circle.name,
+            network_type: circle.network_type,
+            spacetimedb_db_name: circle.spacetimedb_db_name,
+            spacetimedb_host_uri: circle.spacetimedb_host_uri,
+            spacetimedb_creds_dir: circle.spacetimedb_creds_dir,
+            peer_to_peer: circle.peer_to_peer,
+            relay_uri: circle.relay_uri,
+            shared_sensors: SensorOutputKind::all_shareable()
+                .filter(|kind| *kind != SensorOutputKind::MicrophoneUsage)
+                .collect(),
+            privacy_level: None,
+            extra_sensors_by_user: HashMap::new(),
+        };
+
+        let mut upgraded = Circle::from(old);
+
+        assert!(!upgraded.is_shared(SensorOutputKind::MicrophoneUsage));
+        assert!(upgraded.is_shared(SensorOutputKind::OnlineStatus));
+        upgraded.set_shared(SensorOutputKind::MicrophoneUsage, true);
+        assert_eq!(
+            upgraded.shared_sensors(),
+            SensorOutputKind::all_shareable().collect()
+        );
+    }
+}
@@ -0,0 +1,83 @@
+use gwaihir_client_lib::{
+    encryption::{EncryptionMode, GroupKey, KeyPair, PayloadEncryption, PublicKey},
+    UniqueUserId,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct EncryptionSettings {
+    pub enabled: bool,
+    pub mode: EncryptionMode,
+    pub key_pair: KeyPair,
+    pub trusted_keys: Vec<TrustedKey>,
+    pub group_key: Option<GroupKey>,
+}
+
+/// A friend's public key, shared with us out-of-band
+#[derive(Serialize, Deserialize, Clone)]
+pub struct TrustedKey {
+    pub name: String,
+    pub public_key: PublicKey,
+    /// Who the key belongs to, needed to share sensors with them in particular
+    #[serde(default)]
+    pub user_id: Option<UniqueUserId>,
+}
+
+pub enum Readers {
+    Everyone,
+    Friends(Vec<String>),
+    GroupKeyHolders,
+}
+
+impl EncryptionSettings {
+    /// Our keys are always included so we can read statuses encrypted to us, while `enabled`
+    /// only decides whether what we publish is encrypted
+    pub fn to_payload_encryption(&self) -> PayloadEncryption {
+        PayloadEncryption {
+            key_pair: self.key_pair.clone(),
+            recipients: self
+                .trusted_keys
+                .iter()
+                .map(|k| k.public_key.clone())
+                .collect(),
+            keys_by_user: self
+                .trusted_keys
+                .iter()
+                .filter_map(|k| Some((k.user_id.clone()?, k.public_key.clone())))
+                .collect(),
+            group_key: self.group_key.clone(),
+            mode: self.mode,
+            encrypt_published: self.enabled,
+        }
+    }
+
+    pub fn has_key_for(&self, user_id: &UniqueUserId) -> bool {
+        self.trusted_keys
+            .iter()
+            .any(|k| k.user_id.as_ref() == Some(user_id))
+    }
+
+    /// Who is able to read the statuses we publish with these settings
+    pub fn readers(&self) -> Readers {
+        if !self.enabled {
+            return Readers::Everyone;
+        }
+
+        match (self.mode, self.group_key.as_ref()) {
+            (EncryptionMode::Group, Some(_)) => Readers::GroupKeyHolders,
+            _ => Readers::Friends(self.trusted_keys.iter().map(|k| k.name.clone()).collect()),
+        }
+    }
+}
+
+impl Default for EncryptionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            mode: Default::default(),
+            key_pair: KeyPair::generate(),
+            trusted_keys: Vec::new(),
+            group_key: None,
+        }
+    }
+}
@@ -1,3 +1,6 @@
 mod backoff_executor;
+pub mod circle;
+pub mod encryption_settings;
 pub mod network_manager;
 pub mod offline_network_interface;
+pub mod peer_to_peer_settings;
@@ -1,15 +1,27 @@
 use super::{
-    backoff_executor::BackoffExecutor, offline_network_interface::OfflineNetworkInterface,
+    backoff_executor::BackoffExecutor,
+    circle::{Circle, CircleId},
+    offline_network_interface::OfflineNetworkInterface,
 };
 use crate::{
     networking::backoff_executor::BackoffExecutionAction,
-    sensors::outputs::sensor_outputs::SensorOutputs,
+    sensor_monitor_thread::UNCHANGING_SEND_UPDATE_TIME_S,
+    sensors::{
+        outputs::{sensor_output::SensorOutputKind, sensor_outputs::SensorOutputs},
+        privacy_level::PrivacyLevel,
+    },
+};
+use gwaihir_client_lib::{
+    encryption::{PayloadEncryption, SharedEncryption},
+    FriendshipAction, NetworkInterface, NetworkInterfaceCreator, NetworkType, RemoteUpdate,
+    UniqueUserId,
 };
-use delegate::delegate;
-use gwaihir_client_lib::{NetworkInterface, NetworkInterfaceCreator, NetworkType, RemoteUpdate};
 use log::{info, warn};
-use networking_spacetimedb::{SpacetimeDBCreationParameters, SpacetimeDBInterface};
+use networking_p2p::PeerToPeerInterface;
+use networking_relay::RelayInterface;
+use networking_spacetimedb::SpacetimeDBInterface;
 use std::{
+    collections::{HashMap, HashSet},
     sync::mpsc::{self, Receiver, Sender},
     time::{Duration, Instant},
 };
@@ -17,111 +29,290 @@ use std::{
 const MIN_TIME_BETWEEN_RECONNECT_ATTEMPTS: Duration = Duration::from_secs(1);
 const MAX_TIME_BETWEEN_RECONNECT_ATTEMPTS: Duration = Duration::from_secs(60);
 
+/// A remote update, tagged with the circle it was received from
+pub type CircleUpdate = (CircleId, RemoteUpdate<SensorOutputs>);
+
+/// Outputs exactly as they were published to a circle, or to one user in it
+pub struct SentUpdate {
+    pub circle_id: CircleId,
+    /// `None` for what the whole circle receives
+    pub recipient: Option<UniqueUserId>,
+    pub sensor_outputs: SensorOutputs,
+}
+
+/// Maintains one network connection per circle we belong to
 pub struct NetworkManager {
-    network: Box<dyn NetworkInterface<SensorOutputs>>,
-    network_tx: Sender<RemoteUpdate<SensorOutputs>>,
-    network_rx: Receiver<RemoteUpdate<SensorOutputs>>,
+    circles: Vec<CircleNetwork>,
+    network_tx: Sender<CircleUpdate>,
+    network_rx: Receiver<CircleUpdate>,
     egui_ctx: egui::Context,
+    encryption: SharedEncryption,
+}
+
+struct CircleNetwork {
+    circle_id: CircleId,
+    /// What the circle asked for, even if we had to fall back to being offline
+    network_type: NetworkType,
+    shared_sensors: HashSet<SensorOutputKind>,
+    privacy_level: Option<PrivacyLevel>,
+    extra_sensors_by_user: HashMap<UniqueUserId, HashSet<SensorOutputKind>>,
+    network: Box<dyn NetworkInterface<SensorOutputs> + Send>,
     backoff: BackoffExecutor,
+    /// What each user was last sent through `publish_recipient_updates`, and when, so unchanged
+    /// updates aren't sent again until it's time to show we're still around
+    last_recipient_updates: Option<(Instant, HashMap<UniqueUserId, SensorOutputs>)>,
 }
 
 impl NetworkManager {
-    pub fn new<N, P>(egui_ctx: egui::Context, network_creation_parameters: P) -> Self
-    where
-        N: NetworkInterface<SensorOutputs>
-            + NetworkInterfaceCreator<SensorOutputs, N, P>
-            + Send
-            + 'static,
-        P: Send + 'static,
-    {
+    pub fn new(egui_ctx: egui::Context, encryption: SharedEncryption, circles: &[Circle]) -> Self {
         let (network_tx, network_rx) = mpsc::channel();
-        let network = try_init_network_interface::<N, P>(
-            network_tx.clone(),
-            egui_ctx.clone(),
-            network_creation_parameters,
-        );
-        Self {
-            network,
+        let mut manager = Self {
+            circles: Vec::new(),
             network_tx,
             network_rx,
-            egui_ctx: egui_ctx.clone(),
-            backoff: BackoffExecutor::new(
-                MIN_TIME_BETWEEN_RECONNECT_ATTEMPTS,
-                MAX_TIME_BETWEEN_RECONNECT_ATTEMPTS,
-            ),
+            egui_ctx,
+            encryption,
+        };
+        for circle in circles {
+            manager.reinit_circle(circle);
         }
+
+        manager
     }
 
-    pub fn try_recv(&mut self) -> Result<RemoteUpdate<SensorOutputs>, mpsc::TryRecvError> {
+    pub fn try_recv(&mut self) -> Result<CircleUpdate, mpsc::TryRecvError> {
         self.network_rx.try_recv()
     }
 
     pub fn queue_fake_update(
         &mut self,
+        circle_id: CircleId,
         update: RemoteUpdate<SensorOutputs>,
-    ) -> Result<(), mpsc::SendError<RemoteUpdate<SensorOutputs>>> {
-        self.network_tx.send(update)
+    ) -> Result<(), mpsc::SendError<CircleUpdate>> {
+        self.network_tx.send((circle_id, update))
     }
 
     pub fn try_reconnect_if_needed(&mut self) {
-        if self.is_offline() {
-            self.backoff.maybe_execute(
-                || {
-                    let success = self.network.try_reconnect();
-                    info!("Attempting reconnection to the network");
-                    if success {
-                        info!("Successfully reconnected to the network");
-                        BackoffExecutionAction::Reset
-                    } else {
-                        warn!("Failed to reconnect to the network, trying again soon");
-                        BackoffExecutionAction::KeepTrying
-                    }
-                },
-                Instant::now(),
-            );
+        for circle in self.circles.iter_mut() {
+            if !circle.network.is_connected() {
+                let network = &mut circle.network;
+                circle.backoff.maybe_execute(
+                    || {
+                        let success = network.try_reconnect();
+                        info!("Attempting reconnection to the network");
+                        if success {
+                            info!("Successfully reconnected to the network");
+                            BackoffExecutionAction::Reset
+                        } else {
+                            warn!("Failed to reconnect to the network, trying again soon");
+                            BackoffExecutionAction::KeepTrying
+                        }
+                    },
+                    Instant::now(),
+                );
+            }
         }
     }
 
-    pub fn reinit_network(&mut self, new_network_type: NetworkType, spacetimedb_db_name: String) {
+    /// (Re)connects the given circle using its current settings, adding it if it's new.
+    /// Only one circle can use SpacetimeDB at a time, so any other circle asking for it is left
+    /// offline.
+    pub fn reinit_circle(&mut self, circle: &Circle) {
+        self.remove_circle(&circle.id);
         let network_tx = self.network_tx.clone();
         let egui_ctx = self.egui_ctx.clone();
-        self.network.disconnect();
-        match new_network_type {
-            NetworkType::Offline => self.network = get_offline_network(network_tx, egui_ctx),
-            NetworkType::SpacetimeDB => {
-                let creation_params = SpacetimeDBCreationParameters {
-                    db_name: spacetimedb_db_name,
-                };
-                self.network = try_init_network_interface::<SpacetimeDBInterface, _>(
-                    network_tx,
-                    egui_ctx,
-                    creation_params,
-                )
+        let circle_id = circle.id;
+        let mut network_type = circle.network_type.clone();
+        if network_type == NetworkType::SpacetimeDB && self.spacetimedb_circle().is_some() {
+            warn!(
+                "Circle {} can't use SpacetimeDB since another circle already is",
+                circle.name
+            );
+            network_type = NetworkType::Offline;
+        }
+        let network = match network_type {
+            NetworkType::Offline => get_offline_network(circle_id, network_tx, egui_ctx),
+            NetworkType::SpacetimeDB => try_init_network_interface::<SpacetimeDBInterface, _>(
+                circle_id,
+                network_tx,
+                egui_ctx,
+                circle.spacetimedb_creation_parameters(self.encryption.clone()),
+            ),
+            NetworkType::PeerToPeer => try_init_network_interface::<PeerToPeerInterface, _>(
+                circle_id,
+                network_tx,
+                egui_ctx,
+                circle
+                    .peer_to_peer
+                    .to_creation_parameters(self.encryption.clone()),
+            ),
+            NetworkType::Relay => try_init_network_interface::<RelayInterface, _>(
+                circle_id,
+                network_tx,
+                egui_ctx,
+                circle.relay_creation_parameters(self.encryption.clone()),
+            ),
+        };
+        self.circles.push(CircleNetwork {
+            circle_id,
+            network_type,
+            shared_sensors: circle.shared_sensors(),
+            privacy_level: circle.privacy_level,
+            extra_sensors_by_user: circle.extra_sensors_by_user.clone(),
+            network,
+            backoff: BackoffExecutor::new(
+                MIN_TIME_BETWEEN_RECONNECT_ATTEMPTS,
+                MAX_TIME_BETWEEN_RECONNECT_ATTEMPTS,
+            ),
+            last_recipient_updates: None,
+        });
+    }
+
+    /// The SpacetimeDB SDK only supports a single connection per process, so at most one circle
+    /// can use it
+    pub fn spacetimedb_circle(&self) -> Option<CircleId> {
+        self.circles
+            .iter()
+            .find(|c| c.network_type == NetworkType::SpacetimeDB)
+            .map(|c| c.circle_id)
+    }
+
+    pub fn remove_circle(&mut self, circle_id: &CircleId) {
+        if let Some(index) = self.circles.iter().position(|c| &c.circle_id == circle_id) {
+            self.circles.remove(index).network.disconnect();
+        }
+    }
+
+    /// Updates which sensors are shared with the circle and how private they are.
+    /// Takes effect from the next published update.
+    pub fn update_sharing(&mut self, circle: &Circle) {
+        if let Some(network) = self.find_circle_mut(&circle.id) {
+            network.shared_sensors = circle.shared_sensors();
+            network.privacy_level = circle.privacy_level;
+            network.extra_sensors_by_user = circle.extra_sensors_by_user.clone();
+        }
+    }
+
+    /// Takes effect immediately for both published and received statuses
+    pub fn set_encryption(&mut self, encryption: PayloadEncryption) {
+        *self.encryption.write().unwrap() = Some(encryption);
+        // The same outputs have to be encrypted for the new recipients
+        for circle in self.circles.iter_mut() {
+            circle.last_recipient_updates = None;
+        }
+    }
+
+    /// Publishes to every circle, each only receiving the outputs shared with it, and users with
+    /// extra sensors shared with them also receiving those.
+    /// `privacy_level` applies to circles that don't have their own.
+    ///
+    /// *Returns:* Everything that was sent, to which circle and user
+    pub fn publish_update(
+        &mut self,
+        sensor_outputs: &SensorOutputs,
+        privacy_level: PrivacyLevel,
+    ) -> Vec<SentUpdate> {
+        let mut sent = Vec::new();
+        for circle in self.circles.iter_mut() {
+            let privacy_level = circle.privacy_level.unwrap_or(privacy_level);
+            let circle_outputs =
+                privacy_level.apply(&sensor_outputs.filtered(&circle.shared_sensors));
+            circle.network.publish_update(circle_outputs.clone());
+            sent.push(SentUpdate {
+                circle_id: circle.circle_id,
+                recipient: None,
+                sensor_outputs: circle_outputs,
+            });
+
+            let recipient_updates: HashMap<_, _> = circle
+                .extra_sensors_by_user
+                .iter()
+                .map(|(user_id, extra_sensors)| {
+                    let shared = circle
+                        .shared_sensors
+                        .union(extra_sensors)
+                        .copied()
+                        .collect();
+                    (
+                        user_id.clone(),
+                        privacy_level.apply(&sensor_outputs.filtered(&shared)),
+                    )
+                })
+                .collect();
+            sent.extend(
+                recipient_updates
+                    .iter()
+                    .map(|(user_id, sensor_outputs)| SentUpdate {
+                        circle_id: circle.circle_id,
+                        recipient: Some(user_id.clone()),
+                        sensor_outputs: sensor_outputs.clone(),
+                    }),
+            );
+
+            let unchanged =
+                circle
+                    .last_recipient_updates
+                    .as_ref()
+                    .is_some_and(|(sent_at, last)| {
+                        sent_at.elapsed() < UNCHANGING_SEND_UPDATE_TIME_S
+                            && *last == recipient_updates
+                    });
+            if !unchanged {
+                circle.network.publish_recipient_updates(
+                    recipient_updates
+                        .iter()
+                        .map(|(user_id, outputs)| (user_id.clone(), outputs.clone()))
+                        .collect(),
+                );
+                circle.last_recipient_updates = Some((Instant::now(), recipient_updates));
             }
         }
+
+        sent
     }
 
-    pub fn is_offline(&self) -> bool {
-        !self.network.is_connected()
+    pub fn set_username(&self, circle_id: &CircleId, name: String) {
+        if let Some(circle) = self.find_circle(circle_id) {
+            circle.network.set_username(name);
+        }
     }
-}
 
-impl NetworkInterface<SensorOutputs> for NetworkManager {
-    delegate! {
-        to self.network {
-            fn publish_update(&self, sensor_outputs: SensorOutputs);
-            fn set_username(&self, name: String);
-            fn get_current_user_id(&self) -> Option<gwaihir_client_lib::UniqueUserId>;
-            fn get_network_type(&self) -> gwaihir_client_lib::NetworkType;
-            fn is_connected(&self) -> bool;
-            fn try_reconnect(&mut self) -> bool;
-            fn disconnect(&mut self);
+    pub fn update_friendship(
+        &self,
+        circle_id: &CircleId,
+        user_id: &UniqueUserId,
+        action: FriendshipAction,
+    ) {
+        if let Some(circle) = self.find_circle(circle_id) {
+            circle.network.update_friendship(user_id, action);
         }
     }
+
+    pub fn get_current_user_id(&self, circle_id: &CircleId) -> Option<UniqueUserId> {
+        self.find_circle(circle_id)?.network.get_current_user_id()
+    }
+
+    pub fn get_network_type(&self, circle_id: &CircleId) -> Option<NetworkType> {
+        Some(self.find_circle(circle_id)?.network.get_network_type())
+    }
+
+    pub fn is_offline(&self, circle_id: &CircleId) -> bool {
+        self.find_circle(circle_id)
+            .map_or(true, |c| !c.network.is_connected())
+    }
+
+    fn find_circle(&self, circle_id: &CircleId) -> Option<&CircleNetwork> {
+        self.circles.iter().find(|c| &c.circle_id == circle_id)
+    }
+
+    fn find_circle_mut(&mut self, circle_id: &CircleId) -> Option<&mut CircleNetwork> {
+        self.circles.iter_mut().find(|c| &c.circle_id == circle_id)
+    }
 }
 
 fn try_init_network_interface<N, P>(
-    network_tx: Sender<RemoteUpdate<SensorOutputs>>,
+    circle_id: CircleId,
+    network_tx: Sender<CircleUpdate>,
     egui_ctx: egui::Context,
     creation_parameters: P,
 ) -> Box<dyn NetworkInterface<SensorOutputs> + Send>
@@ -137,7 +328,7 @@ where
     run_with_timeout(
         move || {
             Box::new(N::create(
-                get_remote_update_callback(network_tx.clone(), egui_ctx.clone()),
+                get_remote_update_callback(circle_id, network_tx.clone(), egui_ctx.clone()),
                 get_on_disconnect_callback(egui_ctx),
                 creation_parameters,
             )) as Box<dyn NetworkInterface<SensorOutputs> + Send>
@@ -149,27 +340,29 @@ where
         warn!(
             "Defaulting to offline network interface because initialization of the primary failed"
         );
-        get_offline_network(network_tx_clone, ctx_clone)
+        get_offline_network(circle_id, network_tx_clone, ctx_clone)
     })
 }
 
 fn get_offline_network(
-    network_tx: Sender<RemoteUpdate<SensorOutputs>>,
+    circle_id: CircleId,
+    network_tx: Sender<CircleUpdate>,
     egui_ctx: egui::Context,
 ) -> Box<OfflineNetworkInterface<SensorOutputs>> {
     Box::new(OfflineNetworkInterface::create(
-        get_remote_update_callback(network_tx.clone(), egui_ctx.clone()),
+        get_remote_update_callback(circle_id, network_tx.clone(), egui_ctx.clone()),
         get_on_disconnect_callback(egui_ctx),
         (),
     ))
 }
 
 fn get_remote_update_callback(
-    network_tx: Sender<RemoteUpdate<SensorOutputs>>,
+    circle_id: CircleId,
+    network_tx: Sender<CircleUpdate>,
     ctx_clone: egui::Context,
 ) -> impl Fn(RemoteUpdate<SensorOutputs>) + Clone {
     move |update| {
-        network_tx.send(update).unwrap();
+        network_tx.send((circle_id, update)).unwrap();
         ctx_clone.request_repaint();
     }
 }
@@ -215,3 +408,106 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sensors::outputs::{
+        microphone_usage::MicrophoneUsage, online_status::OnlineStatus, sensor_output::SensorOutput,
+    };
+    use gwaihir_client_lib::encryption::KeyPair;
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Only counts how often recipient updates are published
+    struct RecipientUpdateCounter(Arc<AtomicUsize>);
+
+    impl NetworkInterface<SensorOutputs> for RecipientUpdateCounter {
+        fn publish_update(&self, _sensor_outputs: SensorOutputs) {}
+        fn publish_recipient_updates(&self, _updates: Vec<(UniqueUserId, SensorOutputs)>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+        fn set_username(&self, _name: String) {}
+        fn get_current_user_id(&self) -> Option<UniqueUserId> {
+            None
+        }
+        fn get_network_type(&self) -> NetworkType {
+            NetworkType::SpacetimeDB
+        }
+        fn is_connected(&self) -> bool {
+            true
+        }
+        fn try_reconnect(&mut self) -> bool {
+            true
+        }
+        fn disconnect(&mut self) {}
+    }
+
+    #[test]
+    pub fn each_circle_only_receives_what_is_shared_with_it() {
+        let everything = Circle::new("Everything".to_string(), NetworkType::Offline);
+        let mut online_only = Circle::new("Online Only".to_string(), NetworkType::Offline);
+        online_only.set_shared(SensorOutputKind::MicrophoneUsage, false);
+        let mut manager = NetworkManager::new(
+            egui::Context::default(),
+            Default::default(),
+            &[everything.clone(), online_only.clone()],
+        );
+        let outputs = SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage::default()),
+            ],
+        };
+
+        // The offline network echoes what it's sent back to us
+        let sent = manager.publish_update(&outputs, PrivacyLevel::default());
+        assert_eq!(sent.len(), 2);
+        let mut received = HashMap::new();
+        while let Ok((circle_id, update)) = manager.try_recv() {
+            let RemoteUpdate::UserStatusUpdated(status) = update else {
+                panic!("expected a status update");
+            };
+            received.insert(circle_id, status.sensor_outputs.outputs);
+        }
+
+        assert_eq!(received[&everything.id], outputs.outputs);
+        assert_eq!(
+            received[&online_only.id],
+            vec![SensorOutput::OnlineStatus(OnlineStatus { online: true })]
+        );
+    }
+
+    #[test]
+    pub fn unchanged_recipient_updates_are_not_published_again() {
+        let circle = Circle::new("Friends".to_string(), NetworkType::Offline);
+        let mut manager =
+            NetworkManager::new(egui::Context::default(), Default::default(), &[circle]);
+        let published = Arc::new(AtomicUsize::new(0));
+        manager.circles[0].network = Box::new(RecipientUpdateCounter(published.clone()));
+        manager.circles[0].extra_sensors_by_user.insert(
+            UniqueUserId::new("bob"),
+            HashSet::from([SensorOutputKind::MicrophoneUsage]),
+        );
+        let online = SensorOutputs {
+            outputs: vec![SensorOutput::OnlineStatus(OnlineStatus { online: true })],
+        };
+        let offline = SensorOutputs {
+            outputs: vec![SensorOutput::OnlineStatus(OnlineStatus { online: false })],
+        };
+
+        manager.publish_update(&online, PrivacyLevel::default());
+        manager.publish_update(&online, PrivacyLevel::default());
+        assert_eq!(published.load(Ordering::SeqCst), 1);
+
+        manager.publish_update(&offline, PrivacyLevel::default());
+        assert_eq!(published.load(Ordering::SeqCst), 2);
+
+        // A new key means the same outputs need encrypting again
+        manager.set_encryption(PayloadEncryption::new(KeyPair::generate()));
+        manager.publish_update(&offline, PrivacyLevel::default());
+        assert_eq!(published.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,36 @@
+use gwaihir_client_lib::{encryption::SharedEncryption, UniqueUserId};
+use networking_p2p::{PeerToPeerCreationParameters, DEFAULT_DISCOVERY_PORT};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PeerToPeerSettings {
+    /// Generated once, since there is no server to hand out identities
+    pub user_id: UniqueUserId,
+    pub group_name: String,
+    pub discovery_port: u16,
+}
+
+impl PeerToPeerSettings {
+    pub fn to_creation_parameters(
+        &self,
+        encryption: SharedEncryption,
+    ) -> PeerToPeerCreationParameters {
+        PeerToPeerCreationParameters {
+            user_id: self.user_id.clone(),
+            group_name: self.group_name.clone(),
+            discovery_port: self.discovery_port,
+            encryption,
+        }
+    }
+}
+
+impl Default for PeerToPeerSettings {
+    fn default() -> Self {
+        Self {
+            user_id: UniqueUserId::new(Uuid::new_v4().simple().to_string()[..16].to_string()),
+            group_name: "gwaihir".to_string(),
+            discovery_port: DEFAULT_DISCOVERY_PORT,
+        }
+    }
+}
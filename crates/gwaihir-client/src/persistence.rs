@@ -1,23 +1,76 @@
-use crate::triggers::{TriggerManager, TriggerManagerV1, VersionedTriggerManager};
-use gwaihir_client_lib::UniqueUserId;
+use crate::{
+    availability::AvailabilityPatterns,
+    history::HistorySettings,
+    networking::{
+        circle::{default_relay_uri, default_spacetimedb_host_uri, Circle, CircleV1},
+        encryption_settings::EncryptionSettings,
+        peer_to_peer_settings::PeerToPeerSettings,
+    },
+    sensors::{
+        activity_settings::ActivitySettings,
+        app_rules::AppRules,
+        outputs::{manual_status::ManualStatus, sensor_output::SensorOutputKind},
+        overrides::SensorOverrides,
+        privacy_level::PrivacyLevel,
+        sharing_policy::SharingPolicy,
+        ActivitySensorType,
+    },
+    triggers::{TriggerManager, TriggerManagerV1, VersionedTriggerManager},
+};
+use gwaihir_client_lib::{NetworkType, UniqueUserId};
+use networking_spacetimedb::default_creds_dir;
 use pro_serde_versioned::{Upgrade, VersionedUpgrade};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
 
 #[derive(Serialize, Deserialize, Clone)]
 #[serde(into = "VersionedPersistence", from = "VersionedPersistence")]
 pub struct Persistence {
     pub ignored_users: HashSet<UniqueUserId>,
-    pub spacetimedb_db_name: String,
+    pub circles: Vec<Circle>,
 
     #[serde(default)]
     pub trigger_manager: TriggerManager,
+
+    #[serde(default)]
+    pub encryption: EncryptionSettings,
+
+    #[serde(default)]
+    pub sharing_policy: SharingPolicy,
+
+    #[serde(default)]
+    pub sensor_overrides: SensorOverrides,
+
+    #[serde(default)]
+    pub manual_status: Option<ManualStatus>,
+
+    #[serde(default)]
+    pub activity_sensor: ActivitySensorType,
+
+    #[serde(default)]
+    pub app_rules: AppRules,
+
+    #[serde(default)]
+    pub privacy_level: PrivacyLevel,
+
+    #[serde(default)]
+    pub activity_settings: ActivitySettings,
+
+    #[serde(default)]
+    pub history_settings: HistorySettings,
+
+    #[serde(default)]
+    pub availability_patterns: AvailabilityPatterns,
 }
 
 #[derive(Serialize, Deserialize, VersionedUpgrade, Clone)]
+#[allow(clippy::large_enum_variant)] // Only short-lived while (de)serializing
 pub enum VersionedPersistence {
     V1(PersistenceV1),
     V2(PersistenceV2),
+    V3(PersistenceV3),
+    V4(PersistenceV4),
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -33,7 +86,93 @@ pub struct PersistenceV1 {
 pub struct PersistenceV2 {
     pub ignored_users: HashSet<UniqueUserId>,
     pub spacetimedb_db_name: String,
+
+    #[serde(default = "default_spacetimedb_host_uri")]
+    pub spacetimedb_host_uri: String,
+
+    #[serde(default = "default_creds_dir")]
+    pub spacetimedb_creds_dir: String,
+
+    pub trigger_manager: TriggerManager,
+
+    #[serde(default)]
+    pub encryption: EncryptionSettings,
+
+    #[serde(default)]
+    pub peer_to_peer: PeerToPeerSettings,
+
+    #[serde(default = "default_relay_uri")]
+    pub relay_uri: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PersistenceV3 {
+    pub ignored_users: HashSet<UniqueUserId>,
+    pub circles: Vec<CircleV1>,
+    pub trigger_manager: TriggerManager,
+    pub encryption: EncryptionSettings,
+
+    #[serde(default)]
+    pub sharing_policy: SharingPolicy,
+
+    #[serde(default)]
+    pub sensor_overrides: SensorOverrides,
+
+    #[serde(default)]
+    pub manual_status: Option<ManualStatus>,
+
+    #[serde(default)]
+    pub activity_sensor: ActivitySensorType,
+
+    #[serde(default)]
+    pub app_rules: AppRules,
+
+    #[serde(default)]
+    pub privacy_level: PrivacyLevel,
+
+    #[serde(default)]
+    pub activity_settings: ActivitySettings,
+
+    #[serde(default)]
+    pub history_settings: HistorySettings,
+
+    #[serde(default)]
+    pub availability_patterns: AvailabilityPatterns,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PersistenceV4 {
+    pub ignored_users: HashSet<UniqueUserId>,
+    pub circles: Vec<Circle>,
     pub trigger_manager: TriggerManager,
+    pub encryption: EncryptionSettings,
+
+    #[serde(default)]
+    pub sharing_policy: SharingPolicy,
+
+    #[serde(default)]
+    pub sensor_overrides: SensorOverrides,
+
+    #[serde(default)]
+    pub manual_status: Option<ManualStatus>,
+
+    #[serde(default)]
+    pub activity_sensor: ActivitySensorType,
+
+    #[serde(default)]
+    pub app_rules: AppRules,
+
+    #[serde(default)]
+    pub privacy_level: PrivacyLevel,
+
+    #[serde(default)]
+    pub activity_settings: ActivitySettings,
+
+    #[serde(default)]
+    pub history_settings: HistorySettings,
+
+    #[serde(default)]
+    pub availability_patterns: AvailabilityPatterns,
 }
 
 impl Upgrade<PersistenceV2> for PersistenceV1 {
@@ -41,17 +180,86 @@ impl Upgrade<PersistenceV2> for PersistenceV1 {
         PersistenceV2 {
             ignored_users: self.ignored_users,
             spacetimedb_db_name: self.spacetimedb_db_name,
+            spacetimedb_host_uri: default_spacetimedb_host_uri(),
+            spacetimedb_creds_dir: default_creds_dir(),
             trigger_manager: VersionedTriggerManager::V1(self.trigger_manager).into(),
+            encryption: Default::default(),
+            peer_to_peer: Default::default(),
+            relay_uri: default_relay_uri(),
+        }
+    }
+}
+
+impl Upgrade<PersistenceV3> for PersistenceV2 {
+    fn upgrade(self) -> PersistenceV3 {
+        // The single network we used to connect to becomes our only circle
+        let circle = CircleV1 {
+            id: Uuid::new_v4(),
+            name: "Default".to_string(),
+            network_type: NetworkType::SpacetimeDB,
+            spacetimedb_db_name: self.spacetimedb_db_name,
+            spacetimedb_host_uri: self.spacetimedb_host_uri,
+            spacetimedb_creds_dir: self.spacetimedb_creds_dir,
+            peer_to_peer: self.peer_to_peer,
+            relay_uri: self.relay_uri,
+            shared_sensors: SensorOutputKind::all_shareable().collect(),
+            privacy_level: None,
+            extra_sensors_by_user: HashMap::new(),
+        };
+        PersistenceV3 {
+            ignored_users: self.ignored_users,
+            circles: vec![circle],
+            trigger_manager: self.trigger_manager,
+            encryption: self.encryption,
+            sharing_policy: Default::default(),
+            sensor_overrides: Default::default(),
+            manual_status: None,
+            activity_sensor: Default::default(),
+            app_rules: Default::default(),
+            privacy_level: Default::default(),
+            activity_settings: Default::default(),
+            history_settings: Default::default(),
+            availability_patterns: Default::default(),
+        }
+    }
+}
+
+impl Upgrade<PersistenceV4> for PersistenceV3 {
+    fn upgrade(self) -> PersistenceV4 {
+        PersistenceV4 {
+            ignored_users: self.ignored_users,
+            circles: self.circles.into_iter().map(Circle::from).collect(),
+            trigger_manager: self.trigger_manager,
+            encryption: self.encryption,
+            sharing_policy: self.sharing_policy,
+            sensor_overrides: self.sensor_overrides,
+            manual_status: self.manual_status,
+            activity_sensor: self.activity_sensor,
+            app_rules: self.app_rules,
+            privacy_level: self.privacy_level,
+            activity_settings: self.activity_settings,
+            history_settings: self.history_settings,
+            availability_patterns: self.availability_patterns,
         }
     }
 }
 
 impl From<Persistence> for VersionedPersistence {
     fn from(value: Persistence) -> Self {
-        VersionedPersistence::V2(PersistenceV2 {
+        VersionedPersistence::V4(PersistenceV4 {
             ignored_users: value.ignored_users,
-            spacetimedb_db_name: value.spacetimedb_db_name,
+            circles: value.circles,
             trigger_manager: value.trigger_manager,
+            encryption: value.encryption,
+            sharing_policy: value.sharing_policy,
+            sensor_overrides: value.sensor_overrides,
+            manual_status: value.manual_status,
+            activity_sensor: value.activity_sensor,
+            app_rules: value.app_rules,
+            privacy_level: value.privacy_level,
+            activity_settings: value.activity_settings,
+            history_settings: value.history_settings,
+            availability_patterns: value.availability_patterns,
         })
     }
 }
@@ -61,8 +269,18 @@ impl From<VersionedPersistence> for Persistence {
         let upgraded = value.upgrade_to_latest();
         Persistence {
             ignored_users: upgraded.ignored_users,
-            spacetimedb_db_name: upgraded.spacetimedb_db_name,
+            circles: upgraded.circles,
             trigger_manager: upgraded.trigger_manager,
+            encryption: upgraded.encryption,
+            sharing_policy: upgraded.sharing_policy,
+            sensor_overrides: upgraded.sensor_overrides,
+            manual_status: upgraded.manual_status,
+            activity_sensor: upgraded.activity_sensor,
+            app_rules: upgraded.app_rules,
+            privacy_level: upgraded.privacy_level,
+            activity_settings: upgraded.activity_settings,
+            history_settings: upgraded.history_settings,
+            availability_patterns: upgraded.availability_patterns,
         }
     }
 }
@@ -74,9 +292,19 @@ impl Persistence {
 impl Default for Persistence {
     fn default() -> Self {
         Self {
-            spacetimedb_db_name: "gwaihir-test".to_string(),
             ignored_users: Default::default(),
+            circles: vec![Circle::default()],
             trigger_manager: Default::default(),
+            encryption: Default::default(),
+            sharing_policy: Default::default(),
+            sensor_overrides: Default::default(),
+            manual_status: None,
+            activity_sensor: Default::default(),
+            app_rules: Default::default(),
+            privacy_level: Default::default(),
+            activity_settings: Default::default(),
+            history_settings: Default::default(),
+            availability_patterns: Default::default(),
         }
     }
 }
@@ -1,42 +1,65 @@
 use crate::sensors::lock_status_sensor::LockStatusSensor;
-use crate::sensors::microphone_usage_sensor;
 use crate::sensors::{
+    activity_settings::ActivitySettings,
+    app_rules::{AppRules, CompiledAppRules},
     keyboard_mouse_event_provider::RdevKeyboardMouseEventProvider,
     keyboard_mouse_sensor::{KeyboardMouseSensor, ShutdownMessage},
-    outputs::{sensor_output::SensorOutput, sensor_outputs::SensorOutputs},
+    outputs::{
+        sensor_output::{SensorOutput, SensorOutputKind},
+        sensor_outputs::SensorOutputs,
+    },
     window_activity_interpreter::WindowActivityInterpreter,
-    Sensor,
+    ActivitySensorType, Sensor,
+};
+use crate::sensors::{
+    call_status_sensor::CallStatusSensor, camera_usage_sensor, idle_time_sensor,
+    microphone_usage_sensor, screen_sharing_sensor,
 };
 use log::{info, warn};
 use std::time::Instant;
 use std::{
+    cell::{Cell, RefCell},
     ops::ControlFlow,
+    rc::Rc,
     sync::mpsc::{channel, Receiver, Sender, TryRecvError},
     thread::{sleep, JoinHandle},
     time::Duration,
 };
 
 const THREAD_SLEEP_DURATION_MS: u64 = 50;
-const UNCHANGING_SEND_UPDATE_TIME_S: Duration = Duration::from_secs(60 * 5);
+pub const UNCHANGING_SEND_UPDATE_TIME_S: Duration = Duration::from_secs(60 * 5);
+/// Statuses are resent every `UNCHANGING_SEND_UPDATE_TIME_S` even when nothing changed, so one
+/// older than this, which leaves a minute for delays, means the user went offline
+pub const MAX_STATUS_AGE_MINUTES: i64 = UNCHANGING_SEND_UPDATE_TIME_S.as_secs() as i64 / 60 + 1;
 
 pub enum MainToMonitorMessages {
     Shutdown,
     SetEguiContext(egui::Context),
     LockStatusSensorInitialized(LockStatusSensor),
+    SetActivitySensor(ActivitySensorType),
+    SetAppRules(AppRules),
+    SetActivitySettings(ActivitySettings),
 }
 
 #[derive(Debug)]
 pub enum MonitorToMainMessages {
     UpdatedSensorOutputs(SensorOutputs),
+    /// Explains why a sensor's output will be missing
+    SensorUnavailable(SensorOutputKind, String),
 }
 
 struct SensorMonitor {
     rx_from_main: Receiver<MainToMonitorMessages>,
     tx_to_main: Sender<MonitorToMainMessages>,
-    tx_to_keyboard_mouse_listener: Sender<ShutdownMessage>,
+    tx_to_keyboard_mouse_listener: Option<Sender<ShutdownMessage>>,
     egui_ctx: Option<egui::Context>,
 
     sensors: Vec<(Box<dyn Sensor>, SensorOutput)>,
+    /// Kept apart from the other sensors so it can be swapped out
+    activity_sensor: Option<(Box<dyn Sensor>, SensorOutput)>,
+    activity_sensor_type: Option<ActivitySensorType>,
+    activity_settings: Rc<Cell<ActivitySettings>>,
+    app_rules: Rc<RefCell<CompiledAppRules>>,
     last_sent_outputs: Vec<SensorOutput>,
     last_sent_time: Instant,
 }
@@ -61,29 +84,57 @@ impl SensorMonitor {
         rx_from_main: Receiver<MainToMonitorMessages>,
         tx_to_main: Sender<MonitorToMainMessages>,
     ) -> Self {
-        let (keyboard_mouse_sensor, tx_to_keyboard_mouse_listener) =
-            KeyboardMouseSensor::new(RdevKeyboardMouseEventProvider::new());
-
-        let mut sensors: Vec<(Box<dyn Sensor>, SensorOutput)> = vec![
-            (
-                Box::new(WindowActivityInterpreter::new()),
-                SensorOutput::Empty,
-            ),
-            (Box::new(keyboard_mouse_sensor), SensorOutput::Empty),
-        ];
-
-        if let Some(sensor) = microphone_usage_sensor::try_get_sensor() {
-            sensors.push((sensor, SensorOutput::MicrophoneUsage(Default::default())));
+        let app_rules = Rc::new(RefCell::new(CompiledAppRules::default()));
+        let activity_settings = Rc::new(Cell::new(ActivitySettings::default()));
+        let mut sensors: Vec<(Box<dyn Sensor>, SensorOutput)> = vec![(
+            Box::new(WindowActivityInterpreter::new(
+                app_rules.clone(),
+                activity_settings.clone(),
+            )),
+            SensorOutput::Empty,
+        )];
+
+        let mut add_sensor_if_available =
+            |sensor: Result<Box<dyn Sensor>, String>, initial_output: SensorOutput| match sensor {
+                Ok(sensor) => sensors.push((sensor, initial_output)),
+                Err(reason) => {
+                    let kind = initial_output.kind();
+                    warn!("{} is unavailable: {}", kind, reason);
+                    tx_to_main
+                        .send(MonitorToMainMessages::SensorUnavailable(kind, reason))
+                        .unwrap();
+                }
+            };
+        add_sensor_if_available(
+            microphone_usage_sensor::try_get_sensor().map_err(|e| e.to_string()),
+            SensorOutput::MicrophoneUsage(Default::default()),
+        );
+        add_sensor_if_available(
+            screen_sharing_sensor::try_get_sensor().map_err(|e| e.to_string()),
+            SensorOutput::ScreenSharing(Default::default()),
+        );
+
+        if let Some(sensor) = camera_usage_sensor::try_get_sensor() {
+            sensors.push((sensor, SensorOutput::CameraUsage(Default::default())));
         }
 
+        sensors.push((
+            Box::new(CallStatusSensor::new()),
+            SensorOutput::CallStatus(Default::default()),
+        ));
+
         SensorMonitor {
             rx_from_main,
             tx_to_main,
             egui_ctx: None,
 
-            tx_to_keyboard_mouse_listener,
+            tx_to_keyboard_mouse_listener: None,
 
             sensors,
+            activity_sensor: None,
+            activity_sensor_type: None,
+            activity_settings,
+            app_rules,
             last_sent_outputs: Vec::new(),
             last_sent_time: Instant::now(),
         }
@@ -134,7 +185,11 @@ impl SensorMonitor {
                         .collect(),
                 }))
                 .unwrap();
-            for (sensor, _) in self.sensors.iter_mut() {
+            for (sensor, _) in self
+                .sensors
+                .iter_mut()
+                .chain(self.activity_sensor.iter_mut())
+            {
                 sensor.updated_sensor_outputs(&snapshot);
             }
             self.last_sent_time = Instant::now();
@@ -146,7 +201,11 @@ impl SensorMonitor {
     }
 
     fn check_sensor_updates(&mut self) -> bool {
-        for (sensor, old_output) in self.sensors.iter_mut() {
+        for (sensor, old_output) in self
+            .sensors
+            .iter_mut()
+            .chain(self.activity_sensor.iter_mut())
+        {
             let updated_output = sensor.as_mut().get_output();
             *old_output = updated_output;
         }
@@ -157,12 +216,7 @@ impl SensorMonitor {
     fn process_msg(&mut self, msg: MainToMonitorMessages) -> ControlFlow<()> {
         match msg {
             MainToMonitorMessages::Shutdown => {
-                info!("Sending shutdown message to keyboard/mouse listener");
-                self.tx_to_keyboard_mouse_listener
-                    .send(ShutdownMessage {})
-                    .unwrap_or_else(|_e| {
-                        warn!("Failed to send shutdown message to keyboard/mouse listener")
-                    });
+                self.shutdown_keyboard_mouse_listener();
                 return ControlFlow::Break(());
             }
             MainToMonitorMessages::SetEguiContext(ctx) => {
@@ -174,14 +228,62 @@ impl SensorMonitor {
                     SensorOutput::LockStatus(Default::default()),
                 ));
             }
+            MainToMonitorMessages::SetActivitySensor(activity_sensor_type) => {
+                self.set_activity_sensor(activity_sensor_type);
+            }
+            MainToMonitorMessages::SetAppRules(app_rules) => {
+                *self.app_rules.borrow_mut() = app_rules.compile();
+            }
+            MainToMonitorMessages::SetActivitySettings(settings) => {
+                self.set_activity_settings(settings);
+            }
         }
 
         ControlFlow::Continue(())
     }
 
+    fn set_activity_sensor(&mut self, activity_sensor_type: ActivitySensorType) {
+        info!("Using {} to detect activity", activity_sensor_type);
+        self.shutdown_keyboard_mouse_listener();
+        self.activity_sensor_type = Some(activity_sensor_type);
+        let sensor: Option<Box<dyn Sensor>> = match activity_sensor_type {
+            ActivitySensorType::KeyboardMouse => {
+                let (sensor, tx_to_listener) = KeyboardMouseSensor::new(
+                    RdevKeyboardMouseEventProvider::new(),
+                    self.activity_settings.get(),
+                );
+                self.tx_to_keyboard_mouse_listener = Some(tx_to_listener);
+                Some(Box::new(sensor))
+            }
+            ActivitySensorType::IdleTime => idle_time_sensor::try_get_sensor(),
+        };
+        self.activity_sensor = sensor.map(|sensor| (sensor, SensorOutput::Empty));
+    }
+
+    fn set_activity_settings(&mut self, settings: ActivitySettings) {
+        let old_settings = self.activity_settings.replace(settings);
+        let buckets_changed = old_settings.bucket_duration_seconds
+            != settings.bucket_duration_seconds
+            || old_settings.num_buckets != settings.num_buckets;
+        // The keyboard/mouse history can't be rebucketed, so it starts over
+        if buckets_changed && self.activity_sensor_type == Some(ActivitySensorType::KeyboardMouse) {
+            self.set_activity_sensor(ActivitySensorType::KeyboardMouse);
+        }
+    }
+
+    fn shutdown_keyboard_mouse_listener(&mut self) {
+        if let Some(tx) = self.tx_to_keyboard_mouse_listener.take() {
+            info!("Sending shutdown message to keyboard/mouse listener");
+            tx.send(ShutdownMessage {}).unwrap_or_else(|_e| {
+                warn!("Failed to send shutdown message to keyboard/mouse listener")
+            });
+        }
+    }
+
     fn get_sensor_output_snapshot(&self) -> Vec<SensorOutput> {
         self.sensors
             .iter()
+            .chain(self.activity_sensor.iter())
             .map(|(_, output)| output.clone())
             .collect()
     }
@@ -202,6 +304,9 @@ mod tests {
         let (main_to_monitor_tx, main_to_monitor_rx) = channel();
         let (monitor_to_main_tx, monitor_to_main_rx) = channel();
         let monitor = SensorMonitor::new(main_to_monitor_rx, monitor_to_main_tx);
+        // Which sensors are unavailable depends on the machine running the tests
+        while let Ok(MonitorToMainMessages::SensorUnavailable(..)) = monitor_to_main_rx.try_recv() {
+        }
         MonitorAndChannels {
             monitor,
             main_to_monitor_tx,
@@ -255,4 +360,47 @@ mod tests {
             Ok(MonitorToMainMessages::UpdatedSensorOutputs(_))
         );
     }
+
+    #[test]
+    fn set_activity_sensor_replaces_previous_activity_sensor() {
+        let mut mc = init_monitor_and_flush_initial_messages();
+
+        mc.main_to_monitor_tx
+            .send(MainToMonitorMessages::SetActivitySensor(
+                ActivitySensorType::KeyboardMouse,
+            ))
+            .unwrap();
+        mc.monitor.loop_body();
+        assert!(mc.monitor.tx_to_keyboard_mouse_listener.is_some());
+
+        mc.main_to_monitor_tx
+            .send(MainToMonitorMessages::SetActivitySensor(
+                ActivitySensorType::IdleTime,
+            ))
+            .unwrap();
+        mc.monitor.loop_body();
+        assert!(mc.monitor.tx_to_keyboard_mouse_listener.is_none());
+        assert_eq!(
+            mc.monitor.get_sensor_output_snapshot().len(),
+            mc.monitor.sensors.len() + usize::from(mc.monitor.activity_sensor.is_some())
+        );
+    }
+
+    #[test]
+    fn activity_settings_are_shared_without_starting_an_activity_sensor() {
+        let mut mc = init_monitor_and_flush_initial_messages();
+        let settings = ActivitySettings {
+            num_buckets: 60,
+            ..Default::default()
+        };
+
+        mc.main_to_monitor_tx
+            .send(MainToMonitorMessages::SetActivitySettings(settings))
+            .unwrap();
+        mc.monitor.loop_body();
+
+        assert_eq!(mc.monitor.activity_settings.get(), settings);
+        assert!(mc.monitor.activity_sensor.is_none());
+        assert!(mc.monitor.tx_to_keyboard_mouse_listener.is_none());
+    }
 }
@@ -0,0 +1,90 @@
+use super::{ActiveWindowProvider, RawActiveWindow};
+use crate::sensors::outputs::window_activity::WindowName;
+use log::warn;
+use serde::Deserialize;
+use zbus::blocking::{Connection, Proxy};
+
+/// Provided by the "Window Calls" GNOME Shell extension, since GNOME doesn't expose the focused
+/// window itself and `org.gnome.Shell.Eval` is disabled by default
+const WINDOW_CALLS_DESTINATION: &str = "org.gnome.Shell";
+const WINDOW_CALLS_PATH: &str = "/org/gnome/Shell/Extensions/Windows";
+const WINDOW_CALLS_INTERFACE: &str = "org.gnome.Shell.Extensions.Windows";
+
+#[derive(Deserialize)]
+struct GnomeWindow {
+    wm_class: Option<String>,
+    #[serde(default)]
+    focus: bool,
+}
+
+pub struct GnomeShellWindowProvider {
+    proxy: Proxy<'static>,
+}
+
+impl GnomeShellWindowProvider {
+    pub fn new() -> Option<Self> {
+        let proxy = Connection::session().and_then(|conn| {
+            Proxy::new_owned(
+                conn,
+                WINDOW_CALLS_DESTINATION,
+                WINDOW_CALLS_PATH,
+                WINDOW_CALLS_INTERFACE,
+            )
+        });
+        let provider = match proxy {
+            Ok(proxy) => Self { proxy },
+            Err(e) => {
+                warn!("Failed to connect to GNOME Shell: {}", e);
+                return None;
+            }
+        };
+
+        if let Err(e) = provider.list_windows() {
+            warn!(
+                "Failed to list windows from GNOME Shell, is the Window Calls extension installed? {}",
+                e
+            );
+            return None;
+        }
+        Some(provider)
+    }
+
+    fn list_windows(&self) -> zbus::Result<String> {
+        self.proxy.call("List", &())
+    }
+}
+
+impl ActiveWindowProvider for GnomeShellWindowProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        let windows = self.list_windows().map_err(|_| ())?;
+        find_focused_window(&windows)
+            .map(|wm_class| RawActiveWindow {
+                window_name: WindowName::Normal(wm_class),
+            })
+            .ok_or(())
+    }
+}
+
+fn find_focused_window(windows_json: &str) -> Option<String> {
+    let windows: Vec<GnomeWindow> = serde_json::from_str(windows_json).ok()?;
+    windows
+        .into_iter()
+        .find(|w| w.focus)
+        .and_then(|w| w.wm_class)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn find_focused_window_uses_wm_class_of_focused_window() {
+        let windows = r#"[
+            {"wm_class": "org.gnome.Nautilus", "pid": 1, "focus": false},
+            {"wm_class": "firefox", "pid": 2, "focus": true}
+        ]"#;
+
+        assert_eq!(find_focused_window(windows), Some("firefox".to_string()));
+        assert_eq!(find_focused_window("[]"), None);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{ActiveWindowProvider, RawActiveWindow, ReportedActiveWindow};
+use gwaihir_client_lib::APP_ID;
+use log::warn;
+use std::path::PathBuf;
+use zbus::{blocking::Connection, dbus_interface};
+
+const KWIN_DESTINATION: &str = "org.kde.KWin";
+const KWIN_SCRIPTING_PATH: &str = "/Scripting";
+const KWIN_SCRIPTING_INTERFACE: &str = "org.kde.kwin.Scripting";
+
+const RECEIVER_PATH: &str = "/ActiveWindow";
+const RECEIVER_INTERFACE: &str = "io.github.gwaihir.ActiveWindow";
+
+/// Supports both KWin 5 (`clientActivated`/`activeClient`) and KWin 6
+/// (`windowActivated`/`activeWindow`). `service` is our unique bus name.
+fn kwin_script(service: &str) -> String {
+    format!(
+        r#"
+function report(window) {{
+    callDBus("{service}", "{RECEIVER_PATH}", "{RECEIVER_INTERFACE}", "Activated",
+        window ? String(window.resourceClass) : "");
+}}
+var activated = workspace.windowActivated || workspace.clientActivated;
+activated.connect(report);
+report(workspace.activeWindow || workspace.activeClient);
+"#
+    )
+}
+
+/// KWin doesn't let clients ask for the active window, but it runs scripts which can tell us
+/// about it over D-Bus whenever it changes
+pub struct KWinWindowProvider {
+    conn: Connection,
+    active_window: ReportedActiveWindow,
+}
+
+struct ActiveWindowReceiver {
+    active_window: ReportedActiveWindow,
+}
+
+#[dbus_interface(name = "io.github.gwaihir.ActiveWindow")]
+impl ActiveWindowReceiver {
+    fn activated(&self, resource_class: String) {
+        self.active_window
+            .set(Some(resource_class).filter(|c| !c.is_empty()));
+    }
+}
+
+impl KWinWindowProvider {
+    pub fn new() -> Option<Self> {
+        match Self::load_script() {
+            Ok(provider) => Some(provider),
+            Err(e) => {
+                warn!("Failed to load the active window KWin script: {}", e);
+                None
+            }
+        }
+    }
+
+    fn load_script() -> zbus::Result<Self> {
+        let conn = Connection::session()?;
+        let active_window = serve_receiver(&conn)?;
+
+        let service = conn
+            .unique_name()
+            .ok_or_else(|| zbus::Error::Failure("Not connected to a message bus".to_string()))?;
+        let script_path = script_path();
+        std::fs::write(&script_path, kwin_script(service.as_str()))
+            .map_err(|e| zbus::Error::Failure(e.to_string()))?;
+
+        // A previous run may not have cleaned up after itself
+        call_kwin_scripting(&conn, "unloadScript", &(APP_ID,))?;
+        call_kwin_scripting(
+            &conn,
+            "loadScript",
+            &(script_path.to_string_lossy().as_ref(), APP_ID),
+        )?;
+        call_kwin_scripting(&conn, "start", &())?;
+
+        Ok(Self {
+            conn,
+            active_window,
+        })
+    }
+}
+
+impl Drop for KWinWindowProvider {
+    fn drop(&mut self) {
+        let _ = call_kwin_scripting(&self.conn, "unloadScript", &(APP_ID,));
+        let _ = std::fs::remove_file(script_path());
+    }
+}
+
+impl ActiveWindowProvider for KWinWindowProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        self.active_window.get_active_window()
+    }
+}
+
+fn script_path() -> PathBuf {
+    std::env::temp_dir().join(format!("{}-active-window.js", APP_ID))
+}
+
+fn call_kwin_scripting<B>(conn: &Connection, method: &str, body: &B) -> zbus::Result<()>
+where
+    B: serde::Serialize + zbus::zvariant::DynamicType,
+{
+    conn.call_method(
+        Some(KWIN_DESTINATION),
+        KWIN_SCRIPTING_PATH,
+        Some(KWIN_SCRIPTING_INTERFACE),
+        method,
+        body,
+    )
+    .map(|_| ())
+}
+
+/// Exposes the object our KWin script reports to
+fn serve_receiver(conn: &Connection) -> zbus::Result<ReportedActiveWindow> {
+    let active_window = ReportedActiveWindow::default();
+    conn.object_server().at(
+        RECEIVER_PATH,
+        ActiveWindowReceiver {
+            active_window: active_window.clone(),
+        },
+    )?;
+    Ok(active_window)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::dbus_test_utils::private_bus;
+
+    #[test]
+    pub fn receiver_tracks_reported_active_window() {
+        let (kwin, client) = private_bus();
+        let active_window = serve_receiver(&client).unwrap();
+
+        let report = |resource_class: &str| {
+            kwin.call_method(
+                None::<()>,
+                RECEIVER_PATH,
+                Some(RECEIVER_INTERFACE),
+                "Activated",
+                &(resource_class,),
+            )
+            .unwrap();
+        };
+        report("firefox");
+        assert_eq!(active_window.get(), Some("firefox".to_string()));
+
+        // Nothing is focused, e.g. when clicking the desktop
+        report("");
+        assert_eq!(active_window.get(), None);
+    }
+}
@@ -0,0 +1,212 @@
+#[cfg(target_os = "linux")]
+use std::sync::{Arc, Mutex};
+use std::{cell::Cell, cell::RefCell, rc::Rc};
+
+use log::info;
+
+use super::outputs::window_activity::{RepresentsWindow, WindowName};
+
+#[cfg(target_os = "linux")]
+mod gnome;
+#[cfg(target_os = "linux")]
+mod kwin;
+#[cfg(target_os = "linux")]
+mod wlr;
+
+pub struct RawActiveWindow {
+    pub window_name: WindowName,
+}
+
+pub trait ActiveWindowProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()>;
+}
+
+pub struct LockAwareWindowProvider {
+    pub currently_locked: bool,
+    provider: FallbackWindowProvider,
+}
+
+impl LockAwareWindowProvider {
+    pub fn new() -> Self {
+        LockAwareWindowProvider {
+            currently_locked: false,
+            provider: FallbackWindowProvider::detect(),
+        }
+    }
+}
+
+impl ActiveWindowProvider for LockAwareWindowProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        if self.currently_locked {
+            Ok(RawActiveWindow {
+                window_name: WindowName::Locked,
+            })
+        } else {
+            self.provider.get_active_window()
+        }
+    }
+}
+
+impl ActiveWindowProvider for Rc<RefCell<LockAwareWindowProvider>> {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        self.borrow().get_active_window()
+    }
+}
+
+/// Asks the OS directly, which works on Windows and X11 but not on most Wayland compositors
+pub struct OsActiveWindowProvider;
+
+impl ActiveWindowProvider for OsActiveWindowProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        active_win_pos_rs::get_active_window().map(|w| w.into())
+    }
+}
+
+/// The active window as last reported by the desktop, for desktops that tell us about changes
+/// rather than answering when asked
+#[cfg(target_os = "linux")]
+#[derive(Clone, Default)]
+pub struct ReportedActiveWindow(Arc<Mutex<Option<String>>>);
+
+#[cfg(target_os = "linux")]
+impl ReportedActiveWindow {
+    pub fn set(&self, app_name: Option<String>) {
+        *self.0.lock().unwrap() = app_name;
+    }
+
+    pub fn get(&self) -> Option<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ActiveWindowProvider for ReportedActiveWindow {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        self.get()
+            .map(|app_name| RawActiveWindow {
+                window_name: WindowName::Normal(app_name),
+            })
+            .ok_or(())
+    }
+}
+
+/// Tries each provider in order, sticking with the last one that worked until it stops working
+pub struct FallbackWindowProvider {
+    providers: Vec<(&'static str, Box<dyn ActiveWindowProvider>)>,
+    working_provider: Cell<Option<usize>>,
+}
+
+impl FallbackWindowProvider {
+    pub fn new(providers: Vec<(&'static str, Box<dyn ActiveWindowProvider>)>) -> Self {
+        Self {
+            providers,
+            working_provider: Cell::new(None),
+        }
+    }
+
+    /// Picks the providers that make sense for the current desktop, most specific first
+    pub fn detect() -> Self {
+        let mut providers: Vec<(&'static str, Box<dyn ActiveWindowProvider>)> = Vec::new();
+
+        #[cfg(target_os = "linux")]
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            let desktop = std::env::var("XDG_CURRENT_DESKTOP").unwrap_or_default();
+            if desktop.contains("GNOME") {
+                if let Some(p) = gnome::GnomeShellWindowProvider::new() {
+                    providers.push(("GNOME Shell", Box::new(p)));
+                }
+            }
+            if desktop.contains("KDE") {
+                if let Some(p) = kwin::KWinWindowProvider::new() {
+                    providers.push(("KWin", Box::new(p)));
+                }
+            }
+            if let Some(p) = wlr::WlrForeignToplevelProvider::new() {
+                providers.push(("wlr foreign toplevel", Box::new(p)));
+            }
+        }
+
+        // Still sees XWayland windows when nothing else works
+        providers.push(("OS", Box::new(OsActiveWindowProvider)));
+        Self::new(providers)
+    }
+}
+
+impl ActiveWindowProvider for FallbackWindowProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        if let Some(i) = self.working_provider.get() {
+            if let Ok(window) = self.providers[i].1.get_active_window() {
+                return Ok(window);
+            }
+        }
+
+        for (i, (name, provider)) in self.providers.iter().enumerate() {
+            if let Ok(window) = provider.get_active_window() {
+                if self.working_provider.replace(Some(i)) != Some(i) {
+                    info!("Getting the active window from {} provider", name);
+                }
+                return Ok(window);
+            }
+        }
+
+        self.working_provider.set(None);
+        Err(())
+    }
+}
+
+impl From<active_win_pos_rs::ActiveWindow> for RawActiveWindow {
+    fn from(value: active_win_pos_rs::ActiveWindow) -> Self {
+        RawActiveWindow {
+            window_name: WindowName::Normal(value.app_name),
+        }
+    }
+}
+
+impl RepresentsWindow for RawActiveWindow {
+    fn window_name(&self) -> &WindowName {
+        &self.window_name
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    struct FixedWindowProvider(Option<&'static str>);
+
+    impl ActiveWindowProvider for FixedWindowProvider {
+        fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+            self.0
+                .map(|name| RawActiveWindow {
+                    window_name: WindowName::Normal(name.to_string()),
+                })
+                .ok_or(())
+        }
+    }
+
+    #[test]
+    pub fn fallback_provider_uses_first_working_provider() {
+        let provider = FallbackWindowProvider::new(vec![
+            ("broken", Box::new(FixedWindowProvider(None))),
+            ("first", Box::new(FixedWindowProvider(Some("Firefox")))),
+            ("second", Box::new(FixedWindowProvider(Some("Terminal")))),
+        ]);
+
+        let window = provider.get_active_window().unwrap();
+
+        assert_eq!(
+            window.window_name,
+            WindowName::Normal("Firefox".to_string())
+        );
+        assert_eq!(provider.working_provider.get(), Some(1));
+    }
+
+    #[test]
+    pub fn fallback_provider_fails_when_no_provider_works() {
+        let provider =
+            FallbackWindowProvider::new(vec![("broken", Box::new(FixedWindowProvider(None)))]);
+
+        assert!(provider.get_active_window().is_err());
+        assert_eq!(provider.working_provider.get(), None);
+    }
+}
@@ -0,0 +1,202 @@
+use super::{ActiveWindowProvider, RawActiveWindow, ReportedActiveWindow};
+use log::{info, warn};
+use std::{collections::HashMap, hash::Hash};
+use wayland_client::{
+    backend::ObjectId,
+    event_created_child,
+    globals::{registry_queue_init, GlobalListContents},
+    protocol::wl_registry::WlRegistry,
+    Connection, Dispatch, Proxy, QueueHandle,
+};
+use wayland_protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+
+/// Uses the foreign toplevel protocol meant for taskbars, supported by wlroots-based compositors
+/// such as Sway, Hyprland and Wayfire
+pub struct WlrForeignToplevelProvider {
+    active_window: ReportedActiveWindow,
+}
+
+impl WlrForeignToplevelProvider {
+    pub fn new() -> Option<Self> {
+        let active_window = ReportedActiveWindow::default();
+        match listen_for_toplevels(active_window.clone()) {
+            Ok(()) => Some(Self { active_window }),
+            Err(e) => {
+                info!("wlr foreign toplevel management is unavailable: {}", e);
+                None
+            }
+        }
+    }
+}
+
+impl ActiveWindowProvider for WlrForeignToplevelProvider {
+    fn get_active_window(&self) -> Result<RawActiveWindow, ()> {
+        self.active_window.get_active_window()
+    }
+}
+
+#[derive(Default)]
+struct Toplevel {
+    app_id: Option<String>,
+    activated: bool,
+}
+
+/// Keeps track of which toplevel is activated. Changes only apply once the compositor says
+/// they're `done`.
+struct ToplevelTracker<K> {
+    toplevels: HashMap<K, Toplevel>,
+    active_window: ReportedActiveWindow,
+}
+
+impl<K> ToplevelTracker<K>
+where
+    K: Hash + Eq,
+{
+    fn new(active_window: ReportedActiveWindow) -> Self {
+        Self {
+            toplevels: HashMap::new(),
+            active_window,
+        }
+    }
+
+    fn set_app_id(&mut self, key: K, app_id: String) {
+        self.toplevels.entry(key).or_default().app_id = Some(app_id);
+    }
+
+    fn set_activated(&mut self, key: K, activated: bool) {
+        self.toplevels.entry(key).or_default().activated = activated;
+    }
+
+    fn closed(&mut self, key: &K) {
+        self.toplevels.remove(key);
+        self.done();
+    }
+
+    fn done(&mut self) {
+        self.active_window.set(
+            self.toplevels
+                .values()
+                .find(|t| t.activated)
+                .and_then(|t| t.app_id.clone()),
+        );
+    }
+}
+
+fn listen_for_toplevels(active_window: ReportedActiveWindow) -> Result<(), String> {
+    let conn = Connection::connect_to_env().map_err(|e| e.to_string())?;
+    let (globals, mut queue) =
+        registry_queue_init::<ToplevelTracker<ObjectId>>(&conn).map_err(|e| e.to_string())?;
+    let _manager: ZwlrForeignToplevelManagerV1 = globals
+        .bind(&queue.handle(), 1..=3, ())
+        .map_err(|e| e.to_string())?;
+
+    let mut tracker = ToplevelTracker::new(active_window);
+    std::thread::Builder::new()
+        .name("wlr_foreign_toplevel".to_string())
+        .spawn(move || loop {
+            if let Err(e) = queue.blocking_dispatch(&mut tracker) {
+                warn!("Lost connection to the Wayland compositor: {}", e);
+                tracker.active_window.set(None);
+                return;
+            }
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn is_activated(state: &[u8]) -> bool {
+    let activated = zwlr_foreign_toplevel_handle_v1::State::Activated as u32;
+    state
+        .chunks_exact(4)
+        .any(|s| u32::from_ne_bytes([s[0], s[1], s[2], s[3]]) == activated)
+}
+
+impl Dispatch<WlRegistry, GlobalListContents> for ToplevelTracker<ObjectId> {
+    fn event(
+        _state: &mut Self,
+        _proxy: &WlRegistry,
+        _event: <WlRegistry as Proxy>::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+    }
+}
+
+impl Dispatch<ZwlrForeignToplevelManagerV1, ()> for ToplevelTracker<ObjectId> {
+    fn event(
+        _state: &mut Self,
+        _proxy: &ZwlrForeignToplevelManagerV1,
+        _event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        // New toplevels are handled through their own events
+    }
+
+    event_created_child!(ToplevelTracker<ObjectId>, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl Dispatch<ZwlrForeignToplevelHandleV1, ()> for ToplevelTracker<ObjectId> {
+    fn event(
+        state: &mut Self,
+        proxy: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qhandle: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                state.set_app_id(proxy.id(), app_id)
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: s } => {
+                state.set_activated(proxy.id(), is_activated(&s))
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => state.done(),
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.closed(&proxy.id());
+                proxy.destroy();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn is_activated_finds_activated_among_states() {
+        let state =
+            |states: &[u32]| -> Vec<u8> { states.iter().flat_map(|s| s.to_ne_bytes()).collect() };
+
+        assert!(is_activated(&state(&[0, 2])));
+        assert!(!is_activated(&state(&[0, 1])));
+        assert!(!is_activated(&[]));
+    }
+
+    #[test]
+    pub fn tracker_reports_activated_toplevel_once_done() {
+        let active_window = ReportedActiveWindow::default();
+        let mut tracker = ToplevelTracker::new(active_window.clone());
+
+        tracker.set_app_id(1, "firefox".to_string());
+        tracker.set_app_id(2, "foot".to_string());
+        tracker.set_activated(2, true);
+        assert_eq!(active_window.get(), None);
+
+        tracker.done();
+        assert_eq!(active_window.get(), Some("foot".to_string()));
+
+        tracker.closed(&2);
+        assert_eq!(active_window.get(), None);
+    }
+}
@@ -0,0 +1,42 @@
+use gwaihir_client_lib::chrono;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_WINDOW_ACTIVITY_MINUTES: i64 = 10;
+pub const DEFAULT_MAX_APPS_IN_SUMMARY: usize = 5;
+pub const DEFAULT_BUCKET_DURATION_SECONDS: u64 = 10;
+pub const DEFAULT_NUM_BUCKETS: usize = 30;
+
+/// How much activity history is kept and summarized. Whatever is chosen here is sent along with
+/// the data, so others don't need the same settings to make sense of it.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ActivitySettings {
+    /// How far back window activity is summarized
+    pub window_activity_minutes: i64,
+    /// How many of the most used apps are included in the window activity summary
+    pub max_apps_in_summary: usize,
+    /// How long each bar of the keyboard/mouse activity graph covers
+    pub bucket_duration_seconds: u64,
+    /// How many bars of keyboard/mouse activity are kept
+    pub num_buckets: usize,
+}
+
+impl ActivitySettings {
+    pub fn window_activity_retention(&self) -> chrono::Duration {
+        chrono::Duration::minutes(self.window_activity_minutes)
+    }
+
+    pub fn bucket_duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.bucket_duration_seconds)
+    }
+}
+
+impl Default for ActivitySettings {
+    fn default() -> Self {
+        Self {
+            window_activity_minutes: DEFAULT_WINDOW_ACTIVITY_MINUTES,
+            max_apps_in_summary: DEFAULT_MAX_APPS_IN_SUMMARY,
+            bucket_duration_seconds: DEFAULT_BUCKET_DURATION_SECONDS,
+            num_buckets: DEFAULT_NUM_BUCKETS,
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use enum_iterator::Sequence;
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// What apps without a category are grouped under
+pub const UNCATEGORIZED: &str = "Other";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default, Sequence)]
+pub enum MatchKind {
+    #[default]
+    Exact,
+    /// `*` matches anything, `?` matches a single character
+    Glob,
+    Regex,
+}
+
+/// Renames and/or categorizes apps whose OS-reported name matches `pattern`. Matching ignores case.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct AppRule {
+    pub match_kind: MatchKind,
+    pub pattern: String,
+    /// Left empty to keep the app's own name
+    pub alias: String,
+    /// Left empty to leave the app uncategorized
+    pub category: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct AppRules {
+    /// The first matching rule wins
+    pub rules: Vec<AppRule>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct ResolvedApp {
+    pub name: String,
+    pub category: Option<String>,
+}
+
+/// `AppRules` with their patterns compiled, ready to be matched against many app names
+#[derive(Default)]
+pub struct CompiledAppRules {
+    rules: Vec<(Regex, AppRule)>,
+}
+
+impl AppRule {
+    pub fn compile(&self) -> Result<Regex, regex::Error> {
+        let pattern = match self.match_kind {
+            MatchKind::Exact => format!("^{}$", regex::escape(&self.pattern)),
+            MatchKind::Glob => format!(
+                "^{}$",
+                regex::escape(&self.pattern)
+                    .replace(r"\*", ".*")
+                    .replace(r"\?", ".")
+            ),
+            MatchKind::Regex => self.pattern.clone(),
+        };
+        regex::RegexBuilder::new(&pattern)
+            .case_insensitive(true)
+            .build()
+    }
+}
+
+impl AppRules {
+    pub fn compile(&self) -> CompiledAppRules {
+        let rules = self
+            .rules
+            .iter()
+            .filter_map(|rule| match rule.compile() {
+                Ok(regex) => Some((regex, rule.clone())),
+                Err(e) => {
+                    warn!("Ignoring app rule for \"{}\": {}", rule.pattern, e);
+                    None
+                }
+            })
+            .collect();
+
+        CompiledAppRules { rules }
+    }
+}
+
+impl CompiledAppRules {
+    pub fn resolve(&self, app_name: &str) -> ResolvedApp {
+        let rule = self
+            .rules
+            .iter()
+            .find(|(regex, _)| regex.is_match(app_name))
+            .map(|(_, rule)| rule);
+        let non_empty = |s: &String| Some(s.clone()).filter(|s| !s.trim().is_empty());
+        ResolvedApp {
+            name: rule
+                .and_then(|r| non_empty(&r.alias))
+                .unwrap_or_else(|| app_name.to_string()),
+            category: rule.and_then(|r| non_empty(&r.category)),
+        }
+    }
+}
+
+impl Display for MatchKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MatchKind::Exact => write!(f, "Exact"),
+            MatchKind::Glob => write!(f, "Glob"),
+            MatchKind::Regex => write!(f, "Regex"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn rule(match_kind: MatchKind, pattern: &str, alias: &str, category: &str) -> AppRule {
+        AppRule {
+            match_kind,
+            pattern: pattern.to_string(),
+            alias: alias.to_string(),
+            category: category.to_string(),
+        }
+    }
+
+    fn rules() -> AppRules {
+        AppRules {
+            rules: vec![
+                rule(MatchKind::Exact, "code", "VS Code", "Coding"),
+                rule(MatchKind::Glob, "code-*", "VS Code", "Coding"),
+                rule(MatchKind::Regex, "^firefox(-esr)?$", "", "Browsing"),
+                rule(MatchKind::Regex, "(unclosed", "Broken", "Broken"),
+            ],
+        }
+    }
+
+    #[test]
+    pub fn first_matching_rule_is_applied() {
+        let compiled = rules().compile();
+
+        assert_eq!(
+            compiled.resolve("Code"),
+            ResolvedApp {
+                name: "VS Code".to_string(),
+                category: Some("Coding".to_string()),
+            }
+        );
+        assert_eq!(compiled.resolve("code-oss").name, "VS Code");
+        assert_eq!(
+            compiled.resolve("firefox-esr"),
+            ResolvedApp {
+                name: "firefox-esr".to_string(),
+                category: Some("Browsing".to_string()),
+            }
+        );
+        assert_eq!(
+            compiled.resolve("Slack"),
+            ResolvedApp {
+                name: "Slack".to_string(),
+                category: None,
+            }
+        );
+    }
+}
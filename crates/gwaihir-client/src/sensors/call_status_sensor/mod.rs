@@ -0,0 +1,192 @@
+use super::{
+    outputs::{
+        call_status::CallStatus, microphone_usage::MicrophoneUsage, sensor_output::SensorOutput,
+        summarized_window_activity::SummarizedWindowActivity, window_activity::WindowName,
+    },
+    Sensor,
+};
+use gwaihir_client_lib::chrono::{DateTime, Utc};
+
+/// Lowercase parts of app names which mean the app is for calls/video conferencing
+const CONFERENCING_APPS: [&str; 9] = [
+    "zoom", "teams", "skype", "webex", "slack", "discord", "jitsi", "whereby", "signal",
+];
+
+/// Everything that hints at whether we're in a call. None of them are reliable alone: dictation
+/// apps use the microphone, and conferencing apps are often left open between calls.
+#[derive(Clone, PartialEq, Debug, Default)]
+struct CallSignals {
+    microphone_in_use: bool,
+    conferencing_app_using_microphone: bool,
+    conferencing_app_focused: bool,
+    camera_in_use: bool,
+}
+
+impl CallSignals {
+    fn in_call(&self) -> bool {
+        self.conferencing_app_using_microphone
+            || (self.microphone_in_use && (self.camera_in_use || self.conferencing_app_focused))
+            || (self.camera_in_use && self.conferencing_app_focused)
+    }
+}
+
+/// Combines the microphone, camera and window activity sensors' outputs into whether we're in a
+/// call
+pub struct CallStatusSensor {
+    signals: CallSignals,
+    since: Option<DateTime<Utc>>,
+}
+
+impl CallStatusSensor {
+    pub fn new() -> Self {
+        Self {
+            signals: Default::default(),
+            since: None,
+        }
+    }
+
+    fn call_status(&mut self, now: DateTime<Utc>) -> CallStatus {
+        let in_call = self.signals.in_call();
+        self.since = match self.since {
+            Some(since) if in_call => Some(since),
+            None if in_call => Some(now),
+            _ => None,
+        };
+        CallStatus {
+            in_call,
+            since: self.since,
+        }
+    }
+}
+
+impl Sensor for CallStatusSensor {
+    fn get_output(&mut self) -> SensorOutput {
+        SensorOutput::CallStatus(self.call_status(Utc::now()))
+    }
+
+    fn updated_sensor_outputs(&mut self, outputs: &[SensorOutput]) {
+        for output in outputs {
+            match output {
+                SensorOutput::MicrophoneUsage(usage) => {
+                    self.signals.microphone_in_use = usage.num_apps() > 0;
+                    self.signals.conferencing_app_using_microphone =
+                        conferencing_app_using_microphone(usage);
+                }
+                SensorOutput::CameraUsage(usage) => {
+                    self.signals.camera_in_use = usage.num_apps() > 0;
+                }
+                SensorOutput::SummarizedWindowActivity(activity) => {
+                    self.signals.conferencing_app_focused = conferencing_app_focused(activity);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn is_conferencing_app(app_name: &str) -> bool {
+    let app_name = app_name.to_lowercase();
+    CONFERENCING_APPS.iter().any(|app| app_name.contains(app))
+}
+
+fn conferencing_app_using_microphone(usage: &MicrophoneUsage) -> bool {
+    usage
+        .usage
+        .iter()
+        .any(|app| is_conferencing_app(app.as_ref()))
+}
+
+fn conferencing_app_focused(activity: &SummarizedWindowActivity) -> bool {
+    let focused_app_is_conferencing = match &activity.current_window.window_name {
+        WindowName::Normal(name) => is_conferencing_app(name),
+        WindowName::Locked => false,
+    };
+    focused_app_is_conferencing || activity.in_meeting_category()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{
+        camera_usage::CameraUsage, microphone_usage::AppName,
+        summarized_window_activity::SummarizedWindowActivity, window_activity::ActiveWindow,
+    };
+    use gwaihir_client_lib::chrono::Duration;
+
+    fn microphone_usage(apps: &[&str]) -> SensorOutput {
+        SensorOutput::MicrophoneUsage(MicrophoneUsage {
+            usage: apps.iter().map(|a| AppName::new(a.to_string())).collect(),
+            num_unnamed_apps: 0,
+        })
+    }
+
+    fn focused_app(name: &str) -> SensorOutput {
+        SensorOutput::SummarizedWindowActivity(SummarizedWindowActivity {
+            current_window: ActiveWindow {
+                window_name: WindowName::Normal(name.to_string()),
+                started_using: Utc::now(),
+            },
+            current_category: None,
+            recent_usage: Vec::new(),
+            window_length: Duration::minutes(10),
+        })
+    }
+
+    #[test]
+    pub fn microphone_alone_is_not_a_call() {
+        let mut sensor = CallStatusSensor::new();
+
+        sensor.updated_sensor_outputs(&[microphone_usage(&["dictation"]), focused_app("Code")]);
+        assert!(!sensor.call_status(Utc::now()).in_call);
+
+        sensor.updated_sensor_outputs(&[microphone_usage(&["dictation"]), focused_app("Zoom")]);
+        assert!(sensor.call_status(Utc::now()).in_call);
+    }
+
+    #[test]
+    pub fn conferencing_app_using_microphone_is_a_call() {
+        let mut sensor = CallStatusSensor::new();
+
+        sensor
+            .updated_sensor_outputs(&[microphone_usage(&["teams-for-linux"]), focused_app("Code")]);
+
+        assert!(sensor.call_status(Utc::now()).in_call);
+    }
+
+    #[test]
+    pub fn since_is_kept_until_the_call_ends() {
+        let mut sensor = CallStatusSensor::new();
+        let start = Utc::now();
+
+        sensor.updated_sensor_outputs(&[microphone_usage(&["zoom"])]);
+        assert_eq!(sensor.call_status(start).since, Some(start));
+        assert_eq!(
+            sensor.call_status(start + Duration::minutes(5)).since,
+            Some(start)
+        );
+
+        sensor.updated_sensor_outputs(&[microphone_usage(&[])]);
+        assert_eq!(
+            sensor.call_status(start + Duration::minutes(10)),
+            CallStatus {
+                in_call: false,
+                since: None
+            }
+        );
+    }
+
+    #[test]
+    pub fn camera_with_microphone_is_a_call() {
+        let mut sensor = CallStatusSensor::new();
+        let camera_usage = SensorOutput::CameraUsage(CameraUsage {
+            usage: vec![AppName::new("cheese".to_string())],
+            num_unnamed_apps: 0,
+        });
+
+        sensor.updated_sensor_outputs(&[camera_usage.clone(), microphone_usage(&[])]);
+        assert!(!sensor.call_status(Utc::now()).in_call);
+
+        sensor.updated_sensor_outputs(&[camera_usage, microphone_usage(&["dictation"])]);
+        assert!(sensor.call_status(Utc::now()).in_call);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::sensors::{
+    outputs::{camera_usage::CameraUsage, microphone_usage::AppName},
+    pipewire::{self, PipeWireMonitor, PipeWireProps},
+};
+use log::info;
+use serde_json::Value;
+use std::{collections::HashSet, fs, path::Path};
+
+/// These keep cameras open to watch for changes, whether or not anything is recording. Apps
+/// using the camera through them are found from PipeWire instead.
+const IGNORED_PROCESSES: [&str; 2] = ["pipewire", "wireplumber"];
+const VIDEO_SOURCE_CLASS: &str = "Video/Source";
+
+/// *Returns:* A function listing the apps using a camera, either by opening a V4L2 video device
+/// directly or by reading one through PipeWire (e.g. when asked for through xdg-desktop-portal)
+pub fn camera_usage_checker() -> impl FnMut() -> CameraUsage {
+    let monitor = match PipeWireMonitor::shared() {
+        Ok(monitor) => Some(monitor),
+        Err(e) => {
+            info!("Only apps opening cameras directly will be found: {}", e);
+            None
+        }
+    };
+    move || {
+        let mut apps = apps_with_video_device_open();
+        if let Some(monitor) = monitor.as_ref() {
+            apps.extend(pipewire_camera_consumers(&monitor.latest()));
+        }
+        // Apps often have several processes, or several cameras open
+        apps.sort();
+        apps.dedup();
+
+        CameraUsage {
+            usage: apps.into_iter().map(AppName::new).collect(),
+            num_unnamed_apps: 0,
+        }
+    }
+}
+
+/// Only our own processes can be inspected, but those are the ones that matter
+fn apps_with_video_device_open() -> Vec<String> {
+    let Ok(processes) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    processes
+        .filter_map(Result::ok)
+        .filter(|p| p.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter(|p| has_video_device_open(&p.path()))
+        .filter_map(|p| process_name(&p.path()))
+        .filter(|name| !IGNORED_PROCESSES.contains(&name.as_str()))
+        .collect()
+}
+
+/// Cameras are the video sources that belong to a device, unlike screencasts. Whatever they're
+/// linked to is reading from them.
+fn pipewire_camera_consumers(props: &[PipeWireProps]) -> Vec<String> {
+    let cameras: HashSet<u64> = props
+        .iter()
+        .filter(|p| pipewire::get_str(p, "media.class") == Some(VIDEO_SOURCE_CLASS))
+        .filter(|p| p.contains_key("device.api"))
+        .filter_map(|p| get_u64(p, "object.id"))
+        .collect();
+    let consumers: HashSet<u64> = props
+        .iter()
+        .filter(|p| get_u64(p, "link.output.node").is_some_and(|node| cameras.contains(&node)))
+        .filter_map(|p| get_u64(p, "link.input.node"))
+        .collect();
+    props
+        .iter()
+        .filter(|p| get_u64(p, "object.id").is_some_and(|id| consumers.contains(&id)))
+        .filter_map(|p| {
+            pipewire::get_str(p, "application.process.binary")
+                .or_else(|| pipewire::get_str(p, "application.name"))
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+fn get_u64(props: &PipeWireProps, key: &str) -> Option<u64> {
+    props.get(key).and_then(Value::as_u64)
+}
+
+fn process_name(process_dir: &Path) -> Option<String> {
+    fs::read_to_string(process_dir.join("comm"))
+        .ok()
+        .map(|comm| comm.trim().to_string())
+}
+
+fn has_video_device_open(process_dir: &Path) -> bool {
+    let Ok(fds) = fs::read_dir(process_dir.join("fd")) else {
+        return false;
+    };
+    fds.filter_map(Result::ok)
+        .filter_map(|fd| fs::read_link(fd.path()).ok())
+        .any(|target| is_video_device(&target))
+}
+
+fn is_video_device(path: &Path) -> bool {
+    path.to_string_lossy().starts_with("/dev/video")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn only_video_devices_count_as_cameras() {
+        assert!(is_video_device(Path::new("/dev/video0")));
+        assert!(!is_video_device(Path::new("/dev/snd/pcmC0D0c")));
+        assert!(!is_video_device(Path::new("socket:[12345]")));
+    }
+
+    #[test]
+    pub fn apps_reading_cameras_through_pipewire_are_found() {
+        // Firefox reads the webcam through the portal, and OBS reads a screencast
+        let dump = r#"[
+            { "id": 52, "info": { "props": { "object.id": 52, "media.class": "Video/Source",
+                "node.name": "v4l2_input.webcam", "device.api": "v4l2" } } },
+            { "id": 85, "info": { "props": { "object.id": 85, "media.class": "Video/Source",
+                "node.name": "xdpw_stream" } } },
+            { "id": 95, "info": { "props": { "object.id": 95, "media.class": "Stream/Input/Video",
+                "application.process.binary": "firefox" } } },
+            { "id": 96, "info": { "props": { "object.id": 96, "media.class": "Stream/Input/Video",
+                "application.process.binary": "obs" } } },
+            { "id": 100, "info": { "props": { "object.id": 100,
+                "link.output.node": 52, "link.input.node": 95 } } },
+            { "id": 101, "info": { "props": { "object.id": 101,
+                "link.output.node": 85, "link.input.node": 96 } } }
+        ]"#;
+        let props = pipewire::parse_dump(dump.as_bytes()).unwrap();
+
+        assert_eq!(
+            pipewire_camera_consumers(&props),
+            vec!["firefox".to_string()]
+        );
+    }
+}
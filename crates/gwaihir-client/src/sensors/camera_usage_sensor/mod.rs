@@ -0,0 +1,59 @@
+use super::{
+    outputs::{camera_usage::CameraUsage, sensor_output::SensorOutput},
+    Sensor,
+};
+use gwaihir_client_lib::periodic_checker::{HasPeriodicChecker, PeriodicChecker};
+use std::time::Duration;
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+use windows::get_camera_usage;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+/// Finding camera users is slower than asking about the microphone, and calls don't start that
+/// often, so it's checked less frequently
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct CameraUsageSensor {
+    periodic_checker: PeriodicChecker<CameraUsage>,
+}
+
+impl HasPeriodicChecker<CameraUsage> for CameraUsageSensor {
+    fn periodic_checker(&self) -> &PeriodicChecker<CameraUsage> {
+        &self.periodic_checker
+    }
+
+    fn periodic_checker_mut(&mut self) -> &mut PeriodicChecker<CameraUsage> {
+        &mut self.periodic_checker
+    }
+}
+
+impl CameraUsageSensor {
+    fn new(get_camera_usage: impl FnMut() -> CameraUsage + 'static) -> Self {
+        Self {
+            periodic_checker: PeriodicChecker::new(Box::new(get_camera_usage), CHECK_INTERVAL),
+        }
+    }
+}
+
+impl Sensor for CameraUsageSensor {
+    fn get_output(&mut self) -> SensorOutput {
+        self.periodic_checker_mut().check();
+        SensorOutput::CameraUsage(self.periodic_checker().last_check_result())
+    }
+}
+
+#[allow(unreachable_code)]
+pub fn try_get_sensor() -> Option<Box<dyn Sensor>> {
+    #[cfg(target_os = "windows")]
+    return Some(Box::new(CameraUsageSensor::new(get_camera_usage)));
+    #[cfg(target_os = "linux")]
+    return Some(Box::new(CameraUsageSensor::new(
+        linux::camera_usage_checker(),
+    )));
+
+    None::<Box<dyn Sensor>>
+}
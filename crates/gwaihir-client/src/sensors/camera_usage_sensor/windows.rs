@@ -0,0 +1,48 @@
+use crate::sensors::outputs::{camera_usage::CameraUsage, microphone_usage::AppName};
+use std::path::Path;
+use winreg::enums::{HKEY_CURRENT_USER, KEY_READ};
+use winreg::RegKey;
+
+const WEBCAM_CONSENT_STORE: &str =
+    "SOFTWARE\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\webcam";
+
+/// Windows records when each app last stopped using the camera, which is 0 while it's in use
+pub fn get_camera_usage() -> CameraUsage {
+    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+    let usage = [
+        WEBCAM_CONSENT_STORE.to_string(),
+        format!("{}\\NonPackaged", WEBCAM_CONSENT_STORE),
+    ]
+    .iter()
+    .filter_map(|path| hkcu.open_subkey_with_flags(path, KEY_READ).ok())
+    .flat_map(|store| get_apps_using_camera(&store))
+    .collect();
+
+    CameraUsage {
+        usage,
+        num_unnamed_apps: 0,
+    }
+}
+
+fn get_apps_using_camera(store: &RegKey) -> Vec<AppName> {
+    store
+        .enum_keys()
+        .filter_map(|key| key.ok())
+        .filter(|app| {
+            store
+                .open_subkey_with_flags(app, KEY_READ)
+                .and_then(|key| key.get_value::<u64, _>("LastUsedTimeStop"))
+                .is_ok_and(|stopped| stopped == 0)
+        })
+        .map(|app| AppName::new(app_name_from_key(&app)))
+        .collect()
+}
+
+/// Non-packaged apps are keyed by their full path, with '#' in place of '\', which may include
+/// the user's name
+fn app_name_from_key(app_key: &str) -> String {
+    let path = app_key.replace('#', "\\");
+    Path::new(&path)
+        .file_name()
+        .map_or(path.clone(), |name| name.to_string_lossy().to_string())
+}
@@ -0,0 +1,25 @@
+use std::{os::unix::net::UnixStream, thread};
+use zbus::{
+    blocking::{Connection, ConnectionBuilder},
+    Guid,
+};
+
+/// A peer-to-peer connection standing in for the bus, where `.0` plays the part of the other
+/// service and `.1` is what Gwaihir would be connected to
+pub fn private_bus() -> (Connection, Connection) {
+    let (service_stream, client_stream) = UnixStream::pair().unwrap();
+    let guid = Guid::generate();
+    let service = thread::spawn(move || {
+        ConnectionBuilder::unix_stream(service_stream)
+            .server(&guid)
+            .p2p()
+            .build()
+            .unwrap()
+    });
+    let client = ConnectionBuilder::unix_stream(client_stream)
+        .p2p()
+        .build()
+        .unwrap();
+
+    (service.join().unwrap(), client)
+}
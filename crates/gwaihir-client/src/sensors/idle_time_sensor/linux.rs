@@ -0,0 +1,93 @@
+use super::IdleTimeProvider;
+use crate::sensors::logind::{self, LOGIND_DESTINATION, LOGIND_SESSION_INTERFACE};
+use log::{info, warn};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use x11rb::{
+    connection::Connection as _, protocol::screensaver::ConnectionExt as _,
+    rust_connection::RustConnection,
+};
+use zbus::blocking::{Connection, Proxy};
+
+/// Prefers the X11 screensaver extension, which knows about every input event. Under Wayland
+/// (where X11 only sees input to XWayland windows) we fall back to logind's idle hint, which is
+/// coarser since it's only set once the desktop decides the session is idle.
+pub enum LinuxIdleTimeProvider {
+    X11 {
+        conn: Box<RustConnection>,
+        root: u32,
+    },
+    Logind(Proxy<'static>),
+    Unavailable,
+}
+
+impl LinuxIdleTimeProvider {
+    pub fn new() -> Self {
+        if std::env::var_os("WAYLAND_DISPLAY").is_none() {
+            match x11rb::connect(None) {
+                Ok((conn, screen_num)) => {
+                    info!("Using the X11 screensaver extension for idle time");
+                    let root = conn.setup().roots[screen_num].root;
+                    return Self::X11 {
+                        conn: Box::new(conn),
+                        root,
+                    };
+                }
+                Err(e) => warn!("Failed to connect to X11 for idle time: {}", e),
+            }
+        }
+
+        match logind_session_proxy() {
+            Ok(proxy) => {
+                info!("Using logind's idle hint for idle time");
+                Self::Logind(proxy)
+            }
+            Err(e) => {
+                warn!("Failed to connect to logind for idle time: {}", e);
+                Self::Unavailable
+            }
+        }
+    }
+}
+
+impl IdleTimeProvider for LinuxIdleTimeProvider {
+    fn idle_time(&mut self) -> Option<Duration> {
+        match self {
+            Self::X11 { conn, root } => {
+                let reply = conn.screensaver_query_info(*root).ok()?.reply();
+                match reply {
+                    Ok(info) => Some(Duration::from_millis(info.ms_since_user_input.into())),
+                    Err(e) => {
+                        warn!("Failed to query X11 idle time: {}", e);
+                        None
+                    }
+                }
+            }
+            Self::Logind(proxy) => {
+                if !proxy.get_property::<bool>("IdleHint").ok()? {
+                    return Some(Duration::ZERO);
+                }
+
+                // Microseconds since the epoch
+                let idle_since_us = proxy.get_property::<u64>("IdleSinceHint").ok()?;
+                let idle_since = UNIX_EPOCH + Duration::from_micros(idle_since_us);
+                Some(
+                    SystemTime::now()
+                        .duration_since(idle_since)
+                        .unwrap_or_default(),
+                )
+            }
+            Self::Unavailable => None,
+        }
+    }
+}
+
+fn logind_session_proxy() -> zbus::Result<Proxy<'static>> {
+    let conn = Connection::system()?;
+    let session_path = logind::find_session_path(&conn)?;
+    Proxy::new_owned(
+        conn,
+        LOGIND_DESTINATION,
+        session_path,
+        LOGIND_SESSION_INTERFACE,
+    )
+}
@@ -0,0 +1,130 @@
+use super::{
+    outputs::{idle_time::IdleTime, sensor_output::SensorOutput},
+    Sensor,
+};
+use gwaihir_client_lib::{
+    chrono::{DateTime, Duration, Utc},
+    periodic_checker::PeriodicChecker,
+};
+
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+use windows::WindowsIdleTimeProvider;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use linux::LinuxIdleTimeProvider;
+
+/// Idle times are only so precise, so input within this long counts as happening right now, and a
+/// new start of idleness isn't reported unless it moved by more than this
+const IDLE_SINCE_TOLERANCE_SECONDS: i64 = 5;
+
+pub trait IdleTimeProvider {
+    /// *Returns:* How long it's been since the user last provided any input, if the OS can tell us
+    fn idle_time(&mut self) -> Option<std::time::Duration>;
+}
+
+/// An alternative to the `KeyboardMouseSensor` that only asks the OS how long the user has been idle,
+/// rather than hooking into every input event
+pub struct IdleTimeSensor {
+    periodic_checker: PeriodicChecker<Option<std::time::Duration>>,
+    idle_since: Option<DateTime<Utc>>,
+}
+
+impl IdleTimeSensor {
+    pub fn new(mut provider: impl IdleTimeProvider + 'static) -> Self {
+        Self {
+            periodic_checker: PeriodicChecker::new(
+                Box::new(move || provider.idle_time()),
+                std::time::Duration::from_secs(1),
+            ),
+            idle_since: None,
+        }
+    }
+}
+
+impl Sensor for IdleTimeSensor {
+    fn get_output(&mut self) -> SensorOutput {
+        self.periodic_checker.check();
+        match self
+            .periodic_checker
+            .last_check_result()
+            .and_then(|idle| Duration::from_std(idle).ok())
+        {
+            Some(idle) => {
+                self.idle_since = next_idle_since(self.idle_since, idle, Utc::now());
+                SensorOutput::IdleTime(IdleTime {
+                    idle_since: self.idle_since,
+                })
+            }
+            None => SensorOutput::Empty,
+        }
+    }
+}
+
+fn next_idle_since(
+    current: Option<DateTime<Utc>>,
+    idle: Duration,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if idle <= Duration::seconds(IDLE_SINCE_TOLERANCE_SECONDS) {
+        return None;
+    }
+
+    let idle_since = now - idle;
+    match current {
+        Some(current)
+            if (idle_since - current).abs() <= Duration::seconds(IDLE_SINCE_TOLERANCE_SECONDS) =>
+        {
+            Some(current)
+        }
+        _ => Some(idle_since),
+    }
+}
+
+#[allow(unreachable_code)]
+pub fn try_get_sensor() -> Option<Box<dyn Sensor>> {
+    #[cfg(target_os = "windows")]
+    return Some(Box::new(IdleTimeSensor::new(WindowsIdleTimeProvider)));
+
+    #[cfg(target_os = "linux")]
+    return Some(Box::new(IdleTimeSensor::new(LinuxIdleTimeProvider::new())));
+
+    None::<Box<dyn Sensor>>
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn next_idle_since_is_none_while_active() {
+        let now = Utc::now();
+
+        assert_eq!(next_idle_since(None, Duration::seconds(3), now), None);
+        assert_eq!(
+            next_idle_since(None, Duration::seconds(20), now),
+            Some(now - Duration::seconds(20))
+        );
+        assert_eq!(
+            next_idle_since(Some(now - Duration::minutes(5)), Duration::zero(), now),
+            None
+        );
+    }
+
+    #[test]
+    pub fn next_idle_since_stays_put_while_idle() {
+        let now = Utc::now();
+        let idle_since = next_idle_since(None, Duration::minutes(2), now);
+        assert_eq!(idle_since, Some(now - Duration::minutes(2)));
+
+        let later = now + Duration::seconds(30);
+        let slightly_off_idle = Duration::minutes(2) + Duration::seconds(32);
+        assert_eq!(
+            next_idle_since(idle_since, slightly_off_idle, later),
+            idle_since
+        );
+    }
+}
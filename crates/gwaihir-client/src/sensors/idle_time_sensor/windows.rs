@@ -0,0 +1,30 @@
+use super::IdleTimeProvider;
+use log::warn;
+use std::time::Duration;
+use windows::Win32::{
+    System::SystemInformation::GetTickCount,
+    UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
+};
+
+pub struct WindowsIdleTimeProvider;
+
+impl IdleTimeProvider for WindowsIdleTimeProvider {
+    fn idle_time(&mut self) -> Option<Duration> {
+        let mut last_input = LASTINPUTINFO {
+            cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+            dwTime: 0,
+        };
+
+        // SAFETY: last_input is a valid LASTINPUTINFO with its size set, as required
+        if !unsafe { GetLastInputInfo(&mut last_input) }.as_bool() {
+            warn!("GetLastInputInfo failed");
+            return None;
+        }
+
+        // Both are in milliseconds since boot, and wrap around after ~49 days
+        let now = unsafe { GetTickCount() };
+        Some(Duration::from_millis(
+            now.wrapping_sub(last_input.dwTime).into(),
+        ))
+    }
+}
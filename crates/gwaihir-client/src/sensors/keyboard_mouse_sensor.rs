@@ -1,4 +1,5 @@
 use super::{
+    activity_settings::ActivitySettings,
     keyboard_mouse_event_provider::{
         KeyboardMouseEvent, KeyboardMouseEventProvider, KeyboardMouseEventType,
     },
@@ -16,15 +17,12 @@ use std::{
     time::{Duration, SystemTime},
 };
 
-pub const BUCKET_DURATION_SECONDS: u64 = 10;
-pub const BUCKET_DURATION: Duration = Duration::from_secs(BUCKET_DURATION_SECONDS);
-pub const NUM_BUCKETS_TO_KEEP: usize = 30;
-
 pub struct KeyboardMouseSensor {
     rx_from_listener: Receiver<KeyboardMouseEvent>,
     keyboard_quantifier: KeyboardMouseEventHistoricalQuantifier,
     mouse_movement_quantifier: KeyboardMouseEventHistoricalQuantifier,
     mouse_button_quantifier: KeyboardMouseEventHistoricalQuantifier,
+    settings: ActivitySettings,
 
     _listener_thread_handle: JoinHandle<()>,
 }
@@ -58,6 +56,8 @@ impl Sensor for KeyboardMouseSensor {
             keyboard_usage: self.keyboard_quantifier.sensor_data(),
             mouse_movement: self.mouse_movement_quantifier.sensor_data(),
             mouse_button_usage: self.mouse_button_quantifier.sensor_data(),
+            bucket_duration_seconds: self.settings.bucket_duration_seconds,
+            num_buckets: self.settings.num_buckets,
         })
     }
 }
@@ -65,6 +65,7 @@ impl Sensor for KeyboardMouseSensor {
 impl KeyboardMouseSensor {
     pub fn new(
         event_provider: impl KeyboardMouseEventProvider + Send + 'static,
+        settings: ActivitySettings,
     ) -> (Self, Sender<ShutdownMessage>) {
         let (tx_to_main, rx_from_listener) = channel();
         let (tx_to_listener, rx_from_main) = channel();
@@ -89,9 +90,14 @@ impl KeyboardMouseSensor {
         (
             Self {
                 rx_from_listener,
-                keyboard_quantifier: KeyboardMouseEventHistoricalQuantifier::new(now),
-                mouse_movement_quantifier: KeyboardMouseEventHistoricalQuantifier::new(now),
-                mouse_button_quantifier: KeyboardMouseEventHistoricalQuantifier::new(now),
+                keyboard_quantifier: KeyboardMouseEventHistoricalQuantifier::new(now, &settings),
+                mouse_movement_quantifier: KeyboardMouseEventHistoricalQuantifier::new(
+                    now, &settings,
+                ),
+                mouse_button_quantifier: KeyboardMouseEventHistoricalQuantifier::new(
+                    now, &settings,
+                ),
+                settings,
 
                 _listener_thread_handle: listener_handle,
             },
@@ -101,6 +107,7 @@ impl KeyboardMouseSensor {
 }
 
 struct KeyboardMouseEventHistoricalQuantifier {
+    bucket_duration: Duration,
     current_bucket_start: SystemTime,
     current_bucket_end: SystemTime,
 
@@ -111,15 +118,16 @@ struct KeyboardMouseEventHistoricalQuantifier {
 }
 
 impl KeyboardMouseEventHistoricalQuantifier {
-    pub fn new(now: SystemTime) -> Self {
+    pub fn new(now: SystemTime, settings: &ActivitySettings) -> Self {
         Self {
+            bucket_duration: settings.bucket_duration(),
             current_bucket_start: now,
-            current_bucket_end: now + BUCKET_DURATION,
+            current_bucket_end: now + settings.bucket_duration(),
 
             last_event: None,
 
             current_bucket_value: 0.0,
-            past_values: BoundedVecDeque::new(NUM_BUCKETS_TO_KEEP),
+            past_values: BoundedVecDeque::new(settings.num_buckets),
         }
     }
 
@@ -156,7 +164,7 @@ impl KeyboardMouseEventHistoricalQuantifier {
         self.current_bucket_value = 0.0;
         (self.current_bucket_start, self.current_bucket_end) = (
             self.current_bucket_end,
-            self.current_bucket_end + BUCKET_DURATION,
+            self.current_bucket_end + self.bucket_duration,
         )
     }
 }
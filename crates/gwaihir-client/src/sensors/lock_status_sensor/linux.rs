@@ -1,12 +1,203 @@
 use super::{LockStatusSensorError, SessionEvent};
-use std::sync::mpsc::Sender;
+use crate::sensors::logind::{self, LOGIND_SESSION_INTERFACE};
+use log::{info, warn};
+use std::sync::mpsc::{self, Receiver, Sender};
+use zbus::{
+    blocking::{Connection, MessageIterator},
+    zvariant::OwnedObjectPath,
+    MatchRule, MessageType,
+};
+
+const SCREENSAVER_INTERFACE: &str = "org.freedesktop.ScreenSaver";
 
 pub fn register_os_hook(_handle: ()) -> Result<(), LockStatusSensorError> {
     Ok(())
 }
 
+/// On Linux, locks are reported over D-Bus rather than through the event loop, so we start
+/// listening to both logind and the screensaver here.
 pub fn register_msg_hook(
     _builder: &mut eframe::EventLoopBuilder<eframe::UserEvent>,
-    _tx_to_sensor: Sender<SessionEvent>,
-) {
+    tx_to_sensor: Sender<SessionEvent>,
+) -> Result<(), LockStatusSensorError> {
+    let (tx_locked, rx_locked) = mpsc::channel();
+    let logind_started = start_listener("logind", tx_locked.clone(), || {
+        let conn = Connection::system()?;
+        let session_path = logind::find_session_path(&conn)?;
+        logind_lock_changes(&conn, session_path)
+    });
+    let screensaver_started = start_listener("screensaver", tx_locked, || {
+        screensaver_lock_changes(&Connection::session()?)
+    });
+    if !logind_started && !screensaver_started {
+        return Err(LockStatusSensorError::NoLockListeners);
+    }
+
+    std::thread::Builder::new()
+        .name("lock_status_forwarder".to_string())
+        .spawn(move || forward_lock_changes(rx_locked, tx_to_sensor))
+        .expect("Failed to spawn lock status forwarding thread");
+
+    Ok(())
+}
+
+/// Subscribes to lock changes right away, then passes them on from a thread of their own
+///
+/// *Returns:* Whether we're listening
+fn start_listener<I>(
+    name: &'static str,
+    tx_locked: Sender<bool>,
+    listen: impl FnOnce() -> zbus::Result<I>,
+) -> bool
+where
+    I: Iterator<Item = bool> + Send + 'static,
+{
+    let lock_changes = match listen() {
+        Ok(lock_changes) => lock_changes,
+        Err(e) => {
+            warn!("Unable to listen for session locks from {}: {}", name, e);
+            return false;
+        }
+    };
+
+    let spawn_result = std::thread::Builder::new()
+        .name(format!("lock_status_{name}"))
+        .spawn(move || {
+            for locked in lock_changes {
+                if tx_locked.send(locked).is_err() {
+                    return;
+                }
+            }
+            warn!("Stopped receiving session locks from {}", name);
+        });
+
+    match spawn_result {
+        Ok(_) => {
+            info!("Listening for session locks from {}", name);
+            true
+        }
+        Err(e) => {
+            warn!("Failed to spawn {} lock status thread: {}", name, e);
+            false
+        }
+    }
+}
+
+/// Both logind and the screensaver may report the same lock, so only pass on actual changes
+fn forward_lock_changes(rx_locked: Receiver<bool>, tx_to_sensor: Sender<SessionEvent>) {
+    let mut currently_locked = false;
+    for locked in rx_locked {
+        if locked == currently_locked {
+            continue;
+        }
+
+        currently_locked = locked;
+        let event = if locked {
+            SessionEvent::Locked
+        } else {
+            SessionEvent::Unlocked
+        };
+        if tx_to_sensor.send(event).is_err() {
+            return;
+        }
+    }
+}
+
+/// *Returns:* Whether the session is locked, each time logind sends `Lock` or `Unlock` for it
+fn logind_lock_changes(
+    conn: &Connection,
+    session_path: OwnedObjectPath,
+) -> zbus::Result<impl Iterator<Item = bool>> {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(LOGIND_SESSION_INTERFACE)?
+        .path(session_path)?
+        .build();
+
+    Ok(MessageIterator::for_match_rule(rule, conn, None)?
+        .filter_map(Result::ok)
+        .filter_map(|msg| match msg.member()?.as_str() {
+            "Lock" => Some(true),
+            "Unlock" => Some(false),
+            _ => None,
+        }))
+}
+
+/// *Returns:* Whether the screensaver is active, each time it changes
+fn screensaver_lock_changes(conn: &Connection) -> zbus::Result<impl Iterator<Item = bool>> {
+    let rule = MatchRule::builder()
+        .msg_type(MessageType::Signal)
+        .interface(SCREENSAVER_INTERFACE)?
+        .member("ActiveChanged")?
+        .build();
+
+    Ok(MessageIterator::for_match_rule(rule, conn, None)?
+        .filter_map(Result::ok)
+        .filter_map(|msg| msg.body::<bool>().ok()))
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::dbus_test_utils::private_bus;
+    use assert_matches::assert_matches;
+
+    const SESSION_PATH: &str = "/org/freedesktop/login1/session/_31";
+
+    #[test]
+    pub fn logind_lock_changes_reports_lock_and_unlock_of_our_session() {
+        let (service, client) = private_bus();
+        let mut changes =
+            logind_lock_changes(&client, OwnedObjectPath::try_from(SESSION_PATH).unwrap()).unwrap();
+
+        let emit = |path: &str, signal: &str| {
+            service
+                .emit_signal(None::<()>, path, LOGIND_SESSION_INTERFACE, signal, &())
+                .unwrap();
+        };
+        emit("/org/freedesktop/login1/session/_32", "Lock");
+        emit(SESSION_PATH, "Lock");
+        emit(SESSION_PATH, "Unlock");
+
+        assert_eq!(changes.next(), Some(true));
+        assert_eq!(changes.next(), Some(false));
+    }
+
+    #[test]
+    pub fn screensaver_lock_changes_reports_active_changed() {
+        let (service, client) = private_bus();
+        let mut changes = screensaver_lock_changes(&client).unwrap();
+
+        for active in [true, false] {
+            service
+                .emit_signal(
+                    None::<()>,
+                    "/org/freedesktop/ScreenSaver",
+                    SCREENSAVER_INTERFACE,
+                    "ActiveChanged",
+                    &(active,),
+                )
+                .unwrap();
+        }
+
+        assert_eq!(changes.next(), Some(true));
+        assert_eq!(changes.next(), Some(false));
+    }
+
+    #[test]
+    pub fn forward_lock_changes_skips_repeated_states() {
+        let (tx_locked, rx_locked) = mpsc::channel();
+        let (tx_to_sensor, rx_from_forwarder) = mpsc::channel();
+        for locked in [false, true, true, false, false] {
+            tx_locked.send(locked).unwrap();
+        }
+        drop(tx_locked);
+
+        forward_lock_changes(rx_locked, tx_to_sensor);
+
+        let events: Vec<_> = rx_from_forwarder.iter().collect();
+        assert_eq!(events.len(), 2);
+        assert_matches!(events[0], SessionEvent::Locked);
+        assert_matches!(events[1], SessionEvent::Unlocked);
+    }
 }
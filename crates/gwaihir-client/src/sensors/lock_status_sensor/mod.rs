@@ -24,6 +24,7 @@ use linux as sys;
 type WindowHandle = ();
 
 #[allow(unused)]
+#[derive(Debug)]
 pub enum SessionEvent {
     Locked,
     Unlocked,
@@ -33,13 +34,15 @@ pub enum SessionEvent {
 pub enum LockStatusSensorError {
     #[error("The provided window handle was null")]
     NullWindowHandle,
+    #[error("Unable to listen for session locks from anywhere")]
+    NoLockListeners,
 }
 
 type EventLoopBuilder = eframe::EventLoopBuilder<eframe::UserEvent>;
 
 pub struct LockStatusSensorBuilder {}
 pub struct EventLoopRegisteredLockStatusSensorBuilder {
-    sensor_rx: Receiver<SessionEvent>,
+    sensor_rx: Result<Receiver<SessionEvent>, LockStatusSensorError>,
 }
 pub struct FullyRegisteredLockStatusSensorBuilder {
     sensor_rx: Receiver<SessionEvent>,
@@ -73,10 +76,9 @@ impl LockStatusSensorBuilder {
         self,
         builder: &mut EventLoopBuilder,
     ) -> EventLoopRegisteredLockStatusSensorBuilder {
-        let (tx_to_sensor, rx_from_windows) = mpsc::channel();
-        sys::register_msg_hook(builder, tx_to_sensor);
+        let (tx_to_sensor, rx_from_os) = mpsc::channel();
         EventLoopRegisteredLockStatusSensorBuilder {
-            sensor_rx: rx_from_windows,
+            sensor_rx: sys::register_msg_hook(builder, tx_to_sensor).map(|()| rx_from_os),
         }
     }
 }
@@ -86,10 +88,9 @@ impl EventLoopRegisteredLockStatusSensorBuilder {
         self,
         handle: WindowHandle,
     ) -> Result<FullyRegisteredLockStatusSensorBuilder, LockStatusSensorError> {
+        let sensor_rx = self.sensor_rx?;
         sys::register_os_hook(handle)?;
-        Ok(FullyRegisteredLockStatusSensorBuilder {
-            sensor_rx: self.sensor_rx,
-        })
+        Ok(FullyRegisteredLockStatusSensorBuilder { sensor_rx })
     }
 }
 
@@ -132,14 +133,27 @@ pub fn init_lock_status_sensor(
         }
     };
 
+    #[cfg(target_os = "linux")]
+    return match sensor_builder
+        .take()
+        .expect("The lock status sensor builder should be ready when we initialize the app")
+        .register_os_hook(())
+    {
+        Ok(builder) => Some(builder.build()),
+        Err(err) => {
+            error!("{:#?}", err);
+            None
+        }
+    };
+
     None
 }
 
 impl Sensor for LockStatusSensor {
     fn get_output(&mut self) -> SensorOutput {
         match self.session_event_rx.try_recv() {
-            Err(TryRecvError::Empty) => (),
-            Err(TryRecvError::Disconnected) => todo!(),
+            // Once nothing is listening for locks any more, the last status is the best we have
+            Err(TryRecvError::Empty | TryRecvError::Disconnected) => (),
             Ok(SessionEvent::Locked) => self.lock_status.num_locks += 1,
             Ok(SessionEvent::Unlocked) => self.lock_status.num_unlocks += 1,
         }
@@ -161,3 +175,23 @@ impl LockStatusSensor {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    pub fn keeps_the_last_status_once_the_listeners_stop() {
+        let (mut sensor, session_event_tx) = LockStatusSensor::new();
+        session_event_tx.send(SessionEvent::Locked).unwrap();
+        drop(session_event_tx);
+
+        for _ in 0..2 {
+            let SensorOutput::LockStatus(status) = sensor.get_output() else {
+                panic!("expected a lock status");
+            };
+            assert_eq!(status.num_locks, 1);
+            assert_eq!(status.num_unlocks, 0);
+        }
+    }
+}
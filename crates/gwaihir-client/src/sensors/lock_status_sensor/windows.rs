@@ -23,7 +23,7 @@ pub fn register_os_hook(
 pub fn register_msg_hook(
     builder: &mut eframe::EventLoopBuilder<eframe::UserEvent>,
     tx_to_sensor: Sender<SessionEvent>,
-) {
+) -> Result<(), LockStatusSensorError> {
     builder.with_msg_hook(move |msg| {
         let disable_winit_default_processing = false;
         if msg.is_null() {
@@ -46,4 +46,6 @@ pub fn register_msg_hook(
 
         disable_winit_default_processing
     });
+
+    Ok(())
 }
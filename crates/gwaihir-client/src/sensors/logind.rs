@@ -0,0 +1,29 @@
+use zbus::{blocking::Connection, zvariant::OwnedObjectPath};
+
+pub const LOGIND_DESTINATION: &str = "org.freedesktop.login1";
+pub const LOGIND_MANAGER_PATH: &str = "/org/freedesktop/login1";
+pub const LOGIND_MANAGER_INTERFACE: &str = "org.freedesktop.login1.Manager";
+pub const LOGIND_SESSION_INTERFACE: &str = "org.freedesktop.login1.Session";
+
+/// Finds the logind session we're running in
+pub fn find_session_path(conn: &Connection) -> zbus::Result<OwnedObjectPath> {
+    let reply = match std::env::var("XDG_SESSION_ID") {
+        Ok(session_id) => conn.call_method(
+            Some(LOGIND_DESTINATION),
+            LOGIND_MANAGER_PATH,
+            Some(LOGIND_MANAGER_INTERFACE),
+            "GetSession",
+            &(session_id,),
+        ),
+        // Not all desktops set this, but we're still usually part of the session
+        Err(_) => conn.call_method(
+            Some(LOGIND_DESTINATION),
+            LOGIND_MANAGER_PATH,
+            Some(LOGIND_MANAGER_INTERFACE),
+            "GetSessionByPID",
+            &(std::process::id(),),
+        ),
+    }?;
+
+    reply.body::<OwnedObjectPath>()
+}
@@ -3,6 +3,7 @@ use super::{
     Sensor,
 };
 use gwaihir_client_lib::periodic_checker::HasPeriodicChecker;
+use thiserror::Error;
 
 #[cfg(target_os = "windows")]
 mod windows;
@@ -10,9 +11,28 @@ mod windows;
 use windows::WindowsMicrophoneUsageSensor;
 
 #[cfg(target_os = "linux")]
-mod linux;
+mod pipewire;
 #[cfg(target_os = "linux")]
-use linux::LinuxMicrophoneUsageSensor;
+mod pulseaudio;
+#[cfg(target_os = "linux")]
+use super::pipewire::PipeWireError;
+#[cfg(target_os = "linux")]
+use pipewire::PipeWireMicrophoneUsageSensor;
+#[cfg(target_os = "linux")]
+use pulseaudio::PulseAudioMicrophoneUsageSensor;
+
+#[derive(Error, Debug)]
+pub enum MicrophoneUsageSensorError {
+    #[cfg(target_os = "linux")]
+    #[error("Neither PipeWire ({pipewire}) nor PulseAudio ({pulseaudio}) could be reached")]
+    NoAudioServer {
+        pipewire: PipeWireError,
+        pulseaudio: pulsectl::ControllerError,
+    },
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    #[error("Microphone usage isn't supported on this platform")]
+    Unsupported,
+}
 
 pub trait MicrophoneUsageSensor {
     fn check_microphone_usage(&mut self);
@@ -43,12 +63,38 @@ where
 }
 
 #[allow(unreachable_code)]
-pub fn try_get_sensor() -> Option<Box<dyn Sensor>> {
+pub fn try_get_sensor() -> Result<Box<dyn Sensor>, MicrophoneUsageSensorError> {
     #[cfg(target_os = "windows")]
-    return Some(Box::new(WindowsMicrophoneUsageSensor::new()));
+    return Ok(Box::new(WindowsMicrophoneUsageSensor::new()));
 
     #[cfg(target_os = "linux")]
-    return Some(Box::new(LinuxMicrophoneUsageSensor::new()));
+    return try_get_linux_sensor();
+
+    #[cfg(not(any(target_os = "windows", target_os = "linux")))]
+    Err(MicrophoneUsageSensorError::Unsupported)
+}
 
-    None::<Box<dyn Sensor>>
+/// Prefers asking PipeWire directly, since pipewire-pulse isn't always installed alongside it
+#[cfg(target_os = "linux")]
+fn try_get_linux_sensor() -> Result<Box<dyn Sensor>, MicrophoneUsageSensorError> {
+    let pipewire = match PipeWireMicrophoneUsageSensor::new() {
+        Ok(sensor) => {
+            log::info!("Using PipeWire for microphone usage");
+            return Ok(Box::new(sensor));
+        }
+        Err(e) => e,
+    };
+    match PulseAudioMicrophoneUsageSensor::new() {
+        Ok(sensor) => {
+            log::info!(
+                "Using PulseAudio for microphone usage, since PipeWire couldn't be reached: {}",
+                pipewire
+            );
+            Ok(Box::new(sensor))
+        }
+        Err(pulseaudio) => Err(MicrophoneUsageSensorError::NoAudioServer {
+            pipewire,
+            pulseaudio,
+        }),
+    }
 }
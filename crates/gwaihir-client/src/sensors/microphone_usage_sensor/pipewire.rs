@@ -0,0 +1,94 @@
+use std::{collections::BTreeSet, time::Duration};
+
+use gwaihir_client_lib::periodic_checker::{HasPeriodicChecker, PeriodicChecker};
+
+use crate::sensors::{
+    outputs::microphone_usage::{AppName, MicrophoneUsage},
+    pipewire::{self, PipeWireError, PipeWireMonitor, PipeWireProps},
+};
+
+/// The media class of nodes that record audio, as opposed to sources like the microphone itself
+const CAPTURE_STREAM_CLASS: &str = "Stream/Input/Audio";
+
+/// Asks PipeWire directly (through the shared `PipeWireMonitor`) for the streams recording audio,
+/// so it works without pipewire-pulse
+pub struct PipeWireMicrophoneUsageSensor {
+    periodic_checker: PeriodicChecker<MicrophoneUsage>,
+}
+
+impl HasPeriodicChecker<MicrophoneUsage> for PipeWireMicrophoneUsageSensor {
+    fn periodic_checker(&self) -> &PeriodicChecker<MicrophoneUsage> {
+        &self.periodic_checker
+    }
+
+    fn periodic_checker_mut(&mut self) -> &mut PeriodicChecker<MicrophoneUsage> {
+        &mut self.periodic_checker
+    }
+}
+
+impl PipeWireMicrophoneUsageSensor {
+    pub fn new() -> Result<Self, PipeWireError> {
+        let monitor = PipeWireMonitor::shared()?;
+        Ok(Self {
+            periodic_checker: PeriodicChecker::new(
+                Box::new(move || MicrophoneUsage {
+                    usage: capture_stream_apps(&monitor.latest()),
+                    num_unnamed_apps: 0,
+                }),
+                // The monitor only updates its snapshot this often anyway
+                Duration::from_secs(1),
+            ),
+        })
+    }
+}
+
+/// Apps recording more than one stream are only listed once
+fn capture_stream_apps(props: &[PipeWireProps]) -> Vec<AppName> {
+    let binaries: BTreeSet<_> = props
+        .iter()
+        .filter(|p| pipewire::get_str(p, "media.class") == Some(CAPTURE_STREAM_CLASS))
+        .filter_map(|p| pipewire::get_str(p, "application.process.binary"))
+        .collect();
+    binaries
+        .into_iter()
+        .map(|binary| AppName::new(binary.to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::pipewire::tests::EXAMPLE_DUMP;
+
+    #[test]
+    pub fn only_capture_streams_are_listed() {
+        let props = pipewire::parse_dump(EXAMPLE_DUMP.as_bytes()).unwrap();
+
+        let apps = capture_stream_apps(&props);
+
+        assert_eq!(apps, vec![AppName::new("zoom".to_string())]);
+    }
+
+    #[test]
+    pub fn apps_with_several_capture_streams_are_listed_once() {
+        let dump = r#"[
+            { "id": 75, "info": { "props": {
+                "media.class": "Stream/Input/Audio", "application.process.binary": "zoom" } } },
+            { "id": 76, "info": { "props": {
+                "media.class": "Stream/Input/Audio", "application.process.binary": "firefox" } } },
+            { "id": 77, "info": { "props": {
+                "media.class": "Stream/Input/Audio", "application.process.binary": "zoom" } } }
+        ]"#;
+        let props = pipewire::parse_dump(dump.as_bytes()).unwrap();
+
+        let apps = capture_stream_apps(&props);
+
+        assert_eq!(
+            apps,
+            vec![
+                AppName::new("firefox".to_string()),
+                AppName::new("zoom".to_string())
+            ]
+        );
+    }
+}
@@ -1,15 +1,19 @@
 use std::time::Duration;
 
 use gwaihir_client_lib::periodic_checker::{HasPeriodicChecker, PeriodicChecker};
-use pulsectl::controllers::{AppControl, SourceController};
+use pulsectl::{
+    controllers::{AppControl, SourceController},
+    ControllerError,
+};
 
 use crate::sensors::outputs::microphone_usage::{AppName, MicrophoneUsage};
 
-pub struct LinuxMicrophoneUsageSensor {
+/// Also works under PipeWire, as long as pipewire-pulse is installed
+pub struct PulseAudioMicrophoneUsageSensor {
     periodic_checker: PeriodicChecker<MicrophoneUsage>,
 }
 
-impl HasPeriodicChecker<MicrophoneUsage> for LinuxMicrophoneUsageSensor {
+impl HasPeriodicChecker<MicrophoneUsage> for PulseAudioMicrophoneUsageSensor {
     fn periodic_checker(&self) -> &PeriodicChecker<MicrophoneUsage> {
         &self.periodic_checker
     }
@@ -19,19 +23,20 @@ impl HasPeriodicChecker<MicrophoneUsage> for LinuxMicrophoneUsageSensor {
     }
 }
 
-impl LinuxMicrophoneUsageSensor {
-    pub fn new() -> Self {
-        Self {
+impl PulseAudioMicrophoneUsageSensor {
+    pub fn new() -> Result<Self, ControllerError> {
+        Ok(Self {
             periodic_checker: PeriodicChecker::new(
-                Box::new(get_mic_usage_closure()),
+                Box::new(get_mic_usage_closure(SourceController::create()?)),
                 Duration::from_millis(500),
             ),
-        }
+        })
     }
 }
 
-fn get_mic_usage_closure() -> impl FnMut() -> MicrophoneUsage {
-    let mut mic_data_handler = SourceController::create().unwrap();
+fn get_mic_usage_closure(
+    mut mic_data_handler: SourceController,
+) -> impl FnMut() -> MicrophoneUsage {
     move || {
         let apps = match mic_data_handler.list_applications() {
             Ok(apps) => apps
@@ -47,6 +52,9 @@ fn get_mic_usage_closure() -> impl FnMut() -> MicrophoneUsage {
                 vec![]
             }
         };
-        MicrophoneUsage { usage: apps }
+        MicrophoneUsage {
+            usage: apps,
+            num_unnamed_apps: 0,
+        }
     }
 }
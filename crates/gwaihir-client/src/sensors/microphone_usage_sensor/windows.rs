@@ -25,6 +25,7 @@ impl WindowsMicrophoneUsageSensor {
             periodic_checker: PeriodicChecker::new(
                 Box::new(|| MicrophoneUsage {
                     usage: get_all_programs_using_microphone(),
+                    num_unnamed_apps: 0,
                 }),
                 Duration::from_millis(500),
             ),
@@ -1,11 +1,29 @@
 use crate::sensors::outputs::sensor_output::SensorOutput;
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
 
 pub mod active_window_provider;
+pub mod activity_settings;
+pub mod app_rules;
+pub mod call_status_sensor;
+pub mod camera_usage_sensor;
+#[cfg(all(test, target_os = "linux"))]
+pub mod dbus_test_utils;
+pub mod idle_time_sensor;
 pub mod keyboard_mouse_event_provider;
 pub mod keyboard_mouse_sensor;
 pub mod lock_status_sensor;
+#[cfg(target_os = "linux")]
+pub mod logind;
 pub mod microphone_usage_sensor;
 pub mod outputs;
+pub mod overrides;
+#[cfg(target_os = "linux")]
+pub mod pipewire;
+pub mod privacy_level;
+pub mod screen_sharing_sensor;
+pub mod sharing_policy;
 pub mod window_activity_interpreter;
 pub mod window_activity_sensor;
 
@@ -13,3 +31,22 @@ pub trait Sensor {
     fn get_output(&mut self) -> SensorOutput;
     fn updated_sensor_outputs(&mut self, _outputs: &[SensorOutput]) {}
 }
+
+/// Which sensor tells others whether we're actively using our computer
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug, Default, Sequence)]
+pub enum ActivitySensorType {
+    /// Counts keystrokes/mouse movement via global input hooks
+    #[default]
+    KeyboardMouse,
+    /// Only asks the OS how long it's been since the last input
+    IdleTime,
+}
+
+impl Display for ActivitySensorType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivitySensorType::KeyboardMouse => write!(f, "Keyboard/Mouse Usage"),
+            ActivitySensorType::IdleTime => write!(f, "Idle Time"),
+        }
+    }
+}
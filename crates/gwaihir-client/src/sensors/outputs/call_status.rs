@@ -0,0 +1,36 @@
+use egui::{Color32, RichText};
+use gwaihir_client_lib::{
+    chrono::{DateTime, Local, Utc},
+    UniqueUserId,
+};
+use serde::{Deserialize, Serialize};
+
+use super::sensor_output::SensorWidget;
+
+/// Whether the user is in a call, see `CallStatusSensor` for how it's decided
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct CallStatus {
+    pub in_call: bool,
+    /// When the current call started. `None` when not in a call.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl SensorWidget<()> for CallStatus {
+    fn show(&self, ui: &mut egui::Ui, _id: &UniqueUserId) {
+        if !self.in_call {
+            return;
+        }
+
+        let response = ui.label(
+            RichText::new(" 📞 In a call ")
+                .color(Color32::WHITE)
+                .background_color(Color32::from_rgb(0x80, 0x20, 0x80)),
+        );
+        if let Some(since) = self.since {
+            response.on_hover_text_at_pointer(format!(
+                "Since {}",
+                DateTime::<Local>::from(since).format("%H:%M")
+            ));
+        }
+    }
+}
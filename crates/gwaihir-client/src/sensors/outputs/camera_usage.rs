@@ -0,0 +1,44 @@
+use egui::CollapsingHeader;
+use gwaihir_client_lib::UniqueUserId;
+use serde::{Deserialize, Serialize};
+
+use super::microphone_usage::AppName;
+use crate::sensors::outputs::sensor_output::SensorWidget;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct CameraUsage {
+    pub usage: Vec<AppName>,
+    /// Apps using a camera whose names weren't shared, see `PrivacyLevel`
+    #[serde(default)]
+    pub num_unnamed_apps: usize,
+}
+
+impl CameraUsage {
+    pub fn num_apps(&self) -> usize {
+        self.usage.len() + self.num_unnamed_apps
+    }
+
+    pub fn without_app_names(&self) -> Self {
+        Self {
+            usage: vec![],
+            num_unnamed_apps: self.num_apps(),
+        }
+    }
+}
+
+impl SensorWidget<()> for CameraUsage {
+    fn show(&self, ui: &mut egui::Ui, id: &UniqueUserId) {
+        CollapsingHeader::new("Camera Usage")
+            .default_open(true)
+            .id_source(format!("{}_camera", id.as_ref()))
+            .show(ui, |ui| {
+                ui.label(format!(
+                    "{} app(s) currently using a camera:",
+                    self.num_apps()
+                ));
+                for app in self.usage.iter() {
+                    ui.label(app.as_ref());
+                }
+            });
+    }
+}
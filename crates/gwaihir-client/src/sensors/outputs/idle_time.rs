@@ -0,0 +1,47 @@
+use chrono_humanize::HumanTime;
+use gwaihir_client_lib::{
+    chrono::{DateTime, Duration, Utc},
+    UniqueUserId,
+};
+use serde::{Deserialize, Serialize};
+
+use super::sensor_output::SensorWidget;
+
+/// How long someone has to go without input before we show them as idle
+const IDLE_THRESHOLD_SECONDS: i64 = 60;
+
+/// How long the user has gone without touching their keyboard or mouse, according to the OS
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct IdleTime {
+    /// When the user last provided any input, or `None` if they just did. Sending when input
+    /// stopped rather than the idle duration means this only changes when the user pauses or
+    /// comes back, and leaves deciding how long counts as idle to whoever reads it.
+    pub idle_since: Option<DateTime<Utc>>,
+}
+
+impl IdleTime {
+    /// Whether the user had gone without input long enough at `now` to be shown as idle
+    pub fn is_idle(&self, now: DateTime<Utc>) -> bool {
+        self.idle_duration(now) >= Duration::seconds(IDLE_THRESHOLD_SECONDS)
+    }
+
+    pub fn idle_duration(&self, now: DateTime<Utc>) -> Duration {
+        match self.idle_since {
+            Some(idle_since) => now.signed_duration_since(idle_since).max(Duration::zero()),
+            None => Duration::zero(),
+        }
+    }
+}
+
+impl SensorWidget<()> for IdleTime {
+    fn show(&self, ui: &mut egui::Ui, _id: &UniqueUserId) {
+        match self.idle_since {
+            Some(idle_since) if self.is_idle(Utc::now()) => {
+                ui.label(format!("Idle since {}", HumanTime::from(idle_since)));
+            }
+            _ => {
+                ui.label("Active at their computer");
+            }
+        }
+    }
+}
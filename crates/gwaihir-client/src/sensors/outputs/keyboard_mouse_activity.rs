@@ -1,18 +1,44 @@
 use super::sensor_output::SensorWidget;
-use crate::{sensors::keyboard_mouse_sensor, ui::ui_extension_methods::UIExtensionMethods};
+use crate::{
+    sensors::activity_settings::{DEFAULT_BUCKET_DURATION_SECONDS, DEFAULT_NUM_BUCKETS},
+    ui::ui_extension_methods::UIExtensionMethods,
+};
 use egui::{CollapsingHeader, Color32, RichText, Stroke, Vec2};
 use egui_plot::{uniform_grid_spacer, Bar, BarChart, Plot};
+use enum_iterator::Sequence;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
 
 const ONE_THIRD: f64 = 1.0 / 3.0;
 const TWO_THIRDS: f64 = 2.0 / 3.0;
 
+/// How much of the most recent history counts as what the user is doing right now
+const RECENT_SECONDS: u64 = 60;
+/// Typing faster than this recently, without having kept it up, is a burst
+const TYPING_BURST_KEY_PRESSES_PER_MINUTE: f64 = 60.0;
+/// Typing in at least this much of the history counts as sustained focus
+const SUSTAINED_FOCUS_TYPING_FRACTION: f64 = 0.75;
+/// Less history than this can't tell a burst from sustained focus
+const SUSTAINED_FOCUS_MIN_SECONDS: u64 = 120;
+
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct KeyboardMouseActivity {
     pub keyboard_usage: KeyboardMouseActivityData,
     pub mouse_movement: KeyboardMouseActivityData,
     pub mouse_button_usage: KeyboardMouseActivityData,
+    /// Older clients didn't send these, and always used the defaults
+    #[serde(default = "default_bucket_duration_seconds")]
+    pub bucket_duration_seconds: u64,
+    #[serde(default = "default_num_buckets")]
+    pub num_buckets: usize,
+}
+
+fn default_bucket_duration_seconds() -> u64 {
+    DEFAULT_BUCKET_DURATION_SECONDS
+}
+
+fn default_num_buckets() -> usize {
+    DEFAULT_NUM_BUCKETS
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
@@ -20,6 +46,20 @@ pub struct KeyboardMouseActivityData {
     pub data: Vec<f64>,
 }
 
+/// A guess at what the user is doing, from the pattern of their keyboard/mouse activity
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default, Sequence)]
+pub enum ActivityState {
+    /// No keyboard or mouse activity recently
+    #[default]
+    Idle,
+    /// Moving the mouse or clicking, but hardly typing
+    Reading,
+    /// Typing quickly, but only recently
+    TypingBurst,
+    /// Typing steadily for most of the history
+    SustainedFocus,
+}
+
 struct UsageSummary {
     level: UsageLevel,
     min: f64,
@@ -40,8 +80,11 @@ impl SensorWidget<()> for KeyboardMouseActivity {
         let mouse_button_summary = summarize(&self.mouse_button_usage);
         let mouse_movement_summary = summarize(&self.mouse_movement);
 
+        let activity_state = self.activity_state();
+
         let text = ui.create_default_layout_job(vec![
-            RichText::new("Keyboard: ").color(ui.visuals().text_color()),
+            RichText::new(activity_state.to_string()).color(activity_state.color()),
+            RichText::new(" - Keyboard: ").color(ui.visuals().text_color()),
             get_summary_text(&keyboard_summary),
             RichText::new(", Mouse Buttons: ").color(ui.visuals().text_color()),
             get_summary_text(&mouse_button_summary),
@@ -57,6 +100,7 @@ impl SensorWidget<()> for KeyboardMouseActivity {
                     "Keyboard",
                     &self.keyboard_usage.data,
                     keyboard_summary,
+                    self.bucket_duration_seconds,
                     ui,
                     id,
                 );
@@ -64,6 +108,7 @@ impl SensorWidget<()> for KeyboardMouseActivity {
                     "Mouse Buttons",
                     &self.mouse_button_usage.data,
                     mouse_button_summary,
+                    self.bucket_duration_seconds,
                     ui,
                     id,
                 );
@@ -71,6 +116,7 @@ impl SensorWidget<()> for KeyboardMouseActivity {
                     "Mouse Movement",
                     &self.mouse_movement.data,
                     mouse_movement_summary,
+                    self.bucket_duration_seconds,
                     ui,
                     id,
                 );
@@ -79,12 +125,96 @@ impl SensorWidget<()> for KeyboardMouseActivity {
 }
 
 impl KeyboardMouseActivity {
+    /// Scaled to the default history length, so that triggers comparing it to a constant mean
+    /// the same thing whatever the sender's settings are
     pub fn get_total_usage(&self) -> f64 {
-        self.keyboard_usage.total() + self.mouse_movement.total() + self.mouse_button_usage.total()
+        let total = self.keyboard_usage.total()
+            + self.mouse_movement.total()
+            + self.mouse_button_usage.total();
+        let default_history_seconds =
+            (DEFAULT_BUCKET_DURATION_SECONDS * DEFAULT_NUM_BUCKETS as u64) as f64;
+        match self.history_seconds() {
+            0 => total,
+            history_seconds => total * default_history_seconds / history_seconds as f64,
+        }
+    }
+
+    /// Usage in the latest bucket
+    pub fn most_recent_usage(&self) -> f64 {
+        [
+            &self.keyboard_usage,
+            &self.mouse_movement,
+            &self.mouse_button_usage,
+        ]
+        .iter()
+        .filter_map(|d| d.data.last())
+        .sum()
+    }
+
+    /// How fast keys were pressed in the latest bucket
+    pub fn recent_key_presses_per_minute(&self) -> f64 {
+        match (
+            self.keyboard_usage.data.last(),
+            self.bucket_duration_seconds,
+        ) {
+            (Some(key_presses), seconds) if seconds > 0 => key_presses * 60.0 / seconds as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Classifies the pattern of activity across the buckets. Sustained focus wins over a typing
+    /// burst, since someone who has been typing for minutes is deep in something rather than
+    /// dashing off a quick message.
+    pub fn activity_state(&self) -> ActivityState {
+        let recent_buckets = self.num_recent_buckets();
+        let recent_total =
+            |d: &KeyboardMouseActivityData| d.data.iter().rev().take(recent_buckets).sum::<f64>();
+        let recent_key_presses = recent_total(&self.keyboard_usage);
+        let recent_mouse_usage =
+            recent_total(&self.mouse_movement) + recent_total(&self.mouse_button_usage);
+
+        if recent_key_presses == 0.0 && recent_mouse_usage == 0.0 {
+            return ActivityState::Idle;
+        }
+        if self.has_sustained_typing() {
+            return ActivityState::SustainedFocus;
+        }
+
+        let recent_seconds = recent_buckets.min(self.keyboard_usage.data.len()) as u64
+            * self.bucket_duration_seconds;
+        let key_presses_per_minute = match recent_seconds {
+            0 => 0.0,
+            seconds => recent_key_presses * 60.0 / seconds as f64,
+        };
+        if key_presses_per_minute > TYPING_BURST_KEY_PRESSES_PER_MINUTE {
+            ActivityState::TypingBurst
+        } else {
+            ActivityState::Reading
+        }
     }
 
     pub fn is_full(&self) -> bool {
-        self.keyboard_usage.data.len() == keyboard_mouse_sensor::NUM_BUCKETS_TO_KEEP
+        self.keyboard_usage.data.len() >= self.num_buckets
+    }
+
+    fn num_recent_buckets(&self) -> usize {
+        match self.bucket_duration_seconds {
+            0 => 1,
+            seconds => (RECENT_SECONDS / seconds).max(1) as usize,
+        }
+    }
+
+    fn has_sustained_typing(&self) -> bool {
+        let key_presses = &self.keyboard_usage.data;
+        if (key_presses.len() as u64 * self.bucket_duration_seconds) < SUSTAINED_FOCUS_MIN_SECONDS {
+            return false;
+        }
+        let typing_buckets = key_presses.iter().filter(|&&k| k > 0.0).count();
+        typing_buckets as f64 >= key_presses.len() as f64 * SUSTAINED_FOCUS_TYPING_FRACTION
+    }
+
+    fn history_seconds(&self) -> u64 {
+        self.bucket_duration_seconds * self.num_buckets as u64
     }
 }
 
@@ -94,6 +224,28 @@ impl KeyboardMouseActivityData {
     }
 }
 
+impl ActivityState {
+    fn color(&self) -> Color32 {
+        match self {
+            ActivityState::Idle => Color32::GRAY,
+            ActivityState::Reading => Color32::LIGHT_BLUE,
+            ActivityState::TypingBurst => Color32::GOLD,
+            ActivityState::SustainedFocus => Color32::RED,
+        }
+    }
+}
+
+impl Display for ActivityState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ActivityState::Idle => write!(f, "Idle"),
+            ActivityState::Reading => write!(f, "Reading"),
+            ActivityState::TypingBurst => write!(f, "Typing Burst"),
+            ActivityState::SustainedFocus => write!(f, "Sustained Focus"),
+        }
+    }
+}
+
 impl UsageLevel {
     fn from_fractional(fractional_usage: f64) -> Option<Self> {
         match fractional_usage {
@@ -130,6 +282,7 @@ fn show_activity_graph_section(
     collapse_header_text: &str,
     activity_data: &[f64],
     data_summary: Option<UsageSummary>,
+    bucket_duration_seconds: u64,
     ui: &mut egui::Ui,
     id: &gwaihir_client_lib::UniqueUserId,
 ) {
@@ -141,6 +294,7 @@ fn show_activity_graph_section(
                 show_activity_graph(
                     activity_data,
                     data_summary,
+                    bucket_duration_seconds,
                     ui,
                     format!("{}_{}_plot", id.as_ref(), collapse_header_text),
                 );
@@ -153,10 +307,11 @@ fn show_activity_graph_section(
 fn show_activity_graph(
     activity_data: &[f64],
     data_summary: Option<UsageSummary>,
+    bucket_duration_seconds: u64,
     ui: &mut egui::Ui,
     unique_plot_id: String,
 ) {
-    let bucket_duration_s = keyboard_mouse_sensor::BUCKET_DURATION_SECONDS as f64;
+    let bucket_duration_s = bucket_duration_seconds as f64;
     let bars: Vec<_> = activity_data
         .iter()
         .rev()
@@ -253,3 +408,136 @@ fn get_usage_level(value: f64, min: f64, max: f64) -> Option<UsageLevel> {
 
     UsageLevel::from_fractional(fractional_usage)
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    fn activity(
+        data: Vec<f64>,
+        bucket_duration_seconds: u64,
+        num_buckets: usize,
+    ) -> KeyboardMouseActivity {
+        KeyboardMouseActivity {
+            keyboard_usage: KeyboardMouseActivityData { data: data.clone() },
+            mouse_movement: KeyboardMouseActivityData {
+                data: vec![0.0; data.len()],
+            },
+            mouse_button_usage: KeyboardMouseActivityData {
+                data: vec![0.0; data.len()],
+            },
+            bucket_duration_seconds,
+            num_buckets,
+        }
+    }
+
+    #[test]
+    pub fn missing_metadata_uses_defaults() {
+        let json = r#"{
+            "keyboard_usage": { "data": [1.0] },
+            "mouse_movement": { "data": [0.0] },
+            "mouse_button_usage": { "data": [0.0] }
+        }"#;
+
+        let activity: KeyboardMouseActivity = serde_json::from_str(json).unwrap();
+
+        assert_eq!(
+            activity.bucket_duration_seconds,
+            DEFAULT_BUCKET_DURATION_SECONDS
+        );
+        assert_eq!(activity.num_buckets, DEFAULT_NUM_BUCKETS);
+        assert!(!activity.is_full());
+    }
+
+    #[test]
+    pub fn total_usage_is_scaled_to_default_history_length() {
+        // 60 buckets of 10s is twice the default 5 minutes of history
+        let long_history = activity(vec![1.0; 60], 10, 60);
+        assert!(long_history.is_full());
+        assert_eq!(long_history.get_total_usage(), 30.0);
+
+        let default_history = activity(vec![1.0; 30], 10, 30);
+        assert_eq!(default_history.get_total_usage(), 30.0);
+    }
+
+    #[test]
+    pub fn no_recent_activity_is_idle() {
+        assert_eq!(
+            activity(vec![], 10, 30).activity_state(),
+            ActivityState::Idle
+        );
+
+        // Typed a lot, but not in the last minute
+        let mut key_presses = vec![20.0; 24];
+        key_presses.extend([0.0; 6]);
+        assert_eq!(
+            activity(key_presses, 10, 30).activity_state(),
+            ActivityState::Idle
+        );
+    }
+
+    #[test]
+    pub fn mouse_use_without_typing_is_reading() {
+        let mut reading = activity(vec![0.0; 30], 10, 30);
+        reading.mouse_movement.data[29] = 150.0;
+        reading.mouse_button_usage.data[27] = 1.0;
+        assert_eq!(reading.activity_state(), ActivityState::Reading);
+
+        // The odd key press doesn't change that
+        reading.keyboard_usage.data[28] = 2.0;
+        assert_eq!(reading.activity_state(), ActivityState::Reading);
+    }
+
+    #[test]
+    pub fn fast_recent_typing_is_a_burst() {
+        let mut key_presses = vec![0.0; 24];
+        key_presses.extend([15.0; 6]);
+        assert_eq!(
+            activity(key_presses, 10, 30).activity_state(),
+            ActivityState::TypingBurst
+        );
+
+        // Too little history to know whether it's been kept up
+        assert_eq!(
+            activity(vec![15.0; 6], 10, 30).activity_state(),
+            ActivityState::TypingBurst
+        );
+    }
+
+    #[test]
+    pub fn typing_through_most_of_the_history_is_sustained_focus() {
+        let mut key_presses = vec![5.0; 30];
+        // Pauses to think don't break focus
+        key_presses[3] = 0.0;
+        key_presses[10] = 0.0;
+        key_presses[17] = 0.0;
+        assert_eq!(
+            activity(key_presses, 10, 30).activity_state(),
+            ActivityState::SustainedFocus
+        );
+
+        let mut interrupted = vec![0.0; 15];
+        interrupted.extend([5.0; 15]);
+        assert_ne!(
+            activity(interrupted, 10, 30).activity_state(),
+            ActivityState::SustainedFocus
+        );
+    }
+
+    #[test]
+    pub fn classification_accounts_for_bucket_duration() {
+        // 12 key presses every 5 seconds is 144 a minute
+        let mut key_presses = vec![0.0; 48];
+        key_presses.extend([12.0; 12]);
+        assert_eq!(
+            activity(key_presses, 5, 60).activity_state(),
+            ActivityState::TypingBurst
+        );
+
+        // A single 10 minute bucket covers more than the recent window
+        assert_eq!(
+            activity(vec![0.0, 700.0], 600, 2).activity_state(),
+            ActivityState::TypingBurst
+        );
+    }
+}
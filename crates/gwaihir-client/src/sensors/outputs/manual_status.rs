@@ -0,0 +1,80 @@
+use egui::{Color32, RichText};
+use enum_iterator::Sequence;
+use gwaihir_client_lib::{
+    chrono::{DateTime, Local, Utc},
+    UniqueUserId,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+use super::sensor_output::SensorWidget;
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default, Sequence)]
+pub enum Availability {
+    #[default]
+    Available,
+    Focusing,
+    Away,
+    DoNotDisturb,
+}
+
+/// A status explicitly set by the user, rather than inferred from their activity
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct ManualStatus {
+    pub availability: Availability,
+    pub message: String,
+    /// If set, the status no longer applies after this time
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl ManualStatus {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| now >= expires_at)
+    }
+}
+
+impl SensorWidget<()> for ManualStatus {
+    fn show(&self, ui: &mut egui::Ui, _id: &UniqueUserId) {
+        if self.is_expired(Utc::now()) {
+            return;
+        }
+
+        let mut badge = self.availability.to_string();
+        if !self.message.is_empty() {
+            badge = format!("{}: {}", badge, self.message);
+        }
+        let response = ui.label(
+            RichText::new(format!(" {} ", badge))
+                .color(Color32::WHITE)
+                .background_color(self.availability.color()),
+        );
+        if let Some(expires_at) = self.expires_at {
+            response.on_hover_text_at_pointer(format!(
+                "Until {}",
+                DateTime::<Local>::from(expires_at).format("%H:%M")
+            ));
+        }
+    }
+}
+
+impl Availability {
+    pub fn color(&self) -> Color32 {
+        match self {
+            Availability::Available => Color32::DARK_GREEN,
+            Availability::Focusing => Color32::from_rgb(0xB0, 0x70, 0x00),
+            Availability::Away => Color32::DARK_GRAY,
+            Availability::DoNotDisturb => Color32::DARK_RED,
+        }
+    }
+}
+
+impl Display for Availability {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Availability::Available => write!(f, "Available"),
+            Availability::Focusing => write!(f, "Focusing"),
+            Availability::Away => write!(f, "Away"),
+            Availability::DoNotDisturb => write!(f, "Do Not Disturb"),
+        }
+    }
+}
@@ -8,11 +8,27 @@ use crate::sensors::outputs::sensor_output::SensorWidget;
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
 pub struct MicrophoneUsage {
     pub usage: Vec<AppName>,
+    /// Apps using the microphone whose names weren't shared, see `PrivacyLevel`
+    #[serde(default)]
+    pub num_unnamed_apps: usize,
 }
 
 #[nutype(derive(Serialize, Deserialize, PartialEq, AsRef, Clone, Into, Debug, From))]
 pub struct AppName(String);
 
+impl MicrophoneUsage {
+    pub fn num_apps(&self) -> usize {
+        self.usage.len() + self.num_unnamed_apps
+    }
+
+    pub fn without_app_names(&self) -> Self {
+        Self {
+            usage: vec![],
+            num_unnamed_apps: self.num_apps(),
+        }
+    }
+}
+
 impl SensorWidget<()> for MicrophoneUsage {
     fn show(&self, ui: &mut egui::Ui, id: &UniqueUserId) {
         CollapsingHeader::new("Microphone Usage")
@@ -21,7 +37,7 @@ impl SensorWidget<()> for MicrophoneUsage {
             .show(ui, |ui| {
                 ui.label(format!(
                     "{} app(s) currently listening to the microphone:",
-                    self.usage.len()
+                    self.num_apps()
                 ));
                 for app in self.usage.iter() {
                     let pretty_name = app.as_ref().replace('#', "\\");
@@ -1,7 +1,12 @@
+pub mod call_status;
+pub mod camera_usage;
+pub mod idle_time;
 pub mod keyboard_mouse_activity;
 pub mod lock_status;
+pub mod manual_status;
 pub mod microphone_usage;
 pub mod online_status;
+pub mod screen_sharing;
 pub mod sensor_output;
 pub mod sensor_outputs;
 pub mod summarized_window_activity;
@@ -0,0 +1,37 @@
+use egui::{Color32, RichText};
+use gwaihir_client_lib::{
+    chrono::{DateTime, Local, Utc},
+    UniqueUserId,
+};
+use serde::{Deserialize, Serialize};
+
+use super::sensor_output::SensorWidget;
+
+/// Whether the user is sharing their screen, e.g. presenting in a meeting
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct ScreenSharing {
+    pub sharing: bool,
+    /// When they started sharing. `None` when not sharing.
+    pub since: Option<DateTime<Utc>>,
+}
+
+impl SensorWidget<()> for ScreenSharing {
+    fn show(&self, ui: &mut egui::Ui, _id: &UniqueUserId) {
+        if !self.sharing {
+            return;
+        }
+
+        let response = ui.label(
+            RichText::new(" 🖵 Presenting ")
+                .strong()
+                .color(Color32::WHITE)
+                .background_color(Color32::RED),
+        );
+        if let Some(since) = self.since {
+            response.on_hover_text_at_pointer(format!(
+                "Sharing their screen since {}",
+                DateTime::<Local>::from(since).format("%H:%M")
+            ));
+        }
+    }
+}
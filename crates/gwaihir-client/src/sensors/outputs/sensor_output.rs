@@ -1,13 +1,16 @@
 use gwaihir_client_lib::UniqueUserId;
+use kinded::Kinded;
 use serde::{Deserialize, Serialize};
 
 use super::{
+    call_status::CallStatus, camera_usage::CameraUsage, idle_time::IdleTime,
     keyboard_mouse_activity::KeyboardMouseActivity, lock_status::LockStatus,
-    microphone_usage::MicrophoneUsage, online_status::OnlineStatus,
-    summarized_window_activity::SummarizedWindowActivity,
+    manual_status::ManualStatus, microphone_usage::MicrophoneUsage, online_status::OnlineStatus,
+    screen_sharing::ScreenSharing, summarized_window_activity::SummarizedWindowActivity,
 };
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Kinded)]
+#[kinded(display = "Title Case", derive(Hash, Serialize, Deserialize))]
 pub enum SensorOutput {
     Empty,
     LockStatus(LockStatus),
@@ -15,6 +18,11 @@ pub enum SensorOutput {
     OnlineStatus(OnlineStatus),
     SummarizedWindowActivity(SummarizedWindowActivity),
     KeyboardMouseActivity(KeyboardMouseActivity),
+    ManualStatus(ManualStatus),
+    IdleTime(IdleTime),
+    CallStatus(CallStatus),
+    CameraUsage(CameraUsage),
+    ScreenSharing(ScreenSharing),
 }
 
 pub trait SensorWidget<R> {
@@ -22,14 +30,33 @@ pub trait SensorWidget<R> {
 }
 
 impl SensorOutput {
+    pub fn should_send_to_remote(&self) -> bool {
+        self.kind().should_send_to_remote()
+    }
+}
+
+impl SensorOutputKind {
     pub fn should_send_to_remote(&self) -> bool {
         match self {
-            SensorOutput::Empty => false,
-            SensorOutput::LockStatus(_) => false,
-            SensorOutput::MicrophoneUsage(_) => true,
-            SensorOutput::OnlineStatus(_) => true,
-            SensorOutput::SummarizedWindowActivity(_) => true,
-            SensorOutput::KeyboardMouseActivity(_) => true,
+            SensorOutputKind::Empty => false,
+            SensorOutputKind::LockStatus => false,
+            SensorOutputKind::MicrophoneUsage => true,
+            SensorOutputKind::OnlineStatus => true,
+            SensorOutputKind::SummarizedWindowActivity => true,
+            SensorOutputKind::KeyboardMouseActivity => true,
+            SensorOutputKind::ManualStatus => true,
+            SensorOutputKind::IdleTime => true,
+            SensorOutputKind::CallStatus => true,
+            SensorOutputKind::CameraUsage => true,
+            SensorOutputKind::ScreenSharing => true,
         }
     }
+
+    /// The kinds of output that can ever be shared with other users
+    pub fn all_shareable() -> impl Iterator<Item = SensorOutputKind> {
+        SensorOutputKind::all()
+            .iter()
+            .copied()
+            .filter(|kind| kind.should_send_to_remote())
+    }
 }
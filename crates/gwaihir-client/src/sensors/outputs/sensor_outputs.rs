@@ -4,13 +4,26 @@ use gwaihir_client_lib::{
 };
 use log::error;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 use super::{
-    keyboard_mouse_activity::KeyboardMouseActivity, microphone_usage::MicrophoneUsage,
-    online_status::OnlineStatus, sensor_output::SensorOutput,
+    call_status::CallStatus,
+    camera_usage::CameraUsage,
+    idle_time::IdleTime,
+    keyboard_mouse_activity::{ActivityState, KeyboardMouseActivity},
+    manual_status::{Availability, ManualStatus},
+    microphone_usage::MicrophoneUsage,
+    online_status::OnlineStatus,
+    screen_sharing::ScreenSharing,
+    sensor_output::{SensorOutput, SensorOutputKind},
     summarized_window_activity::SummarizedWindowActivity,
 };
 
+/// Typing faster than this probably means the user is busy writing something
+const BUSY_TYPING_KEY_PRESSES_PER_MINUTE: f64 = 100.0;
+/// Idle for longer than this probably means the user stepped away
+const IDLE_MINUTES_UNTIL_INACTIVE: i64 = 5;
+
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
 pub struct SensorOutputs {
     pub outputs: Vec<SensorOutput>,
@@ -42,8 +55,13 @@ impl SensorOutputs {
             .and_then(|a| a.is_full().then_some(a.get_total_usage()))
     }
 
+    pub fn get_activity_state(&self) -> Option<ActivityState> {
+        self.find_keyboard_mouse_activity()
+            .map(|a| a.activity_state())
+    }
+
     pub fn get_num_apps_using_microphone(&self) -> Option<usize> {
-        self.find_microphone_usage().map(|u| u.usage.len())
+        self.find_microphone_usage().map(|u| u.num_apps())
     }
 
     pub fn active_window_duration(&self) -> Option<Duration> {
@@ -67,6 +85,109 @@ impl SensorOutputs {
         self.find_sensor_output(|o| match_variant!(o, SensorOutput::MicrophoneUsage))
     }
 
+    pub fn find_camera_usage(&self) -> Option<&CameraUsage> {
+        self.find_sensor_output(|o| match_variant!(o, SensorOutput::CameraUsage))
+    }
+
+    pub fn find_screen_sharing(&self) -> Option<&ScreenSharing> {
+        self.find_sensor_output(|o| match_variant!(o, SensorOutput::ScreenSharing))
+    }
+
+    pub fn find_call_status(&self) -> Option<&CallStatus> {
+        self.find_sensor_output(|o| match_variant!(o, SensorOutput::CallStatus))
+    }
+
+    pub fn find_idle_time(&self) -> Option<&IdleTime> {
+        self.find_sensor_output(|o| match_variant!(o, SensorOutput::IdleTime))
+    }
+
+    pub fn idle_duration(&self) -> Option<Duration> {
+        self.find_idle_time().map(|i| i.idle_duration(Utc::now()))
+    }
+
+    /// Only returns a status that hasn't expired yet
+    pub fn find_manual_status(&self) -> Option<&ManualStatus> {
+        self.find_sensor_output(|o| match_variant!(o, SensorOutput::ManualStatus))
+            .filter(|s| !s.is_expired(Utc::now()))
+    }
+
+    /// Users who haven't set a status are assumed to be available
+    pub fn get_availability(&self) -> Availability {
+        self.find_manual_status()
+            .map(|s| s.availability)
+            .unwrap_or_default()
+    }
+
+    /// Whether the user is probably in a call or meeting. Clients which don't share their call
+    /// status are judged by their microphone and the category of their current app instead.
+    pub fn is_in_meeting(&self) -> bool {
+        if let Some(call_status) = self.find_call_status() {
+            return call_status.in_call;
+        }
+
+        let using_microphone = self.get_num_apps_using_microphone().is_some_and(|n| n > 0);
+        let in_meeting_app = self
+            .find_summarized_window_activity()
+            .is_some_and(|a| a.in_meeting_category());
+        using_microphone || in_meeting_app
+    }
+
+    /// Whether the user has recently used their keyboard/mouse, if we know
+    pub fn is_active(&self) -> Option<bool> {
+        let keyboard_mouse_active = self
+            .find_keyboard_mouse_activity()
+            .map(|a| a.get_total_usage() > 0.0);
+        let idle_time_active = self
+            .idle_duration()
+            .map(|d| d < Duration::minutes(IDLE_MINUTES_UNTIL_INACTIVE));
+        keyboard_mouse_active.or(idle_time_active)
+    }
+
+    /// A guess at how good a time it is to reach out, from 0 (unreachable) to 100 (free to talk)
+    pub fn get_availability_score(&self) -> f64 {
+        match self.get_availability() {
+            Availability::DoNotDisturb => return 0.0,
+            Availability::Away => return 10.0,
+            Availability::Focusing => return 25.0,
+            Availability::Available => {}
+        }
+        if self.find_online_status().is_some_and(|s| !s.online) {
+            return 0.0;
+        }
+        if self.find_screen_sharing().is_some_and(|s| s.sharing) {
+            return 5.0;
+        }
+        if self.is_locked() == Some(true) {
+            return 10.0;
+        }
+        if self.is_in_meeting() {
+            return 15.0;
+        }
+
+        let busy_typing = self.find_keyboard_mouse_activity().is_some_and(|a| {
+            a.recent_key_presses_per_minute() > BUSY_TYPING_KEY_PRESSES_PER_MINUTE
+        });
+        match self.is_active() {
+            Some(true) if busy_typing => 60.0,
+            Some(true) => 90.0,
+            // They may have stepped away
+            Some(false) => 50.0,
+            None => 75.0,
+        }
+    }
+
+    /// Keeps only the outputs whose kind is in `shared`
+    pub fn filtered(&self, shared: &HashSet<SensorOutputKind>) -> SensorOutputs {
+        SensorOutputs {
+            outputs: self
+                .outputs
+                .iter()
+                .filter(|o| shared.contains(&o.kind()))
+                .cloned()
+                .collect(),
+        }
+    }
+
     pub fn find_sensor_output<R>(&self, f: impl FnMut(&SensorOutput) -> Option<&R>) -> Option<&R> {
         self.outputs.iter().find_map(f)
     }
@@ -82,3 +203,76 @@ impl AcceptsOnlineStatus for SensorOutputs {
             .push(SensorOutput::OnlineStatus(OnlineStatus { online }))
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::microphone_usage::MicrophoneUsage;
+    use maplit::hashset;
+
+    #[test]
+    pub fn filtered_keeps_only_shared_kinds() {
+        let outputs = SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage::default()),
+            ],
+        };
+
+        let filtered = outputs.filtered(&hashset![SensorOutputKind::OnlineStatus]);
+
+        assert_eq!(
+            filtered.outputs,
+            vec![SensorOutput::OnlineStatus(OnlineStatus { online: true })]
+        );
+        assert!(outputs.filtered(&HashSet::new()).outputs.is_empty());
+    }
+
+    #[test]
+    pub fn get_availability_ignores_expired_manual_status() {
+        let status = |expires_at| SensorOutputs {
+            outputs: vec![SensorOutput::ManualStatus(ManualStatus {
+                availability: Availability::DoNotDisturb,
+                message: "Writing".to_string(),
+                expires_at,
+            })],
+        };
+
+        assert_eq!(status(None).get_availability(), Availability::DoNotDisturb);
+        assert_eq!(
+            status(Some(Utc::now() - Duration::minutes(1))).get_availability(),
+            Availability::Available
+        );
+        assert_eq!(
+            SensorOutputs { outputs: vec![] }.get_availability(),
+            Availability::Available
+        );
+    }
+
+    #[test]
+    pub fn availability_score_drops_in_meetings_and_with_manual_status() {
+        let outputs = |microphone_usage: Vec<String>| SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage {
+                    usage: microphone_usage.into_iter().map(Into::into).collect(),
+                    ..Default::default()
+                }),
+            ],
+        };
+        let free = outputs(Vec::new());
+        let in_meeting = outputs(vec!["Zoom".to_string()]);
+        let mut do_not_disturb = free.clone();
+        do_not_disturb
+            .outputs
+            .push(SensorOutput::ManualStatus(ManualStatus {
+                availability: Availability::DoNotDisturb,
+                message: String::new(),
+                expires_at: None,
+            }));
+
+        assert!(in_meeting.is_in_meeting());
+        assert!(in_meeting.get_availability_score() < free.get_availability_score());
+        assert_eq!(do_not_disturb.get_availability_score(), 0.0);
+    }
+}
@@ -9,7 +9,11 @@ use serde::{Deserialize, Serialize};
 use serde_with::serde_as;
 use serde_with::DurationSeconds;
 
-use crate::sensors::window_activity_interpreter::DEFAULT_TIME_TO_KEEP_WINDOW_ACTIVITY;
+use crate::sensors::activity_settings::DEFAULT_WINDOW_ACTIVITY_MINUTES;
+use crate::sensors::app_rules::UNCATEGORIZED;
+
+/// Categories (see `AppRules`) which mean the user is probably in a meeting
+const MEETING_CATEGORIES: [&str; 2] = ["meeting", "meetings"];
 use crate::ui::ui_extension_methods::UIExtensionMethods;
 
 use super::sensor_output::SensorOutput;
@@ -19,18 +23,29 @@ use super::{
     window_activity::{ActiveWindow, WindowActivity},
 };
 
-const DEFAULT_MAX_NUM_APPS_IN_SUMMARY: usize = 5;
-
+#[serde_as]
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct SummarizedWindowActivity {
     pub current_window: ActiveWindow,
+    #[serde(default)]
+    pub current_category: Option<String>,
     pub recent_usage: Vec<AppUsage>,
+    /// How far back `recent_usage` goes. Older clients didn't send it, and always used the default.
+    #[serde_as(as = "DurationSeconds<i64>")]
+    #[serde(default = "default_window_length")]
+    pub window_length: Duration,
+}
+
+fn default_window_length() -> Duration {
+    Duration::minutes(DEFAULT_WINDOW_ACTIVITY_MINUTES)
 }
 
 #[serde_as]
 #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
 pub struct AppUsage {
     pub app_name: WindowName,
+    #[serde(default)]
+    pub category: Option<String>,
 
     #[serde_as(as = "DurationSeconds<i64>")]
     pub recent_usage: Duration,
@@ -63,7 +78,12 @@ impl SensorWidget<()> for SummarizedWindowActivity {
 }
 
 impl SummarizedWindowActivity {
-    pub fn summarize(activity: &WindowActivity, now: DateTime<Utc>, cutoff: DateTime<Utc>) -> Self {
+    pub fn summarize(
+        activity: &WindowActivity,
+        now: DateTime<Utc>,
+        cutoff: DateTime<Utc>,
+        max_num_apps: usize,
+    ) -> Self {
         let mut time_totals: HashMap<WindowName, Duration> = HashMap::new();
         Self::add_time_total(
             time_totals.entry(activity.current_window.window_name.clone()),
@@ -87,17 +107,111 @@ impl SummarizedWindowActivity {
             )
         }
 
-        let recent_usage = Self::humanize_to_recent_usage(time_totals);
+        let recent_usage = Self::humanize_to_recent_usage(time_totals, max_num_apps);
         SummarizedWindowActivity {
             current_window: activity.current_window.clone(),
+            current_category: None,
+            recent_usage,
+            window_length: now - cutoff,
+        }
+    }
+
+    /// Sets the category of each app found in `categories`
+    pub fn categorize(mut self, categories: &HashMap<WindowName, String>) -> Self {
+        self.current_category = categories.get(&self.current_window.window_name).cloned();
+        for app_usage in self.recent_usage.iter_mut() {
+            app_usage.category = categories.get(&app_usage.app_name).cloned();
+        }
+        self
+    }
+
+    /// *Returns:* The same activity with each app replaced by its category
+    pub fn to_categories_only(&self) -> Self {
+        let to_category = |window_name: &WindowName, category: Option<&String>| match window_name {
+            WindowName::Locked => WindowName::Locked,
+            WindowName::Normal(_) => WindowName::Normal(
+                category
+                    .cloned()
+                    .unwrap_or_else(|| UNCATEGORIZED.to_string()),
+            ),
+        };
+
+        let mut time_totals: HashMap<WindowName, Duration> = HashMap::new();
+        for app_usage in self.recent_usage.iter() {
+            let total = time_totals
+                .entry(to_category(
+                    &app_usage.app_name,
+                    app_usage.category.as_ref(),
+                ))
+                .or_insert_with(Duration::zero);
+            *total = *total + app_usage.recent_usage;
+        }
+        // There can't be more categories than apps
+        let mut recent_usage = Self::humanize_to_recent_usage(time_totals, self.recent_usage.len());
+        for app_usage in recent_usage.iter_mut() {
+            if let WindowName::Normal(category) = &app_usage.app_name {
+                app_usage.category = Some(category.clone());
+            }
+        }
+
+        let current_name = to_category(
+            &self.current_window.window_name,
+            self.current_category.as_ref(),
+        );
+        SummarizedWindowActivity {
+            current_category: match &current_name {
+                WindowName::Locked => None,
+                WindowName::Normal(category) => Some(category.clone()),
+            },
+            current_window: ActiveWindow {
+                window_name: current_name,
+                started_using: self.current_window.started_using,
+            },
             recent_usage,
+            window_length: self.window_length,
+        }
+    }
+
+    /// Whether app names were replaced by their categories before being shared
+    pub fn is_categories_only(&self) -> bool {
+        self.recent_usage.iter().all(|a| match &a.app_name {
+            WindowName::Locked => true,
+            WindowName::Normal(name) => a.category.as_ref() == Some(name),
+        })
+    }
+
+    /// *Returns:* The total recent usage of each category, most used first. Empty if no app is categorized.
+    pub fn usage_by_category(&self) -> Vec<(String, Duration)> {
+        if self.recent_usage.iter().all(|a| a.category.is_none()) {
+            return vec![];
+        }
+
+        let mut totals: HashMap<String, Duration> = HashMap::new();
+        for app_usage in self.recent_usage.iter().filter(|a| !a.is_lock_window()) {
+            let category = app_usage
+                .category
+                .clone()
+                .unwrap_or_else(|| UNCATEGORIZED.to_string());
+            let total = totals.entry(category).or_insert_with(Duration::zero);
+            *total = *total + app_usage.recent_usage;
         }
+
+        let mut usage = totals.into_iter().collect::<Vec<_>>();
+        usage.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then_with(|| a_name.cmp(b_name)));
+        usage
     }
 
     pub fn is_locked(&self) -> bool {
         self.current_window.window_name == WindowName::Locked
     }
 
+    /// Whether the current app is categorized as a meeting app
+    pub fn in_meeting_category(&self) -> bool {
+        self.current_category
+            .as_ref()
+            .is_some_and(|c| MEETING_CATEGORIES.iter().any(|m| c.eq_ignore_ascii_case(m)))
+    }
+
     fn add_time_total(
         entry: Entry<'_, WindowName, Duration>,
         started_using: DateTime<Utc>,
@@ -121,11 +235,15 @@ impl SummarizedWindowActivity {
             .or_insert(current_duration);
     }
 
-    fn humanize_to_recent_usage(time_totals: HashMap<WindowName, Duration>) -> Vec<AppUsage> {
+    fn humanize_to_recent_usage(
+        time_totals: HashMap<WindowName, Duration>,
+        max_num_apps: usize,
+    ) -> Vec<AppUsage> {
         let mut recent_usage = time_totals
             .into_iter()
             .map(|(k, v)| AppUsage {
                 app_name: k,
+                category: None,
                 recent_usage: Self::round_to_nearest_10_seconds(v),
             })
             .filter(|a| !a.recent_usage.is_zero())
@@ -137,7 +255,7 @@ impl SummarizedWindowActivity {
                 b.recent_usage.cmp(&a.recent_usage)
             }
         });
-        recent_usage.truncate(DEFAULT_MAX_NUM_APPS_IN_SUMMARY);
+        recent_usage.truncate(max_num_apps);
         recent_usage
     }
 
@@ -165,19 +283,29 @@ impl SummarizedWindowActivity {
             ui.create_default_layout_job(vec![
                 RichText::new("Using: ").color(ui.visuals().text_color()),
                 RichText::new(format!("{} ", self.current_window.window_name.clone())).strong(),
+                RichText::new(self.current_category_text()).color(ui.visuals().weak_text_color()),
                 RichText::new(format!("(started using {})", time_using_current))
                     .color(ui.visuals().text_color()),
             ])
         }
     }
 
+    fn current_category_text(&self) -> String {
+        match self.current_category.as_ref() {
+            Some(category) if *category != self.current_window.window_name.to_string() => {
+                format!("[{}] ", category)
+            }
+            _ => String::new(),
+        }
+    }
+
     fn show_details(&self, ui: &mut egui::Ui) {
         ui.label(format!(
             "In the past {}:",
-            DEFAULT_TIME_TO_KEEP_WINDOW_ACTIVITY
+            self.window_length
                 .to_std()
-                .unwrap()
-                .humanize()
+                .map(|d| d.humanize())
+                .unwrap_or_default()
         ));
 
         for app_usage in self.recent_usage.iter() {
@@ -200,6 +328,19 @@ impl SummarizedWindowActivity {
                 ));
             });
         }
+
+        let usage_by_category = self.usage_by_category();
+        if !usage_by_category.is_empty() && !self.is_categories_only() {
+            ui.label("By category:");
+            for (category, usage) in usage_by_category {
+                ui.horizontal_with_no_item_spacing(|ui| {
+                    ui.label("\t");
+                    ui.label(RichText::new(category).color(ui.visuals().strong_text_color()));
+                    ui.label(" for ");
+                    ui.label(usage.to_std().unwrap().humanize().to_string());
+                });
+            }
+        }
     }
 }
 
@@ -219,7 +360,7 @@ pub mod tests {
         // includes current
         // doesn't include 0s (before or after rounding)
         // sorts consistently (UNTESTED)
-        // only keeps the top DEFAULT_MAX_NUM_APPS_IN_SUMMARY apps (UNTESTED)
+        // only keeps the top max_num_apps apps (UNTESTED)
         let cutoff =
             Utc.from_utc_datetime(&NaiveDateTime::from_timestamp_millis(1694119546000).unwrap());
         let now = cutoff + Duration::seconds(100);
@@ -264,21 +405,110 @@ pub mod tests {
         let expected_usage = vec![
             AppUsage {
                 app_name: WindowName::Normal("Current".to_string()),
+                category: None,
                 recent_usage: Duration::seconds(50),
             },
             AppUsage {
                 app_name: WindowName::Normal("Fully past cutoff".to_string()),
+                category: None,
                 recent_usage: Duration::seconds(30),
             },
             AppUsage {
                 app_name: WindowName::Normal("Crossing cutoff".to_string()),
+                category: None,
                 recent_usage: Duration::seconds(10),
             },
         ];
 
-        let summary = SummarizedWindowActivity::summarize(&window_activity, now, cutoff);
+        let summary = SummarizedWindowActivity::summarize(&window_activity, now, cutoff, 5);
 
         assert_eq!(summary.current_window, window_activity.current_window);
         assert_eq!(summary.recent_usage, expected_usage);
+        assert_eq!(summary.window_length, Duration::seconds(100));
+
+        let summary = SummarizedWindowActivity::summarize(&window_activity, now, cutoff, 2);
+        assert_eq!(summary.recent_usage, expected_usage[..2]);
+    }
+
+    #[test]
+    pub fn usage_is_grouped_by_category() {
+        let usage = |name: &str, seconds: i64| AppUsage {
+            app_name: WindowName::Normal(name.to_string()),
+            category: None,
+            recent_usage: Duration::seconds(seconds),
+        };
+        let summary = SummarizedWindowActivity {
+            current_window: ActiveWindow {
+                window_name: WindowName::Normal("VS Code".to_string()),
+                started_using: Utc::now(),
+            },
+            current_category: None,
+            recent_usage: vec![
+                usage("VS Code", 60),
+                usage("Firefox", 50),
+                usage("Terminal", 20),
+                usage("Slack", 10),
+            ],
+            window_length: Duration::minutes(10),
+        };
+        assert!(summary.usage_by_category().is_empty());
+
+        let categories = [
+            ("VS Code", "Coding"),
+            ("Terminal", "Coding"),
+            ("Firefox", "Browsing"),
+        ]
+        .into_iter()
+        .map(|(app, category)| (WindowName::Normal(app.to_string()), category.to_string()))
+        .collect();
+        let summary = summary.categorize(&categories);
+
+        assert_eq!(summary.current_category, Some("Coding".to_string()));
+        assert_eq!(
+            summary.usage_by_category(),
+            vec![
+                ("Coding".to_string(), Duration::seconds(80)),
+                ("Browsing".to_string(), Duration::seconds(50)),
+                (UNCATEGORIZED.to_string(), Duration::seconds(10)),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn categories_only_replaces_app_names() {
+        let usage = |name: WindowName, category: Option<&str>, seconds: i64| AppUsage {
+            app_name: name,
+            category: category.map(str::to_string),
+            recent_usage: Duration::seconds(seconds),
+        };
+        let app = |name: &str| WindowName::Normal(name.to_string());
+        let summary = SummarizedWindowActivity {
+            current_window: ActiveWindow {
+                window_name: app("VS Code"),
+                started_using: Utc::now(),
+            },
+            current_category: Some("Coding".to_string()),
+            recent_usage: vec![
+                usage(app("VS Code"), Some("Coding"), 60),
+                usage(app("Terminal"), Some("Coding"), 30),
+                usage(WindowName::Locked, None, 40),
+                usage(app("Slack"), None, 10),
+            ],
+            window_length: Duration::minutes(10),
+        };
+        assert!(!summary.is_categories_only());
+
+        let categories_only = summary.to_categories_only();
+
+        assert!(categories_only.is_categories_only());
+        assert_eq!(categories_only.current_window.window_name, app("Coding"));
+        assert_eq!(
+            categories_only.recent_usage,
+            vec![
+                usage(app("Coding"), Some("Coding"), 90),
+                usage(WindowName::Locked, None, 40),
+                usage(app(UNCATEGORIZED), Some(UNCATEGORIZED), 10),
+            ]
+        );
     }
 }
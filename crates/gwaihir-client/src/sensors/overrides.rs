@@ -0,0 +1,178 @@
+use super::outputs::{
+    sensor_output::{SensorOutput, SensorOutputKind},
+    sensor_outputs::SensorOutputs,
+};
+use gwaihir_client_lib::chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub enum OverrideMode {
+    /// Don't send this sensor's output at all
+    Hidden,
+    /// Always send this instead of the real output
+    Fake(SensorOutput),
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct SensorOverride {
+    pub mode: OverrideMode,
+    /// If set, the real output is sent again after this time
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl SensorOverride {
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self.expires_at {
+            Some(expires_at) => now < expires_at,
+            None => true,
+        }
+    }
+}
+
+/// Lets the user lie about (or hide) the output of any sensor. Sensors without an override send their real output.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct SensorOverrides {
+    overrides: HashMap<SensorOutputKind, SensorOverride>,
+}
+
+impl SensorOverrides {
+    pub fn get(&self, kind: SensorOutputKind) -> Option<&SensorOverride> {
+        self.overrides.get(&kind)
+    }
+
+    /// Passing `None` goes back to sending the real output
+    pub fn set(&mut self, kind: SensorOutputKind, sensor_override: Option<SensorOverride>) {
+        match sensor_override {
+            Some(sensor_override) => self.overrides.insert(kind, sensor_override),
+            None => self.overrides.remove(&kind),
+        };
+    }
+
+    /// *Returns:* Whether any overrides were removed
+    pub fn remove_expired(&mut self, now: DateTime<Utc>) -> bool {
+        let num_overrides = self.overrides.len();
+        self.overrides.retain(|_, o| o.is_active(now));
+        self.overrides.len() != num_overrides
+    }
+
+    /// Replaces or removes the real outputs according to the active overrides
+    pub fn apply(&self, sensor_outputs: &SensorOutputs, now: DateTime<Utc>) -> SensorOutputs {
+        let active_override = |kind| self.get(kind).filter(|o| o.is_active(now));
+        let mut outputs: Vec<SensorOutput> = sensor_outputs
+            .outputs
+            .iter()
+            .filter_map(|output| match active_override(output.kind()) {
+                None => Some(output.clone()),
+                Some(SensorOverride {
+                    mode: OverrideMode::Hidden,
+                    ..
+                }) => None,
+                Some(SensorOverride {
+                    mode: OverrideMode::Fake(fake),
+                    ..
+                }) => Some(fake.clone()),
+            })
+            .collect();
+
+        // Fakes are sent even if we don't have the real sensor
+        for (kind, sensor_override) in self.overrides.iter() {
+            if let OverrideMode::Fake(fake) = &sensor_override.mode {
+                if sensor_override.is_active(now) && !outputs.iter().any(|o| o.kind() == *kind) {
+                    outputs.push(fake.clone());
+                }
+            }
+        }
+
+        SensorOutputs { outputs }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{microphone_usage::MicrophoneUsage, online_status::OnlineStatus};
+    use gwaihir_client_lib::chrono::Duration;
+
+    fn outputs() -> SensorOutputs {
+        SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage {
+                    usage: vec!["Zoom".to_string().into()],
+                    ..Default::default()
+                }),
+            ],
+        }
+    }
+
+    #[test]
+    pub fn no_overrides_sends_real_outputs() {
+        let overrides = SensorOverrides::default();
+
+        assert_eq!(overrides.apply(&outputs(), Utc::now()), outputs());
+    }
+
+    #[test]
+    pub fn hidden_and_fake_overrides_are_applied() {
+        let mut overrides = SensorOverrides::default();
+        overrides.set(
+            SensorOutputKind::OnlineStatus,
+            Some(SensorOverride {
+                mode: OverrideMode::Hidden,
+                expires_at: None,
+            }),
+        );
+        let fake_usage = SensorOutput::MicrophoneUsage(MicrophoneUsage::default());
+        overrides.set(
+            SensorOutputKind::MicrophoneUsage,
+            Some(SensorOverride {
+                mode: OverrideMode::Fake(fake_usage.clone()),
+                expires_at: None,
+            }),
+        );
+
+        assert_eq!(
+            overrides.apply(&outputs(), Utc::now()).outputs,
+            vec![fake_usage]
+        );
+    }
+
+    #[test]
+    pub fn fake_is_sent_without_real_output() {
+        let mut overrides = SensorOverrides::default();
+        let fake_status = SensorOutput::OnlineStatus(OnlineStatus { online: false });
+        overrides.set(
+            SensorOutputKind::OnlineStatus,
+            Some(SensorOverride {
+                mode: OverrideMode::Fake(fake_status.clone()),
+                expires_at: None,
+            }),
+        );
+
+        let applied = overrides.apply(&SensorOutputs { outputs: vec![] }, Utc::now());
+
+        assert_eq!(applied.outputs, vec![fake_status]);
+    }
+
+    #[test]
+    pub fn expired_overrides_are_ignored_and_removed() {
+        let now = Utc::now();
+        let mut overrides = SensorOverrides::default();
+        overrides.set(
+            SensorOutputKind::OnlineStatus,
+            Some(SensorOverride {
+                mode: OverrideMode::Hidden,
+                expires_at: Some(now + Duration::hours(1)),
+            }),
+        );
+
+        assert_eq!(overrides.apply(&outputs(), now).outputs.len(), 1);
+        let later = now + Duration::hours(2);
+        assert_eq!(overrides.apply(&outputs(), later), outputs());
+
+        assert!(!overrides.remove_expired(now));
+        assert!(overrides.remove_expired(later));
+        assert!(overrides.get(SensorOutputKind::OnlineStatus).is_none());
+    }
+}
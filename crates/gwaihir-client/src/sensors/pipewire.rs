@@ -0,0 +1,171 @@
+use log::error;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    io::Read,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+
+/// The properties of a PipeWire object, like its `media.class`
+pub type PipeWireProps = HashMap<String, serde_json::Value>;
+
+/// How often the monitor asks PipeWire for a new snapshot
+const DUMP_INTERVAL: Duration = Duration::from_secs(1);
+/// A stuck `pw-dump` is killed after this long, rather than holding up every sensor using it
+const DUMP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Started by whichever sensor needs it first, then shared by all of them
+static SHARED_MONITOR: Mutex<Option<PipeWireMonitor>> = Mutex::new(None);
+
+#[derive(Error, Debug)]
+pub enum PipeWireError {
+    #[error("Failed to run pw-dump: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("pw-dump failed: {0}")]
+    Dump(String),
+    #[error("pw-dump didn't finish within {0:?}")]
+    TimedOut(Duration),
+    #[error("Failed to parse pw-dump's output: {0}")]
+    Parse(#[from] serde_json::Error),
+}
+
+#[derive(Deserialize)]
+struct PipeWireObject {
+    info: Option<PipeWireObjectInfo>,
+}
+
+#[derive(Deserialize)]
+struct PipeWireObjectInfo {
+    #[serde(default)]
+    props: PipeWireProps,
+}
+
+/// Keeps a recent snapshot of everything PipeWire knows about, updated from a thread of its own
+/// so that sensors never wait on `pw-dump`
+#[derive(Clone)]
+pub struct PipeWireMonitor {
+    latest: Arc<Mutex<Arc<Vec<PipeWireProps>>>>,
+}
+
+impl PipeWireMonitor {
+    /// *Returns:* The monitor every sensor shares, starting it if this is the first time it's
+    /// needed. Fails if PipeWire isn't installed or running.
+    pub fn shared() -> Result<PipeWireMonitor, PipeWireError> {
+        let mut shared = SHARED_MONITOR.lock().unwrap();
+        if let Some(monitor) = shared.as_ref() {
+            return Ok(monitor.clone());
+        }
+
+        let monitor = PipeWireMonitor::start()?;
+        *shared = Some(monitor.clone());
+        Ok(monitor)
+    }
+
+    fn start() -> Result<PipeWireMonitor, PipeWireError> {
+        let monitor = PipeWireMonitor {
+            latest: Arc::new(Mutex::new(Arc::new(dump()?))),
+        };
+        let latest = monitor.latest.clone();
+        std::thread::Builder::new()
+            .name("pipewire_monitor".to_string())
+            .spawn(move || loop {
+                std::thread::sleep(DUMP_INTERVAL);
+                match dump() {
+                    Ok(props) => *latest.lock().unwrap() = Arc::new(props),
+                    Err(e) => error!("Failed to get a PipeWire snapshot: {}", e),
+                }
+            })?;
+
+        Ok(monitor)
+    }
+
+    /// *Returns:* The properties of every object in the most recent snapshot
+    pub fn latest(&self) -> Arc<Vec<PipeWireProps>> {
+        self.latest.lock().unwrap().clone()
+    }
+}
+
+/// *Returns:* The properties of every object PipeWire knows about
+fn dump() -> Result<Vec<PipeWireProps>, PipeWireError> {
+    let mut child = Command::new("pw-dump")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    // Read while it runs, since pw-dump blocks once the pipe is full
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let reader = std::thread::spawn(move || {
+        let mut output = Vec::new();
+        stdout.read_to_end(&mut output).map(|_| output)
+    });
+
+    let deadline = Instant::now() + DUMP_TIMEOUT;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            child.kill().ok();
+            child.wait().ok();
+            return Err(PipeWireError::TimedOut(DUMP_TIMEOUT));
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let output = reader.join().expect("reading pw-dump's output panicked")?;
+    if !status.success() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            pipe.read_to_string(&mut stderr).ok();
+        }
+        return Err(PipeWireError::Dump(stderr.trim().to_string()));
+    }
+    parse_dump(&output)
+}
+
+pub fn parse_dump(dump: &[u8]) -> Result<Vec<PipeWireProps>, PipeWireError> {
+    let objects: Vec<PipeWireObject> = serde_json::from_slice(dump)?;
+    Ok(objects
+        .into_iter()
+        .filter_map(|o| o.info)
+        .map(|info| info.props)
+        .collect())
+}
+
+pub fn get_str<'a>(props: &'a PipeWireProps, key: &str) -> Option<&'a str> {
+    props.get(key).and_then(|v| v.as_str())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// A trimmed down `pw-dump`, with a microphone, Zoom recording from it, Firefox playing audio
+    /// and a screen being shared
+    pub const EXAMPLE_DUMP: &str = r#"[
+        { "id": 0, "type": "PipeWire:Interface:Core", "info": { "props": { "core.name": "pipewire-0" } } },
+        { "id": 40, "type": "PipeWire:Interface:Node", "info": { "props": {
+            "media.class": "Audio/Source", "node.name": "alsa_input.usb-mic", "device.api": "alsa" } } },
+        { "id": 52, "type": "PipeWire:Interface:Node", "info": { "props": {
+            "media.class": "Video/Source", "node.name": "v4l2_input.webcam", "device.api": "v4l2" } } },
+        { "id": 75, "type": "PipeWire:Interface:Node", "info": { "props": {
+            "media.class": "Stream/Input/Audio", "application.process.binary": "zoom",
+            "application.process.id": 1234 } } },
+        { "id": 80, "type": "PipeWire:Interface:Node", "info": { "props": {
+            "media.class": "Stream/Output/Audio", "application.process.binary": "firefox" } } },
+        { "id": 85, "type": "PipeWire:Interface:Node", "info": { "props": {
+            "media.class": "Video/Source", "node.name": "xdpw_stream" } } },
+        { "id": 90, "type": "PipeWire:Interface:Metadata", "info": null }
+    ]"#;
+
+    #[test]
+    pub fn objects_without_info_are_skipped() {
+        let props = parse_dump(EXAMPLE_DUMP.as_bytes()).unwrap();
+
+        assert_eq!(props.len(), 6);
+        assert_eq!(get_str(&props[0], "core.name"), Some("pipewire-0"));
+        assert_eq!(get_str(&props[3], "application.process.id"), None);
+    }
+}
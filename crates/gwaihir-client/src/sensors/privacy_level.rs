@@ -0,0 +1,119 @@
+use super::outputs::{sensor_output::SensorOutput, sensor_outputs::SensorOutputs};
+use enum_iterator::Sequence;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// How much others may learn about which apps we're using
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default, Sequence)]
+pub enum PrivacyLevel {
+    #[default]
+    FullAppNames,
+    /// Apps are replaced by their categories (see `AppRules`), and only the number of apps using
+    /// the microphone is shared
+    CategoriesOnly,
+    /// No window activity is shared, and only the number of apps using the microphone is shared
+    Nothing,
+}
+
+impl PrivacyLevel {
+    pub fn apply(&self, sensor_outputs: &SensorOutputs) -> SensorOutputs {
+        if *self == PrivacyLevel::FullAppNames {
+            return sensor_outputs.clone();
+        }
+
+        let outputs = sensor_outputs
+            .outputs
+            .iter()
+            .filter_map(|output| match output {
+                SensorOutput::SummarizedWindowActivity(activity) => match self {
+                    PrivacyLevel::Nothing => None,
+                    _ => Some(SensorOutput::SummarizedWindowActivity(
+                        activity.to_categories_only(),
+                    )),
+                },
+                SensorOutput::MicrophoneUsage(usage) => {
+                    Some(SensorOutput::MicrophoneUsage(usage.without_app_names()))
+                }
+                SensorOutput::CameraUsage(usage) => {
+                    Some(SensorOutput::CameraUsage(usage.without_app_names()))
+                }
+                other => Some(other.clone()),
+            })
+            .collect();
+
+        SensorOutputs { outputs }
+    }
+}
+
+impl Display for PrivacyLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PrivacyLevel::FullAppNames => write!(f, "Full App Names"),
+            PrivacyLevel::CategoriesOnly => write!(f, "Categories Only"),
+            PrivacyLevel::Nothing => write!(f, "Nothing"),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{
+        microphone_usage::MicrophoneUsage,
+        online_status::OnlineStatus,
+        summarized_window_activity::{AppUsage, SummarizedWindowActivity},
+        window_activity::{ActiveWindow, WindowName},
+    };
+    use gwaihir_client_lib::chrono::{Duration, Utc};
+
+    fn outputs() -> SensorOutputs {
+        let code = WindowName::Normal("VS Code".to_string());
+        SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::SummarizedWindowActivity(SummarizedWindowActivity {
+                    current_window: ActiveWindow {
+                        window_name: code.clone(),
+                        started_using: Utc::now(),
+                    },
+                    current_category: Some("Coding".to_string()),
+                    recent_usage: vec![AppUsage {
+                        app_name: code,
+                        category: Some("Coding".to_string()),
+                        recent_usage: Duration::seconds(60),
+                    }],
+                    window_length: Duration::minutes(10),
+                }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage {
+                    usage: vec!["Zoom".to_string().into()],
+                    ..Default::default()
+                }),
+            ],
+        }
+    }
+
+    #[test]
+    pub fn categories_only_hides_app_names() {
+        let applied = PrivacyLevel::CategoriesOnly.apply(&outputs());
+
+        let activity = applied.find_summarized_window_activity().unwrap();
+        assert!(activity.is_categories_only());
+        assert_eq!(
+            activity.current_window.window_name,
+            WindowName::Normal("Coding".to_string())
+        );
+        let mic = applied.find_microphone_usage().unwrap();
+        assert!(mic.usage.is_empty());
+        assert_eq!(applied.get_num_apps_using_microphone(), Some(1));
+        assert!(applied.find_online_status().is_some());
+    }
+
+    #[test]
+    pub fn nothing_removes_window_activity() {
+        let applied = PrivacyLevel::Nothing.apply(&outputs());
+
+        assert!(applied.find_summarized_window_activity().is_none());
+        assert_eq!(applied.get_num_apps_using_microphone(), Some(1));
+        assert_eq!(PrivacyLevel::FullAppNames.apply(&outputs()), outputs());
+    }
+}
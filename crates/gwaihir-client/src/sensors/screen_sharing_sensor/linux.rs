@@ -0,0 +1,36 @@
+use crate::sensors::pipewire::{self, PipeWireMonitor, PipeWireProps};
+
+const VIDEO_SOURCE_CLASS: &str = "Video/Source";
+
+/// Screencasts (e.g. started through xdg-desktop-portal) are PipeWire video sources which, unlike
+/// cameras, don't belong to any device. They only exist while the screen is being shared.
+pub fn is_screen_sharing(monitor: &PipeWireMonitor) -> bool {
+    monitor.latest().iter().any(is_screencast)
+}
+
+fn is_screencast(props: &PipeWireProps) -> bool {
+    pipewire::get_str(props, "media.class") == Some(VIDEO_SOURCE_CLASS)
+        && !props.contains_key("device.api")
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::pipewire::tests::EXAMPLE_DUMP;
+
+    #[test]
+    pub fn cameras_are_not_screencasts() {
+        let props = pipewire::parse_dump(EXAMPLE_DUMP.as_bytes()).unwrap();
+
+        let screencasts = props
+            .iter()
+            .filter(|p| is_screencast(p))
+            .collect::<Vec<_>>();
+
+        assert_eq!(screencasts.len(), 1);
+        assert_eq!(
+            pipewire::get_str(screencasts[0], "node.name"),
+            Some("xdpw_stream")
+        );
+    }
+}
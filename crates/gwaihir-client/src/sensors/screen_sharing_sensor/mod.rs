@@ -0,0 +1,93 @@
+use super::{
+    outputs::{screen_sharing::ScreenSharing, sensor_output::SensorOutput},
+    Sensor,
+};
+use gwaihir_client_lib::{
+    chrono::{DateTime, Utc},
+    periodic_checker::PeriodicChecker,
+};
+use std::time::Duration;
+use thiserror::Error;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+use super::pipewire::{PipeWireError, PipeWireMonitor};
+
+#[derive(Error, Debug)]
+pub enum ScreenSharingSensorError {
+    #[cfg(target_os = "linux")]
+    #[error("Screen sharing can only be detected through PipeWire: {0}")]
+    PipeWire(#[from] PipeWireError),
+    #[cfg(not(target_os = "linux"))]
+    #[error("Screen sharing detection isn't supported on this platform")]
+    Unsupported,
+}
+
+pub struct ScreenSharingSensor {
+    periodic_checker: PeriodicChecker<bool>,
+    since: Option<DateTime<Utc>>,
+}
+
+impl ScreenSharingSensor {
+    fn new(is_screen_sharing: impl FnMut() -> bool + 'static) -> Self {
+        Self {
+            periodic_checker: PeriodicChecker::new(
+                Box::new(is_screen_sharing),
+                Duration::from_secs(2),
+            ),
+            since: None,
+        }
+    }
+}
+
+impl Sensor for ScreenSharingSensor {
+    fn get_output(&mut self) -> SensorOutput {
+        self.periodic_checker.check();
+        let sharing = self.periodic_checker.last_check_result();
+        self.since = match self.since {
+            Some(since) if sharing => Some(since),
+            None if sharing => Some(Utc::now()),
+            _ => None,
+        };
+        SensorOutput::ScreenSharing(ScreenSharing {
+            sharing,
+            since: self.since,
+        })
+    }
+}
+
+#[allow(unreachable_code)]
+pub fn try_get_sensor() -> Result<Box<dyn Sensor>, ScreenSharingSensorError> {
+    #[cfg(target_os = "linux")]
+    {
+        let monitor = PipeWireMonitor::shared()?;
+        return Ok(Box::new(ScreenSharingSensor::new(move || {
+            linux::is_screen_sharing(&monitor)
+        })));
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    Err(ScreenSharingSensorError::Unsupported)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    #[test]
+    pub fn sharing_starts_now() {
+        let mut sensor = ScreenSharingSensor::new(|| true);
+        let before = Utc::now();
+
+        let output = sensor.get_output();
+
+        assert!(matches!(
+            output,
+            SensorOutput::ScreenSharing(ScreenSharing {
+                sharing: true,
+                since: Some(since),
+            }) if since >= before
+        ));
+    }
+}
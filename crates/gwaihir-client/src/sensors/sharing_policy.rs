@@ -0,0 +1,76 @@
+use super::outputs::{sensor_output::SensorOutputKind, sensor_outputs::SensorOutputs};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Which kinds of sensor output may be published at all, before any per-circle filtering
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct SharingPolicy {
+    /// Stored as the disabled kinds so that newly added sensors are shared unless turned off
+    disabled: HashSet<SensorOutputKind>,
+}
+
+impl SharingPolicy {
+    pub fn is_shared(&self, kind: SensorOutputKind) -> bool {
+        kind.should_send_to_remote() && !self.disabled.contains(&kind)
+    }
+
+    pub fn set_shared(&mut self, kind: SensorOutputKind, shared: bool) {
+        if shared {
+            self.disabled.remove(&kind);
+        } else {
+            self.disabled.insert(kind);
+        }
+    }
+
+    /// Removes every output this policy doesn't allow to be shared
+    pub fn apply(&self, sensor_outputs: &SensorOutputs) -> SensorOutputs {
+        SensorOutputs {
+            outputs: sensor_outputs
+                .outputs
+                .iter()
+                .filter(|o| self.is_shared(o.kind()))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::sensors::outputs::{
+        lock_status::LockStatus, microphone_usage::MicrophoneUsage, online_status::OnlineStatus,
+        sensor_output::SensorOutput,
+    };
+
+    #[test]
+    pub fn default_shares_all_shareable_kinds() {
+        let policy = SharingPolicy::default();
+
+        for kind in SensorOutputKind::all_shareable() {
+            assert!(policy.is_shared(kind));
+        }
+        assert!(!policy.is_shared(SensorOutputKind::LockStatus));
+    }
+
+    #[test]
+    pub fn apply_removes_disabled_kinds() {
+        let mut policy = SharingPolicy::default();
+        policy.set_shared(SensorOutputKind::MicrophoneUsage, false);
+        let outputs = SensorOutputs {
+            outputs: vec![
+                SensorOutput::OnlineStatus(OnlineStatus { online: true }),
+                SensorOutput::MicrophoneUsage(MicrophoneUsage::default()),
+                SensorOutput::LockStatus(LockStatus::default()),
+            ],
+        };
+
+        assert_eq!(
+            policy.apply(&outputs).outputs,
+            vec![SensorOutput::OnlineStatus(OnlineStatus { online: true })]
+        );
+
+        policy.set_shared(SensorOutputKind::MicrophoneUsage, true);
+        assert_eq!(policy.apply(&outputs).outputs.len(), 2);
+    }
+}
@@ -1,29 +1,49 @@
-use std::{cell::RefCell, rc::Rc};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
 
-use gwaihir_client_lib::chrono::{Duration, Utc};
-use once_cell::sync::Lazy;
+use gwaihir_client_lib::chrono::Utc;
 
 use super::{
     active_window_provider::LockAwareWindowProvider,
-    outputs::{sensor_output::SensorOutput, summarized_window_activity::SummarizedWindowActivity},
+    activity_settings::ActivitySettings,
+    app_rules::CompiledAppRules,
+    outputs::{
+        sensor_output::SensorOutput,
+        summarized_window_activity::SummarizedWindowActivity,
+        window_activity::{WindowActivity, WindowName},
+    },
     window_activity_sensor::WindowActivitySensor,
     Sensor,
 };
 
-pub static DEFAULT_TIME_TO_KEEP_WINDOW_ACTIVITY: Lazy<Duration> =
-    Lazy::new(|| Duration::seconds(60 * 10));
-
 pub struct WindowActivityInterpreter {
     window_activity_sensor: WindowActivitySensor<Rc<RefCell<LockAwareWindowProvider>>>,
     lock_aware_active_window_provider: Rc<RefCell<LockAwareWindowProvider>>,
+    app_rules: Rc<RefCell<CompiledAppRules>>,
+    settings: Rc<Cell<ActivitySettings>>,
 }
 
 impl Sensor for WindowActivityInterpreter {
     fn get_output(&mut self) -> SensorOutput {
+        let settings = self.settings.get();
+        self.window_activity_sensor
+            .set_time_to_keep_activity(settings.window_activity_retention());
         if let Some(window_activity) = self.window_activity_sensor.update() {
+            let (window_activity, categories) =
+                apply_app_rules(&self.app_rules.borrow(), window_activity);
             let now = Utc::now();
-            let cutoff = now - *DEFAULT_TIME_TO_KEEP_WINDOW_ACTIVITY;
-            SummarizedWindowActivity::summarize(&window_activity, now, cutoff).into()
+            let cutoff = now - settings.window_activity_retention();
+            SummarizedWindowActivity::summarize(
+                &window_activity,
+                now,
+                cutoff,
+                settings.max_apps_in_summary,
+            )
+            .categorize(&categories)
+            .into()
         } else {
             SensorOutput::Empty
         }
@@ -41,14 +61,45 @@ impl Sensor for WindowActivityInterpreter {
 }
 
 impl WindowActivityInterpreter {
-    pub fn new() -> Self {
+    pub fn new(
+        app_rules: Rc<RefCell<CompiledAppRules>>,
+        settings: Rc<Cell<ActivitySettings>>,
+    ) -> Self {
         let active_window_provider = Rc::new(RefCell::new(LockAwareWindowProvider::new()));
         Self {
             window_activity_sensor: WindowActivitySensor::new(
-                *DEFAULT_TIME_TO_KEEP_WINDOW_ACTIVITY,
+                settings.get().window_activity_retention(),
                 active_window_provider.clone(),
             ),
             lock_aware_active_window_provider: active_window_provider,
+            app_rules,
+            settings,
         }
     }
 }
+
+/// Renames the windows according to the app rules, so that apps with the same alias are summarized together
+///
+/// *Returns:* The renamed activity, and the category of each renamed window that has one
+fn apply_app_rules(
+    app_rules: &CompiledAppRules,
+    mut window_activity: WindowActivity,
+) -> (WindowActivity, HashMap<WindowName, String>) {
+    let mut categories = HashMap::new();
+    let mut rename = |window_name: &mut WindowName| {
+        if let WindowName::Normal(app_name) = window_name {
+            let resolved = app_rules.resolve(app_name);
+            *app_name = resolved.name;
+            if let Some(category) = resolved.category {
+                categories.insert(window_name.clone(), category);
+            }
+        }
+    };
+
+    rename(&mut window_activity.current_window.window_name);
+    for window in window_activity.previously_active_windows.iter_mut() {
+        rename(&mut window.window_name);
+    }
+
+    (window_activity, categories)
+}
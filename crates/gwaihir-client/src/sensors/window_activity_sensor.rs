@@ -30,6 +30,10 @@ where
         }
     }
 
+    pub fn set_time_to_keep_activity(&mut self, time_to_keep_activity: Duration) {
+        self.time_to_keep_activity = time_to_keep_activity;
+    }
+
     pub fn update(&mut self) -> Option<WindowActivity> {
         match self.active_window_provider.get_active_window() {
             Ok(active_window) => {
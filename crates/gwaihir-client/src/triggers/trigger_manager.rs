@@ -354,9 +354,9 @@ mod default_triggers {
     }
 
     pub fn summary_in_meeting() -> Trigger {
-        let criteria = Expression::GreaterThan(
-            ValuePointer::NumAppsUsingMicrophone(TimeSpecifier::Current),
-            ValuePointer::ConstUsize(0),
+        let criteria = Expression::Equals(
+            ValuePointer::InCall(TimeSpecifier::Current),
+            ValuePointer::ConstBool(true),
         );
         let actions = vec![Action::SetSummary(
             SummaryTemplate::new("In Meeting".to_owned()).unwrap(),
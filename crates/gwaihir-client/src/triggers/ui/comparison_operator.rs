@@ -1,4 +1,5 @@
 use crate::{
+    sensors::outputs::{keyboard_mouse_activity::ActivityState, manual_status::Availability},
     triggers::{
         value_pointer::{ValueKind, ValuePointerKind},
         Expression, ExpressionRef, TimeSpecifier, ValuePointer,
@@ -35,7 +36,7 @@ impl ComparisonOperator {
                 self,
                 ComparisonOperator::Equals | ComparisonOperator::NotEquals
             ),
-            ValueKind::UserId => matches!(
+            ValueKind::UserId | ValueKind::Availability | ValueKind::ActivityState => matches!(
                 self,
                 ComparisonOperator::Equals | ComparisonOperator::NotEquals
             ),
@@ -227,17 +228,26 @@ impl From<&ValuePointerKind> for ValueKind {
         match value {
             ValuePointerKind::OnlineStatus
             | ValuePointerKind::LockStatus
+            | ValuePointerKind::InCall
+            | ValuePointerKind::IsScreenSharing
             | ValuePointerKind::ConstBool => ValueKind::Bool,
-            ValuePointerKind::TotalKeyboardMouseUsage | ValuePointerKind::ConstF64 => {
-                ValueKind::F64
-            }
+            ValuePointerKind::TotalKeyboardMouseUsage
+            | ValuePointerKind::AvailabilityScore
+            | ValuePointerKind::ConstF64 => ValueKind::F64,
             ValuePointerKind::UserId | ValuePointerKind::ConstUserId => ValueKind::UserId,
             ValuePointerKind::NumAppsUsingMicrophone | ValuePointerKind::ConstUsize => {
                 ValueKind::Usize
             }
             ValuePointerKind::TimeSinceMostRecentUpdate
             | ValuePointerKind::ConstDuration
-            | ValuePointerKind::ActiveWindowDuration => ValueKind::Duration,
+            | ValuePointerKind::ActiveWindowDuration
+            | ValuePointerKind::IdleDuration => ValueKind::Duration,
+            ValuePointerKind::Availability | ValuePointerKind::ConstAvailability => {
+                ValueKind::Availability
+            }
+            ValuePointerKind::ActivityState | ValuePointerKind::ConstActivityState => {
+                ValueKind::ActivityState
+            }
         }
     }
 }
@@ -262,6 +272,18 @@ impl ValuePointerKind {
                 ValuePointer::ConstDuration(std::time::Duration::from_secs(30))
             }
             ValuePointerKind::ActiveWindowDuration => ValuePointer::ActiveWindowDuration(time),
+            ValuePointerKind::Availability => ValuePointer::Availability(time),
+            ValuePointerKind::IdleDuration => ValuePointer::IdleDuration(time),
+            ValuePointerKind::AvailabilityScore => ValuePointer::AvailabilityScore(time),
+            ValuePointerKind::InCall => ValuePointer::InCall(time),
+            ValuePointerKind::IsScreenSharing => ValuePointer::IsScreenSharing(time),
+            ValuePointerKind::ActivityState => ValuePointer::ActivityState(time),
+            ValuePointerKind::ConstAvailability => {
+                ValuePointer::ConstAvailability(Availability::DoNotDisturb)
+            }
+            ValuePointerKind::ConstActivityState => {
+                ValuePointer::ConstActivityState(ActivityState::SustainedFocus)
+            }
         }
     }
 
@@ -282,11 +304,23 @@ impl ValuePointerKind {
             ValuePointerKind::ActiveWindowDuration => {
                 UserSelectableExpression::ActiveWindowDuration.to_string()
             }
+            ValuePointerKind::Availability => UserSelectableExpression::Availability.to_string(),
+            ValuePointerKind::IdleDuration => UserSelectableExpression::IdleDuration.to_string(),
+            ValuePointerKind::AvailabilityScore => {
+                UserSelectableExpression::AvailabilityScore.to_string()
+            }
+            ValuePointerKind::InCall => UserSelectableExpression::InCall.to_string(),
+            ValuePointerKind::IsScreenSharing => {
+                UserSelectableExpression::IsScreenSharing.to_string()
+            }
+            ValuePointerKind::ActivityState => UserSelectableExpression::ActivityState.to_string(),
             ValuePointerKind::ConstBool
             | ValuePointerKind::ConstUserId
             | ValuePointerKind::ConstF64
             | ValuePointerKind::ConstUsize
-            | ValuePointerKind::ConstDuration => "Fixed Value".to_owned(),
+            | ValuePointerKind::ConstDuration
+            | ValuePointerKind::ConstAvailability
+            | ValuePointerKind::ConstActivityState => "Fixed Value".to_owned(),
         }
     }
 }
@@ -140,6 +140,44 @@ impl SimpleTriggerWidgetExtension for ValuePointer {
                 t.ui(ui);
                 ui.label(UserSelectableExpression::ActiveWindowDuration.to_string());
             }
+            ValuePointer::Availability(t) => {
+                t.ui(ui);
+                ui.label(UserSelectableExpression::Availability.to_string());
+            }
+            ValuePointer::IdleDuration(t) => {
+                t.ui(ui);
+                ui.label(UserSelectableExpression::IdleDuration.to_string());
+            }
+            ValuePointer::AvailabilityScore(t) => {
+                t.ui(ui);
+                ui.label(UserSelectableExpression::AvailabilityScore.to_string());
+            }
+            ValuePointer::InCall(t) => {
+                t.ui(ui);
+                ui.label(UserSelectableExpression::InCall.to_string());
+            }
+            ValuePointer::IsScreenSharing(t) => {
+                t.ui(ui);
+                ui.label(UserSelectableExpression::IsScreenSharing.to_string());
+            }
+            ValuePointer::ActivityState(t) => {
+                t.ui(ui);
+                ui.label(UserSelectableExpression::ActivityState.to_string());
+            }
+            ValuePointer::ConstAvailability(a) => {
+                if ui.small_button(a.to_string()).clicked() {
+                    if let Some(next) = enum_iterator::next_cycle(a) {
+                        *a = next;
+                    }
+                }
+            }
+            ValuePointer::ConstActivityState(s) => {
+                if ui.small_button(s.to_string()).clicked() {
+                    if let Some(next) = enum_iterator::next_cycle(s) {
+                        *s = next;
+                    }
+                }
+            }
         }
     }
 }
@@ -1,4 +1,7 @@
-use crate::triggers::{value_pointer::ValuePointerKind, Expression, TimeSpecifier, ValuePointer};
+use crate::{
+    sensors::outputs::{keyboard_mouse_activity::ActivityState, manual_status::Availability},
+    triggers::{value_pointer::ValuePointerKind, Expression, TimeSpecifier, ValuePointer},
+};
 use enum_iterator::Sequence;
 use gwaihir_client_lib::UniqueUserId;
 
@@ -11,6 +14,12 @@ pub enum UserSelectableExpression {
     NumAppsUsingMicrophone,
     TimeSinceMostRecentUpdate,
     ActiveWindowDuration,
+    Availability,
+    IdleDuration,
+    AvailabilityScore,
+    InCall,
+    IsScreenSharing,
+    ActivityState,
 }
 
 impl UserSelectableExpression {
@@ -44,6 +53,30 @@ impl UserSelectableExpression {
                 ValuePointer::ActiveWindowDuration(TimeSpecifier::Current),
                 ValuePointerKind::ConstDuration.get_default_value_pointer(),
             ),
+            UserSelectableExpression::Availability => Expression::NotEquals(
+                ValuePointer::Availability(TimeSpecifier::Current),
+                ValuePointer::ConstAvailability(Availability::DoNotDisturb),
+            ),
+            UserSelectableExpression::IdleDuration => Expression::GreaterThan(
+                ValuePointer::IdleDuration(TimeSpecifier::Current),
+                ValuePointerKind::ConstDuration.get_default_value_pointer(),
+            ),
+            UserSelectableExpression::AvailabilityScore => Expression::GreaterThan(
+                ValuePointer::AvailabilityScore(TimeSpecifier::Current),
+                ValuePointer::ConstF64(50.0),
+            ),
+            UserSelectableExpression::InCall => Expression::Equals(
+                ValuePointer::InCall(TimeSpecifier::Current),
+                ValuePointer::ConstBool(true),
+            ),
+            UserSelectableExpression::IsScreenSharing => Expression::Equals(
+                ValuePointer::IsScreenSharing(TimeSpecifier::Current),
+                ValuePointer::ConstBool(true),
+            ),
+            UserSelectableExpression::ActivityState => Expression::Equals(
+                ValuePointer::ActivityState(TimeSpecifier::Current),
+                ValuePointer::ConstActivityState(ActivityState::SustainedFocus),
+            ),
         }
     }
 }
@@ -60,6 +93,12 @@ impl std::fmt::Display for UserSelectableExpression {
                 write!(f, "Time Since Most Recent Update")
             }
             UserSelectableExpression::ActiveWindowDuration => write!(f, "Active Window Duration"),
+            UserSelectableExpression::Availability => write!(f, "Availability"),
+            UserSelectableExpression::IdleDuration => write!(f, "Idle Duration"),
+            UserSelectableExpression::AvailabilityScore => write!(f, "Availability Score"),
+            UserSelectableExpression::InCall => write!(f, "In a Call"),
+            UserSelectableExpression::IsScreenSharing => write!(f, "Screen Sharing"),
+            UserSelectableExpression::ActivityState => write!(f, "Activity State"),
         }
     }
 }
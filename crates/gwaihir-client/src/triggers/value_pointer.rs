@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use crate::sensors::outputs::sensor_outputs::SensorOutputs;
+use crate::sensors::outputs::{
+    keyboard_mouse_activity::ActivityState, manual_status::Availability,
+    sensor_outputs::SensorOutputs,
+};
 
 use super::{
     expression::{EvalData, EvalResult, EvaluationError, OperationType},
@@ -23,12 +26,20 @@ pub enum ValuePointer {
     UserId,
     TimeSinceMostRecentUpdate,
     ActiveWindowDuration(TimeSpecifier),
+    Availability(TimeSpecifier),
+    IdleDuration(TimeSpecifier),
+    AvailabilityScore(TimeSpecifier),
+    InCall(TimeSpecifier),
+    IsScreenSharing(TimeSpecifier),
+    ActivityState(TimeSpecifier),
 
     ConstBool(bool),
     ConstUserId(UniqueUserId),
     ConstF64(f64),
     ConstUsize(usize),
     ConstDuration(Duration),
+    ConstAvailability(Availability),
+    ConstActivityState(ActivityState),
 }
 
 #[derive(Serialize, Deserialize, PartialEq, Clone, Debug)]
@@ -44,6 +55,8 @@ pub enum Value {
     F64(f64),
     Usize(usize),
     Duration(Duration),
+    Availability(Availability),
+    ActivityState(ActivityState),
 }
 
 impl ValuePointer {
@@ -86,6 +99,27 @@ impl ValuePointer {
                 .active_window_duration()
                 .and_then(|d| d.to_std().ok())
                 .map(Value::Duration),
+            ValuePointer::Availability(t) => Some(Value::Availability(
+                get_outputs_by_time_specifier(&data.update, t).get_availability(),
+            )),
+            ValuePointer::ConstAvailability(a) => Some(Value::Availability(*a)),
+            ValuePointer::IdleDuration(t) => get_outputs_by_time_specifier(&data.update, t)
+                .idle_duration()
+                .and_then(|d| d.to_std().ok())
+                .map(Value::Duration),
+            ValuePointer::AvailabilityScore(t) => Some(Value::F64(
+                get_outputs_by_time_specifier(&data.update, t).get_availability_score(),
+            )),
+            ValuePointer::InCall(t) => get_outputs_by_time_specifier(&data.update, t)
+                .find_call_status()
+                .map(|s| Value::Bool(s.in_call)),
+            ValuePointer::IsScreenSharing(t) => get_outputs_by_time_specifier(&data.update, t)
+                .find_screen_sharing()
+                .map(|s| Value::Bool(s.sharing)),
+            ValuePointer::ActivityState(t) => get_outputs_by_time_specifier(&data.update, t)
+                .get_activity_state()
+                .map(Value::ActivityState),
+            ValuePointer::ConstActivityState(s) => Some(Value::ActivityState(*s)),
         }
     }
 }
@@ -108,6 +142,12 @@ impl Value {
             (Value::F64(left), Value::F64(right)) => EvalResult::Ok(left == right),
             (Value::Usize(left), Value::Usize(right)) => EvalResult::Ok(left == right),
             (Value::Duration(left), Value::Duration(right)) => EvalResult::Ok(left == right),
+            (Value::Availability(left), Value::Availability(right)) => {
+                EvalResult::Ok(left == right)
+            }
+            (Value::ActivityState(left), Value::ActivityState(right)) => {
+                EvalResult::Ok(left == right)
+            }
 
             (a @ Value::Bool(_), b)
             | (a, b @ Value::Bool(_))
@@ -116,7 +156,11 @@ impl Value {
             | (a @ Value::F64(_), b)
             | (a, b @ Value::F64(_))
             | (a @ Value::Usize(_), b)
-            | (a, b @ Value::Usize(_)) => {
+            | (a, b @ Value::Usize(_))
+            | (a @ Value::Duration(_), b)
+            | (a, b @ Value::Duration(_))
+            | (a @ Value::Availability(_), b)
+            | (a, b @ Value::Availability(_)) => {
                 EvalResult::Err(EvaluationError::TypeMismatch(a.to_owned(), b.to_owned()))
             }
         }
@@ -138,11 +182,17 @@ impl Value {
             | (a @ Value::F64(_), b)
             | (a, b @ Value::F64(_))
             | (a @ Value::Usize(_), b)
-            | (a, b @ Value::Usize(_)) => EvalResult::Err(EvaluationError::InvalidOperation(
-                OperationType::GreaterThan,
-                a.to_owned(),
-                b.to_owned(),
-            )),
+            | (a, b @ Value::Usize(_))
+            | (a @ Value::Availability(_), b)
+            | (a, b @ Value::Availability(_))
+            | (a @ Value::ActivityState(_), b)
+            | (a, b @ Value::ActivityState(_)) => {
+                EvalResult::Err(EvaluationError::InvalidOperation(
+                    OperationType::GreaterThan,
+                    a.to_owned(),
+                    b.to_owned(),
+                ))
+            }
         }
     }
 
@@ -158,11 +208,17 @@ impl Value {
             | (a @ Value::F64(_), b)
             | (a, b @ Value::F64(_))
             | (a @ Value::Usize(_), b)
-            | (a, b @ Value::Usize(_)) => EvalResult::Err(EvaluationError::InvalidOperation(
-                OperationType::LessThan,
-                a.to_owned(),
-                b.to_owned(),
-            )),
+            | (a, b @ Value::Usize(_))
+            | (a @ Value::Availability(_), b)
+            | (a, b @ Value::Availability(_))
+            | (a @ Value::ActivityState(_), b)
+            | (a, b @ Value::ActivityState(_)) => {
+                EvalResult::Err(EvaluationError::InvalidOperation(
+                    OperationType::LessThan,
+                    a.to_owned(),
+                    b.to_owned(),
+                ))
+            }
         }
     }
 }
@@ -196,12 +252,20 @@ pub mod persistence {
         UserId,
         TimeSinceMostRecentUpdate,
         ActiveWindowDuration(TimeSpecifier),
+        Availability(TimeSpecifier),
+        IdleDuration(TimeSpecifier),
+        AvailabilityScore(TimeSpecifier),
+        InCall(TimeSpecifier),
+        IsScreenSharing(TimeSpecifier),
+        ActivityState(TimeSpecifier),
 
         ConstBool(bool),
         ConstUserId(UniqueUserId),
         ConstF64(f64),
         ConstUsize(usize),
         ConstDuration(Duration),
+        ConstAvailability(Availability),
+        ConstActivityState(ActivityState),
     }
 
     impl From<VersionedValuePointer> for ValuePointer {
@@ -222,6 +286,14 @@ pub mod persistence {
                 ValuePointerV2::TimeSinceMostRecentUpdate => Self::TimeSinceMostRecentUpdate,
                 ValuePointerV2::ConstDuration(d) => Self::ConstDuration(d),
                 ValuePointerV2::ActiveWindowDuration(t) => Self::ActiveWindowDuration(t),
+                ValuePointerV2::Availability(t) => Self::Availability(t),
+                ValuePointerV2::ConstAvailability(a) => Self::ConstAvailability(a),
+                ValuePointerV2::IdleDuration(t) => Self::IdleDuration(t),
+                ValuePointerV2::AvailabilityScore(t) => Self::AvailabilityScore(t),
+                ValuePointerV2::InCall(t) => Self::InCall(t),
+                ValuePointerV2::IsScreenSharing(t) => Self::IsScreenSharing(t),
+                ValuePointerV2::ActivityState(t) => Self::ActivityState(t),
+                ValuePointerV2::ConstActivityState(s) => Self::ConstActivityState(s),
             }
         }
     }
@@ -247,6 +319,14 @@ pub mod persistence {
                 }
                 ValuePointer::ConstDuration(d) => ValuePointerV2::ConstDuration(d),
                 ValuePointer::ActiveWindowDuration(t) => ValuePointerV2::ActiveWindowDuration(t),
+                ValuePointer::Availability(t) => ValuePointerV2::Availability(t),
+                ValuePointer::ConstAvailability(a) => ValuePointerV2::ConstAvailability(a),
+                ValuePointer::IdleDuration(t) => ValuePointerV2::IdleDuration(t),
+                ValuePointer::AvailabilityScore(t) => ValuePointerV2::AvailabilityScore(t),
+                ValuePointer::InCall(t) => ValuePointerV2::InCall(t),
+                ValuePointer::IsScreenSharing(t) => ValuePointerV2::IsScreenSharing(t),
+                ValuePointer::ActivityState(t) => ValuePointerV2::ActivityState(t),
+                ValuePointer::ConstActivityState(s) => ValuePointerV2::ConstActivityState(s),
             })
         }
     }
@@ -0,0 +1,76 @@
+use super::widgets::show_centered_window;
+use crate::sensors::activity_settings::ActivitySettings;
+use egui::DragValue;
+
+pub struct ActivitySettingsWindow {
+    shown: bool,
+    /// Changing the buckets clears the keyboard/mouse history, so edits wait until they're applied
+    draft: Option<ActivitySettings>,
+}
+
+impl ActivitySettingsWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            draft: None,
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    /// *Returns:* Whether the settings changed
+    pub fn show(&mut self, ctx: &egui::Context, settings: &mut ActivitySettings) -> bool {
+        let mut changed = false;
+        let draft = self.draft.get_or_insert(*settings);
+        self.shown = show_centered_window(self.shown, "Activity History", ctx, |ui| {
+            ui.label("How much of your activity is kept and shared. Others see it the way you've set it here.");
+            ui.separator();
+
+            egui::Grid::new("activity_settings_grid").show(ui, |ui| {
+                ui.label("Summarize window activity over");
+                ui.add(
+                    DragValue::new(&mut draft.window_activity_minutes)
+                        .clamp_range(1..=24 * 60)
+                        .suffix(" min"),
+                );
+                ui.end_row();
+
+                ui.label("Most used apps to include");
+                ui.add(DragValue::new(&mut draft.max_apps_in_summary).clamp_range(1..=50));
+                ui.end_row();
+
+                ui.label("Keyboard/mouse bucket duration");
+                ui.add(
+                    DragValue::new(&mut draft.bucket_duration_seconds)
+                        .clamp_range(1..=10 * 60)
+                        .suffix(" s"),
+                );
+                ui.end_row();
+
+                ui.label("Keyboard/mouse buckets to keep");
+                ui.add(DragValue::new(&mut draft.num_buckets).clamp_range(1..=500));
+                ui.end_row();
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    *settings = *draft;
+                    changed = true;
+                }
+
+                if ui.button("Revert").clicked() {
+                    *draft = *settings;
+                }
+
+                if ui.button("Reset to defaults").clicked() {
+                    *draft = ActivitySettings::default();
+                }
+            });
+        });
+
+        changed
+    }
+}
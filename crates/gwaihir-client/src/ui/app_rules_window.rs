@@ -0,0 +1,117 @@
+use super::widgets::show_centered_window;
+use crate::sensors::app_rules::{AppRule, AppRules, MatchKind};
+use egui::{Color32, RichText, ScrollArea};
+
+pub struct AppRulesWindow {
+    shown: bool,
+    /// Edits aren't used until they're applied, so half-typed regexes don't break anything
+    draft: Option<AppRules>,
+    error_msg: Option<String>,
+}
+
+impl AppRulesWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            draft: None,
+            error_msg: None,
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    /// *Returns:* Whether the rules changed
+    pub fn show(&mut self, ctx: &egui::Context, app_rules: &mut AppRules) -> bool {
+        let mut changed = false;
+        let draft = self.draft.get_or_insert_with(|| app_rules.clone());
+        let error_msg = &mut self.error_msg;
+        self.shown = show_centered_window(self.shown, "App Rules", ctx, |ui| {
+            ui.label("Rename and categorize apps by the name the OS reports for them. The first matching rule is used.");
+            ui.separator();
+
+            ScrollArea::vertical().max_height(300.0).show(ui, |ui| {
+                show_rules_grid(ui, &mut draft.rules);
+            });
+            if ui.button("Add rule").clicked() {
+                draft.rules.push(AppRule::default());
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Apply").clicked() {
+                    match validate(draft) {
+                        Ok(()) => {
+                            *app_rules = draft.clone();
+                            *error_msg = None;
+                            changed = true;
+                        }
+                        Err(e) => *error_msg = Some(e),
+                    }
+                }
+
+                if ui.button("Revert").clicked() {
+                    *draft = app_rules.clone();
+                    *error_msg = None;
+                }
+            });
+
+            if let Some(error_msg) = error_msg.as_ref() {
+                ui.label(RichText::new(error_msg).color(Color32::RED));
+            }
+        });
+
+        changed
+    }
+}
+
+fn show_rules_grid(ui: &mut egui::Ui, rules: &mut Vec<AppRule>) {
+    let mut to_remove = None;
+    egui::Grid::new("app_rules_grid")
+        .striped(true)
+        .show(ui, |ui| {
+            ui.label("Match");
+            ui.label("App name");
+            ui.label("Alias");
+            ui.label("Category");
+            ui.end_row();
+
+            for (i, rule) in rules.iter_mut().enumerate() {
+                egui::ComboBox::from_id_source(("app_rule_match_kind", i))
+                    .selected_text(rule.match_kind.to_string())
+                    .show_ui(ui, |ui| {
+                        for match_kind in enum_iterator::all::<MatchKind>() {
+                            ui.selectable_value(
+                                &mut rule.match_kind,
+                                match_kind,
+                                match_kind.to_string(),
+                            );
+                        }
+                    });
+                ui.text_edit_singleline(&mut rule.pattern);
+                ui.text_edit_singleline(&mut rule.alias);
+                ui.text_edit_singleline(&mut rule.category);
+                if ui.small_button("🗑").clicked() {
+                    to_remove = Some(i);
+                }
+                ui.end_row();
+            }
+        });
+
+    if let Some(i) = to_remove {
+        rules.remove(i);
+    }
+}
+
+fn validate(app_rules: &AppRules) -> Result<(), String> {
+    for rule in app_rules.rules.iter() {
+        if rule.pattern.trim().is_empty() {
+            return Err("Every rule needs an app name to match".to_string());
+        }
+        rule.compile()
+            .map_err(|e| format!("Invalid pattern \"{}\": {}", rule.pattern, e))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,217 @@
+use super::widgets::show_centered_window;
+use crate::networking::{
+    encryption_settings::{EncryptionSettings, Readers, TrustedKey},
+    network_manager::NetworkManager,
+};
+use egui::{Color32, RichText};
+use gwaihir_client_lib::{
+    encryption::{EncryptionMode, GroupKey, KeyPair, PublicKey},
+    UniqueUserId,
+};
+
+pub struct EncryptionWindow {
+    shown: bool,
+    friend_name_input: String,
+    friend_key_input: String,
+    friend_user_id_input: String,
+    group_key_input: String,
+    error_msg: Option<String>,
+}
+
+impl EncryptionWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            friend_name_input: String::new(),
+            friend_key_input: String::new(),
+            friend_user_id_input: String::new(),
+            group_key_input: String::new(),
+            error_msg: None,
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        settings: &mut EncryptionSettings,
+        network_manager: &mut NetworkManager,
+    ) {
+        let mut changed = false;
+        self.shown = show_centered_window(self.shown, "Encryption", ctx, |ui| {
+            changed |= ui
+                .checkbox(&mut settings.enabled, "Encrypt published statuses")
+                .changed();
+
+            ui.horizontal(|ui| {
+                ui.label("Your public key: ");
+                let public_key = settings.key_pair.public_key().to_string();
+                ui.label(RichText::new(&public_key).monospace());
+                if ui.button("Copy").clicked() {
+                    ui.output_mut(|o| o.copied_text = public_key);
+                }
+            });
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Regenerate key pair")
+                    .on_hover_text("Friends will need your new public key to read your data")
+                    .clicked()
+                {
+                    settings.key_pair = KeyPair::generate();
+                    changed = true;
+                }
+            });
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.label("Mode: ");
+                changed |= ui
+                    .selectable_value(
+                        &mut settings.mode,
+                        EncryptionMode::PerRecipient,
+                        "Per friend",
+                    )
+                    .changed();
+                changed |= ui
+                    .selectable_value(&mut settings.mode, EncryptionMode::Group, "Group key")
+                    .changed();
+            });
+
+            if settings.mode == EncryptionMode::Group {
+                changed |= self.show_group_key(ui, settings);
+                ui.separator();
+                ui.label("Friends' keys are still used for sensors shared with them in particular");
+            }
+            changed |= self.show_trusted_keys(ui, settings);
+
+            if let Some(error_msg) = self.error_msg.as_ref() {
+                ui.label(RichText::new(error_msg).color(Color32::RED));
+            }
+
+            ui.separator();
+            show_readers(ui, settings.readers());
+        });
+
+        if changed {
+            network_manager.set_encryption(settings.to_payload_encryption());
+        }
+    }
+
+    fn show_trusted_keys(&mut self, ui: &mut egui::Ui, settings: &mut EncryptionSettings) -> bool {
+        let mut changed = false;
+        ui.label("Friends' public keys:");
+        let mut to_remove = None;
+        for (i, trusted_key) in settings.trusted_keys.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(&trusted_key.name).strong());
+                ui.label(RichText::new(trusted_key.public_key.to_string()).monospace());
+                if let Some(user_id) = trusted_key.user_id.as_ref() {
+                    ui.label(format!("(user {})", user_id));
+                }
+                if ui.small_button("Remove").clicked() {
+                    to_remove = Some(i);
+                }
+            });
+        }
+
+        if let Some(i) = to_remove {
+            settings.trusted_keys.remove(i);
+            changed = true;
+        }
+
+        ui.horizontal(|ui| {
+            ui.label("Name: ");
+            ui.add(egui::TextEdit::singleline(&mut self.friend_name_input).desired_width(100.0));
+            ui.label("Key: ");
+            ui.text_edit_singleline(&mut self.friend_key_input);
+        });
+        ui.horizontal(|ui| {
+            ui.label("User ID (optional): ");
+            ui.add(egui::TextEdit::singleline(&mut self.friend_user_id_input).desired_width(150.0));
+            if ui.button("Add").clicked() {
+                match self.friend_key_input.parse::<PublicKey>() {
+                    Ok(public_key) => {
+                        let user_id = UniqueUserId::new(self.friend_user_id_input.trim());
+                        settings.trusted_keys.push(TrustedKey {
+                            name: std::mem::take(&mut self.friend_name_input),
+                            public_key,
+                            user_id: (!user_id.is_empty()).then_some(user_id),
+                        });
+                        self.friend_key_input.clear();
+                        self.friend_user_id_input.clear();
+                        self.error_msg = None;
+                        changed = true;
+                    }
+                    Err(e) => self.error_msg = Some(e.to_string()),
+                }
+            }
+        });
+
+        changed
+    }
+
+    fn show_group_key(&mut self, ui: &mut egui::Ui, settings: &mut EncryptionSettings) -> bool {
+        let mut changed = false;
+        match settings.group_key.as_ref() {
+            Some(group_key) => {
+                ui.horizontal(|ui| {
+                    ui.label(format!("Group key in use (id {})", group_key.id()));
+                    if ui.button("Copy").clicked() {
+                        ui.output_mut(|o| o.copied_text = group_key.to_string());
+                    }
+                });
+            }
+            None => {
+                ui.label(RichText::new("No group key set").color(Color32::RED));
+            }
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Generate new group key").clicked() {
+                settings.group_key = Some(GroupKey::generate());
+                changed = true;
+            }
+
+            ui.text_edit_singleline(&mut self.group_key_input);
+            if ui.button("Import").clicked() {
+                match self.group_key_input.parse::<GroupKey>() {
+                    Ok(group_key) => {
+                        settings.group_key = Some(group_key);
+                        self.group_key_input.clear();
+                        self.error_msg = None;
+                        changed = true;
+                    }
+                    Err(e) => self.error_msg = Some(e.to_string()),
+                }
+            }
+        });
+
+        changed
+    }
+}
+
+fn show_readers(ui: &mut egui::Ui, readers: Readers) {
+    match readers {
+        Readers::Everyone => {
+            ui.label(
+                RichText::new("Anyone with access to the network can read your data")
+                    .color(Color32::GOLD),
+            );
+        }
+        Readers::GroupKeyHolders => {
+            ui.label("Anyone with the group key can read your data");
+        }
+        Readers::Friends(names) if names.is_empty() => {
+            ui.label("Only you can read your data");
+        }
+        Readers::Friends(names) => {
+            ui.label("These friends can read your data:");
+            for name in names {
+                ui.label(format!("\t{}", name));
+            }
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use super::widgets::show_centered_window;
+use crate::networking::{circle::CircleId, network_manager::NetworkManager};
+use egui::{Color32, RichText};
+use gwaihir_client_lib::{FriendshipAction, Friendships, UniqueUserId};
+use std::collections::HashMap;
+
+pub struct FriendsWindow {
+    shown: bool,
+    friend_id_input: HashMap<CircleId, String>,
+}
+
+impl FriendsWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            friend_id_input: HashMap::new(),
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    /// *Returns:* How many friend requests are waiting for an answer, across all circles
+    pub fn num_pending_requests(friendships: &HashMap<CircleId, Friendships>) -> usize {
+        friendships
+            .values()
+            .map(|f| f.incoming_requests.len())
+            .sum()
+    }
+
+    /// `get_display_name` gives the name of a user we're receiving statuses from
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        circles: &[(CircleId, String)],
+        friendships: &HashMap<CircleId, Friendships>,
+        network_manager: &NetworkManager,
+        get_display_name: impl Fn(&CircleId, &UniqueUserId) -> Option<String>,
+    ) {
+        self.shown = show_centered_window(self.shown, "Friends", ctx, |ui| {
+            let mut any_shown = false;
+            for (circle_id, circle_name) in circles {
+                let Some(friendships) = friendships.get(circle_id) else {
+                    continue;
+                };
+                any_shown = true;
+                let update = |user_id: &UniqueUserId, action| {
+                    network_manager.update_friendship(circle_id, user_id, action)
+                };
+                let display_name = |user_id: &UniqueUserId| {
+                    get_display_name(circle_id, user_id).unwrap_or_else(|| user_id.to_string())
+                };
+
+                egui::CollapsingHeader::new(RichText::new(circle_name).strong())
+                    .id_source(("friends", circle_id))
+                    .default_open(true)
+                    .show(ui, |ui| {
+                        ui.label("Requests:");
+                        if friendships.incoming_requests.is_empty() {
+                            ui.label(RichText::new("None").italics());
+                        }
+                        for request in friendships.incoming_requests.iter() {
+                            ui.horizontal(|ui| {
+                                let name = request.username.as_ref();
+                                if name.is_empty() {
+                                    ui.label(request.user_id.to_string());
+                                } else {
+                                    ui.label(format!("{} ({})", name, request.user_id));
+                                }
+                                if ui.button("Accept").clicked() {
+                                    update(&request.user_id, FriendshipAction::Accept);
+                                }
+                                if ui.button("Decline").clicked() {
+                                    update(&request.user_id, FriendshipAction::Remove);
+                                }
+                                if ui.button("Block").clicked() {
+                                    update(&request.user_id, FriendshipAction::Block);
+                                }
+                            });
+                        }
+
+                        ui.separator();
+                        ui.label("Friends:");
+                        if friendships.friends.is_empty() {
+                            ui.label(RichText::new("None").italics());
+                        }
+                        let mut friends = friendships.friends.iter().collect::<Vec<_>>();
+                        friends.sort();
+                        for user_id in friends {
+                            ui.horizontal(|ui| {
+                                ui.label(display_name(user_id));
+                                if ui.button("Remove").clicked() {
+                                    update(user_id, FriendshipAction::Remove);
+                                }
+                                if ui.button("Block").clicked() {
+                                    update(user_id, FriendshipAction::Block);
+                                }
+                            });
+                        }
+
+                        let mut outgoing = friendships.outgoing_requests.iter().collect::<Vec<_>>();
+                        outgoing.sort();
+                        for user_id in outgoing {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(format!("{} (waiting)", user_id)).weak());
+                                if ui.button("Cancel").clicked() {
+                                    update(user_id, FriendshipAction::Remove);
+                                }
+                            });
+                        }
+
+                        if !friendships.blocked.is_empty() {
+                            ui.separator();
+                            ui.label("Blocked:");
+                            let mut blocked = friendships.blocked.iter().collect::<Vec<_>>();
+                            blocked.sort();
+                            for user_id in blocked {
+                                ui.horizontal(|ui| {
+                                    ui.label(
+                                        RichText::new(user_id.to_string()).color(Color32::RED),
+                                    );
+                                    if ui.button("Unblock").clicked() {
+                                        update(user_id, FriendshipAction::Remove);
+                                    }
+                                });
+                            }
+                        }
+
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            let input = self.friend_id_input.entry(*circle_id).or_default();
+                            ui.add(
+                                egui::TextEdit::singleline(input)
+                                    .hint_text("User ID")
+                                    .desired_width(150.0),
+                            );
+                            let user_id = input.trim();
+                            if ui
+                                .add_enabled(!user_id.is_empty(), egui::Button::new("Send Request"))
+                                .clicked()
+                            {
+                                update(&UniqueUserId::new(user_id), FriendshipAction::Request);
+                                input.clear();
+                            }
+                        });
+                    });
+            }
+
+            if !any_shown {
+                ui.label(
+                    "None of your circles need friend requests. In SpacetimeDB circles, users only see each other once both have agreed to.",
+                );
+            }
+        });
+    }
+}
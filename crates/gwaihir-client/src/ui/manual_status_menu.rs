@@ -0,0 +1,80 @@
+use crate::sensors::outputs::manual_status::{Availability, ManualStatus};
+use gwaihir_client_lib::chrono::{Duration, Utc};
+
+const EXPIRY_OPTIONS: [(&str, Option<i64>); 4] = [
+    ("Don't clear", None),
+    ("30 minutes", Some(30)),
+    ("1 hour", Some(60)),
+    ("4 hours", Some(60 * 4)),
+];
+
+pub struct ManualStatusMenu {
+    availability: Availability,
+    message_input: String,
+    expiry_minutes: Option<i64>,
+}
+
+impl ManualStatusMenu {
+    pub fn new() -> Self {
+        Self {
+            availability: Availability::default(),
+            message_input: String::new(),
+            expiry_minutes: None,
+        }
+    }
+
+    pub fn title(status: Option<&ManualStatus>) -> String {
+        match status.filter(|s| !s.is_expired(Utc::now())) {
+            Some(status) => format!("Status: {}", status.availability),
+            None => "Status".to_string(),
+        }
+    }
+
+    /// *Returns:* Whether the status changed
+    pub fn show(&mut self, ui: &mut egui::Ui, status: &mut Option<ManualStatus>) -> bool {
+        for availability in enum_iterator::all::<Availability>() {
+            ui.radio_value(
+                &mut self.availability,
+                availability,
+                availability.to_string(),
+            );
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("Message: ");
+            ui.text_edit_singleline(&mut self.message_input);
+        });
+        ui.horizontal(|ui| {
+            ui.label("Clear after: ");
+            for (text, minutes) in EXPIRY_OPTIONS {
+                ui.selectable_value(&mut self.expiry_minutes, minutes, text);
+            }
+        });
+
+        let mut changed = false;
+        ui.horizontal(|ui| {
+            if ui.button("Set").clicked() {
+                *status = Some(ManualStatus {
+                    availability: self.availability,
+                    message: self.message_input.trim().to_string(),
+                    expires_at: self
+                        .expiry_minutes
+                        .map(|minutes| Utc::now() + Duration::minutes(minutes)),
+                });
+                changed = true;
+                ui.close_menu();
+            }
+
+            if ui.button("Clear").clicked() {
+                *status = None;
+                self.availability = Availability::default();
+                self.message_input.clear();
+                changed = true;
+                ui.close_menu();
+            }
+        });
+
+        changed
+    }
+}
@@ -1,6 +1,15 @@
+pub mod activity_settings_window;
 pub mod add_fake_user_window;
+pub mod app_rules_window;
+pub mod encryption_window;
+pub mod friends_window;
+pub mod manual_status_menu;
 pub mod network_window;
+pub mod overrides_window;
 pub mod raw_data_window;
+pub mod sent_data_window;
+pub mod sharing_window;
 pub mod time_formatting;
+pub mod timeline_window;
 pub mod ui_extension_methods;
 pub mod widgets;
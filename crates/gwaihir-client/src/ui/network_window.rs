@@ -1,19 +1,34 @@
 use super::widgets::show_centered_window;
-use crate::{networking::network_manager::NetworkManager, persistence::Persistence};
-use egui::ComboBox;
-use gwaihir_client_lib::{NetworkInterface, NetworkType};
+use crate::{
+    networking::{
+        circle::{Circle, CircleId},
+        encryption_settings::EncryptionSettings,
+        network_manager::NetworkManager,
+    },
+    persistence::Persistence,
+    sensors::{outputs::sensor_output::SensorOutputKind, privacy_level::PrivacyLevel},
+};
+use egui::{CollapsingHeader, Color32, ComboBox};
+use gwaihir_client_lib::{NetworkType, UniqueUserId};
+use std::collections::HashSet;
+
+const NETWORK_TYPES: [NetworkType; 4] = [
+    NetworkType::Offline,
+    NetworkType::SpacetimeDB,
+    NetworkType::PeerToPeer,
+    NetworkType::Relay,
+];
 
 pub struct NetworkWindow {
     shown: bool,
-    selected_network_type: NetworkType,
+    new_recipient_id: String,
 }
 
 impl NetworkWindow {
-    pub fn new(network_manager: &NetworkManager) -> Self {
-        let initial_network_type = network_manager.get_network_type();
+    pub fn new() -> Self {
         Self {
             shown: false,
-            selected_network_type: initial_network_type,
+            new_recipient_id: String::new(),
         }
     }
 
@@ -26,52 +41,264 @@ impl NetworkWindow {
         ctx: &egui::Context,
         network_manager: &mut NetworkManager,
         persistence: &mut Persistence,
-        clear_user_statuses: impl FnOnce(),
+        mut clear_user_statuses: impl FnMut(&CircleId),
     ) {
-        self.shown = show_centered_window(self.shown, "Network", ctx, |ui| {
-            ui.label(format!(
-                "Current network: {}",
-                network_manager.get_network_type()
-            ));
-            ui.horizontal(|ui| {
-                ui.label("Network: ");
-                ComboBox::from_id_source("network_type_selector")
-                    .selected_text(self.selected_network_type.to_string())
-                    .show_ui(ui, |ui| {
-                        ui.selectable_value(
-                            &mut self.selected_network_type,
-                            NetworkType::Offline,
-                            NetworkType::Offline.to_string(),
-                        );
-                        ui.selectable_value(
-                            &mut self.selected_network_type,
-                            NetworkType::SpacetimeDB,
-                            NetworkType::SpacetimeDB.to_string(),
-                        );
+        self.shown = show_centered_window(self.shown, "Circles", ctx, |ui| {
+            let mut to_remove = None;
+            let encryption = &persistence.encryption;
+            for circle in persistence.circles.iter_mut() {
+                egui::CollapsingHeader::new(&circle.name)
+                    .id_source(circle.id)
+                    .show(ui, |ui| {
+                        ui.label(format!(
+                            "Current network: {}",
+                            network_manager
+                                .get_network_type(&circle.id)
+                                .map_or_else(|| "None".to_string(), |t| t.to_string())
+                        ));
+                        ui.horizontal(|ui| {
+                            ui.label("Name: ");
+                            ui.text_edit_singleline(&mut circle.name);
+                        });
+                        ui.horizontal(|ui| {
+                            ui.label("Network: ");
+                            ComboBox::from_id_source(("network_type_selector", circle.id))
+                                .selected_text(circle.network_type.to_string())
+                                .show_ui(ui, |ui| {
+                                    for network_type in NETWORK_TYPES {
+                                        let text = network_type.to_string();
+                                        ui.selectable_value(
+                                            &mut circle.network_type,
+                                            network_type,
+                                            text,
+                                        );
+                                    }
+                                });
+                        });
+
+                        show_network_specific_config(ui, circle);
+
+                        // The SpacetimeDB SDK can only hold one connection at a time
+                        let spacetimedb_taken = circle.network_type == NetworkType::SpacetimeDB
+                            && network_manager
+                                .spacetimedb_circle()
+                                .is_some_and(|id| id != circle.id);
+                        if spacetimedb_taken {
+                            ui.colored_label(
+                                Color32::RED,
+                                "Another circle is already using SpacetimeDB, and only one can at a time. Use a different network, or switch the other circle away from SpacetimeDB first.",
+                            );
+                        }
+
+                        let shared_sensors_changed = show_shared_sensors(ui, circle);
+                        let privacy_level_changed = show_privacy_level(ui, circle);
+                        // Other networks send everyone the same status
+                        let extra_sensors_changed = circle.network_type == NetworkType::SpacetimeDB
+                            && show_extra_sensors_by_user(
+                                ui,
+                                circle,
+                                encryption,
+                                &mut self.new_recipient_id,
+                            );
+                        if shared_sensors_changed || privacy_level_changed || extra_sensors_changed
+                        {
+                            network_manager.update_sharing(circle);
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui
+                                .add_enabled(!spacetimedb_taken, egui::Button::new("Update Network"))
+                                .clicked()
+                            {
+                                clear_user_statuses(&circle.id);
+                                network_manager.reinit_circle(circle);
+                            }
+
+                            if ui.button("Leave Circle").clicked() {
+                                to_remove = Some(circle.id);
+                            }
+                        });
                     });
-            });
+            }
 
-            self.show_network_specific_config(ui, persistence);
+            if let Some(circle_id) = to_remove {
+                clear_user_statuses(&circle_id);
+                network_manager.remove_circle(&circle_id);
+                persistence.circles.retain(|c| c.id != circle_id);
+            }
 
-            if ui.button("Update Network").clicked() {
-                clear_user_statuses();
-                network_manager.reinit_network(
-                    self.selected_network_type.clone(),
-                    persistence.spacetimedb_db_name.clone(),
-                );
+            ui.separator();
+            if ui.button("Add Circle").clicked() {
+                let circle = Circle::new("New Circle".to_string(), NetworkType::Offline);
+                network_manager.reinit_circle(&circle);
+                persistence.circles.push(circle);
             }
         });
     }
+}
 
-    fn show_network_specific_config(&self, ui: &mut egui::Ui, persistence: &mut Persistence) {
-        match self.selected_network_type {
-            NetworkType::Offline => (),
-            NetworkType::SpacetimeDB => {
-                ui.horizontal(|ui| {
-                    ui.label("DB Name: ");
-                    ui.text_edit_singleline(&mut persistence.spacetimedb_db_name);
-                });
-            }
+fn show_network_specific_config(ui: &mut egui::Ui, circle: &mut Circle) {
+    match circle.network_type {
+        NetworkType::Offline => (),
+        NetworkType::SpacetimeDB => {
+            ui.horizontal(|ui| {
+                ui.label("Host URI: ");
+                ui.text_edit_singleline(&mut circle.spacetimedb_host_uri);
+            });
+            ui.horizontal(|ui| {
+                ui.label("DB Name: ");
+                ui.text_edit_singleline(&mut circle.spacetimedb_db_name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Credentials Directory: ");
+                ui.text_edit_singleline(&mut circle.spacetimedb_creds_dir);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Catch up on the last ");
+                ui.add(
+                    egui::DragValue::new(&mut circle.spacetimedb_backfill_minutes)
+                        .clamp_range(0..=24 * 60),
+                );
+                ui.label(" minutes of friends' statuses");
+            })
+            .response
+            .on_hover_text(
+                "Fetched when connecting. Sensors shared with you in particular aren't kept, so only what everyone saw is caught up on.",
+            );
+        }
+        NetworkType::PeerToPeer => {
+            ui.horizontal(|ui| {
+                ui.label("Group Name: ");
+                ui.text_edit_singleline(&mut circle.peer_to_peer.group_name);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Discovery Port: ");
+                ui.add(egui::DragValue::new(
+                    &mut circle.peer_to_peer.discovery_port,
+                ));
+            });
+            ui.label(
+                "Peers on your local network using the same group name and port will find each other",
+            );
+        }
+        NetworkType::Relay => {
+            ui.horizontal(|ui| {
+                ui.label("Relay URI: ");
+                ui.text_edit_singleline(&mut circle.relay_uri);
+            });
+        }
+    }
+}
+
+/// *Returns:* Whether the set of shared sensors changed
+fn show_shared_sensors(ui: &mut egui::Ui, circle: &mut Circle) -> bool {
+    let mut changed = false;
+    ui.label("Shared with this circle:");
+    for kind in SensorOutputKind::all_shareable() {
+        let mut shared = circle.is_shared(kind);
+        if ui.checkbox(&mut shared, kind.to_string()).changed() {
+            circle.set_shared(kind, shared);
+            changed = true;
         }
     }
+
+    changed
+}
+
+/// *Returns:* Whether the circle's privacy level changed
+fn show_privacy_level(ui: &mut egui::Ui, circle: &mut Circle) -> bool {
+    let text = |level: Option<PrivacyLevel>| match level {
+        Some(level) => level.to_string(),
+        None => "Default".to_string(),
+    };
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("App privacy: ");
+        ComboBox::from_id_source(("privacy_level_selector", circle.id))
+            .selected_text(text(circle.privacy_level))
+            .show_ui(ui, |ui| {
+                let levels =
+                    std::iter::once(None).chain(enum_iterator::all::<PrivacyLevel>().map(Some));
+                for level in levels {
+                    changed |= ui
+                        .selectable_value(&mut circle.privacy_level, level, text(level))
+                        .changed();
+                }
+            });
+    });
+
+    changed
+}
+
+/// *Returns:* Whether the sensors shared with particular users changed
+fn show_extra_sensors_by_user(
+    ui: &mut egui::Ui,
+    circle: &mut Circle,
+    encryption: &EncryptionSettings,
+    new_recipient_id: &mut String,
+) -> bool {
+    let mut changed = false;
+    ui.label("Also shared with particular users:");
+
+    let mut user_ids: Vec<_> = circle.extra_sensors_by_user.keys().cloned().collect();
+    user_ids.sort();
+    for user_id in user_ids {
+        let shared_sensors = circle.shared_sensors();
+        let Some(extra_sensors) = circle.extra_sensors_by_user.get_mut(&user_id) else {
+            continue;
+        };
+        let mut remove = false;
+        CollapsingHeader::new(user_id.to_string())
+            .id_source(("extra_sensors", circle.id, user_id.to_string()))
+            .show(ui, |ui| {
+                if !encryption.has_key_for(&user_id) {
+                    ui.colored_label(
+                        Color32::RED,
+                        "Nothing extra is sent until you add their public key with this user ID in the encryption settings",
+                    );
+                }
+                let mut not_yet_shared = SensorOutputKind::all_shareable()
+                    .filter(|kind| !shared_sensors.contains(kind))
+                    .peekable();
+                if not_yet_shared.peek().is_none() {
+                    ui.label("Everything is already shared with the whole circle");
+                }
+                for kind in not_yet_shared {
+                    let mut shared = extra_sensors.contains(&kind);
+                    if ui.checkbox(&mut shared, kind.to_string()).changed() {
+                        if shared {
+                            extra_sensors.insert(kind);
+                        } else {
+                            extra_sensors.remove(&kind);
+                        }
+                        changed = true;
+                    }
+                }
+                remove = ui.button("Remove User").clicked();
+            });
+        if remove {
+            circle.extra_sensors_by_user.remove(&user_id);
+            changed = true;
+        }
+    }
+
+    ui.horizontal(|ui| {
+        ui.add(
+            egui::TextEdit::singleline(new_recipient_id)
+                .hint_text("User ID")
+                .desired_width(150.0),
+        );
+        let user_id = UniqueUserId::new(new_recipient_id.trim());
+        let can_add = !user_id.is_empty() && !circle.extra_sensors_by_user.contains_key(&user_id);
+        if ui
+            .add_enabled(can_add, egui::Button::new("Add User"))
+            .clicked()
+        {
+            circle.extra_sensors_by_user.insert(user_id, HashSet::new());
+            new_recipient_id.clear();
+            changed = true;
+        }
+    });
+
+    changed
 }
@@ -0,0 +1,180 @@
+use super::widgets::show_centered_window;
+use crate::sensors::{
+    outputs::{
+        sensor_output::{SensorOutput, SensorOutputKind},
+        sensor_outputs::SensorOutputs,
+    },
+    overrides::{OverrideMode, SensorOverride, SensorOverrides},
+};
+use chrono_humanize::HumanTime;
+use egui::{Color32, RichText, ScrollArea};
+use gwaihir_client_lib::chrono::{Duration, Utc};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, PartialEq)]
+enum DraftMode {
+    Real,
+    Hidden,
+    Fake,
+}
+
+struct OverrideDraft {
+    mode: DraftMode,
+    json_input: String,
+    time_limited: bool,
+    minutes: u32,
+    error_msg: Option<String>,
+}
+
+impl OverrideDraft {
+    fn new(current: Option<&SensorOverride>, real_output: Option<&SensorOutput>) -> Self {
+        let (mode, fake) = match current.map(|o| &o.mode) {
+            None => (DraftMode::Real, real_output),
+            Some(OverrideMode::Hidden) => (DraftMode::Hidden, real_output),
+            Some(OverrideMode::Fake(fake)) => (DraftMode::Fake, Some(fake)),
+        };
+
+        Self {
+            mode,
+            json_input: fake
+                .and_then(|f| serde_json::to_string_pretty(f).ok())
+                .unwrap_or_default(),
+            time_limited: false,
+            minutes: 60,
+            error_msg: None,
+        }
+    }
+
+    fn to_override(&self, kind: SensorOutputKind) -> Result<Option<SensorOverride>, String> {
+        let mode = match self.mode {
+            DraftMode::Real => return Ok(None),
+            DraftMode::Hidden => OverrideMode::Hidden,
+            DraftMode::Fake => {
+                let fake: SensorOutput =
+                    serde_json::from_str(&self.json_input).map_err(|e| e.to_string())?;
+                if fake.kind() != kind {
+                    return Err(format!("Expected a {} output, not {}", kind, fake.kind()));
+                }
+                OverrideMode::Fake(fake)
+            }
+        };
+
+        let expires_at = self
+            .time_limited
+            .then(|| Utc::now() + Duration::minutes(self.minutes.into()));
+        Ok(Some(SensorOverride { mode, expires_at }))
+    }
+}
+
+pub struct OverridesWindow {
+    shown: bool,
+    drafts: HashMap<SensorOutputKind, OverrideDraft>,
+}
+
+impl OverridesWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            drafts: HashMap::new(),
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    /// `real_outputs` are used to pre-fill the fake values with something sensible
+    ///
+    /// *Returns:* Whether any override changed
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        overrides: &mut SensorOverrides,
+        real_outputs: Option<&SensorOutputs>,
+    ) -> bool {
+        let mut changed = false;
+        self.shown = show_centered_window(self.shown, "Sensor Overrides", ctx, |ui| {
+            ui.label("Choose what each sensor reports to others. Faked values are sent as-is");
+            ScrollArea::vertical().show(ui, |ui| {
+                for kind in SensorOutputKind::all_shareable() {
+                    ui.separator();
+                    let current = overrides.get(kind);
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(kind.to_string()).strong());
+                        ui.label(current_override_text(current));
+                    });
+
+                    let draft = self.drafts.entry(kind).or_insert_with(|| {
+                        let real_output = real_outputs
+                            .and_then(|outputs| outputs.outputs.iter().find(|o| o.kind() == kind));
+                        OverrideDraft::new(current, real_output)
+                    });
+                    if show_draft(ui, kind, draft) {
+                        match draft.to_override(kind) {
+                            Ok(sensor_override) => {
+                                overrides.set(kind, sensor_override);
+                                draft.error_msg = None;
+                                changed = true;
+                            }
+                            Err(e) => draft.error_msg = Some(e),
+                        }
+                    }
+                }
+            });
+        });
+
+        changed
+    }
+}
+
+/// *Returns:* Whether the user asked to apply the draft
+fn show_draft(ui: &mut egui::Ui, kind: SensorOutputKind, draft: &mut OverrideDraft) -> bool {
+    ui.horizontal(|ui| {
+        ui.selectable_value(&mut draft.mode, DraftMode::Real, "Real");
+        ui.selectable_value(&mut draft.mode, DraftMode::Hidden, "Hidden");
+        ui.selectable_value(&mut draft.mode, DraftMode::Fake, "Fake");
+    });
+
+    if draft.mode == DraftMode::Fake {
+        ui.push_id(kind, |ui| {
+            ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                ui.code_editor(&mut draft.json_input);
+            });
+        });
+    }
+
+    let mut apply = false;
+    ui.horizontal(|ui| {
+        if draft.mode != DraftMode::Real {
+            ui.checkbox(&mut draft.time_limited, "Only for the next");
+            ui.add_enabled(
+                draft.time_limited,
+                egui::DragValue::new(&mut draft.minutes)
+                    .clamp_range(1..=60 * 24)
+                    .suffix(" min"),
+            );
+        }
+        apply = ui.button("Apply").clicked();
+    });
+
+    if let Some(error_msg) = draft.error_msg.as_ref() {
+        ui.label(RichText::new(error_msg).color(Color32::RED));
+    }
+
+    apply
+}
+
+fn current_override_text(current: Option<&SensorOverride>) -> String {
+    let Some(current) = current else {
+        return "(sending real data)".to_string();
+    };
+
+    let mode = match current.mode {
+        OverrideMode::Hidden => "hidden",
+        OverrideMode::Fake(_) => "faked",
+    };
+    match current.expires_at {
+        Some(expires_at) => format!("({}, ends {})", mode, HumanTime::from(expires_at)),
+        None => format!("({})", mode),
+    }
+}
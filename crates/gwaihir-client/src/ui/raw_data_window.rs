@@ -12,15 +12,6 @@ pub struct TimestampedData<T> {
     data: T,
 }
 
-impl<T> TimestampedData<T> {
-    pub fn now(data: T) -> Self {
-        Self {
-            time: Local::now(),
-            data,
-        }
-    }
-}
-
 impl From<&UserStatus<SensorOutputs>> for TimestampedData<SensorOutputs> {
     fn from(value: &UserStatus<SensorOutputs>) -> Self {
         Self {
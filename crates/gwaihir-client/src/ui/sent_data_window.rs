@@ -0,0 +1,77 @@
+use super::widgets::show_centered_window;
+use crate::networking::{
+    circle::{Circle, CircleId},
+    network_manager::SentUpdate,
+};
+use egui::{CollapsingHeader, ScrollArea, TextEdit};
+use gwaihir_client_lib::{
+    chrono::{DateTime, Local},
+    UniqueUserId,
+};
+use std::collections::HashMap;
+
+/// Shows what we last published, to each circle and to each user sent extra sensors
+pub struct SentDataWindow {
+    shown: bool,
+    sent: Option<(DateTime<Local>, Vec<SentUpdate>)>,
+}
+
+impl SentDataWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            sent: None,
+        }
+    }
+
+    pub fn set_sent(&mut self, sent: Vec<SentUpdate>) {
+        self.sent = Some((Local::now(), sent));
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        circles: &[Circle],
+        own_user_ids: &HashMap<CircleId, UniqueUserId>,
+    ) {
+        self.shown = show_centered_window(self.shown, "Last Sent Data", ctx, |ui| {
+            let Some((time, sent)) = self.sent.as_ref() else {
+                ui.label("No data yet");
+                return;
+            };
+
+            ui.label(format!("Data from {}", time));
+            ScrollArea::vertical().show(ui, |ui| {
+                for update in sent {
+                    let circle_name = circles
+                        .iter()
+                        .find(|c| c.id == update.circle_id)
+                        .map_or("Unknown circle", |c| c.name.as_str());
+                    let title = match update.recipient.as_ref() {
+                        Some(user_id) => format!("{} (only to {})", circle_name, user_id),
+                        None => circle_name.to_string(),
+                    };
+                    CollapsingHeader::new(title)
+                        .id_source(("sent_data", update.circle_id, &update.recipient))
+                        .show(ui, |ui| {
+                            if let Some(user_id) = own_user_ids.get(&update.circle_id) {
+                                ui.label(format!("Your user ID: {}", user_id));
+                            }
+                            match serde_json::to_string_pretty(&update.sensor_outputs) {
+                                Ok(mut text) => {
+                                    TextEdit::multiline(&mut text).show(ui);
+                                }
+                                Err(_) => {
+                                    ui.label("Failed to serialize...");
+                                }
+                            }
+                        });
+                }
+            });
+        });
+    }
+}
@@ -0,0 +1,66 @@
+use super::widgets::show_centered_window;
+use crate::sensors::{
+    outputs::sensor_output::SensorOutputKind, privacy_level::PrivacyLevel,
+    sharing_policy::SharingPolicy,
+};
+use egui::{Color32, ComboBox, RichText};
+use std::collections::HashMap;
+
+pub struct SharingWindow {
+    shown: bool,
+}
+
+impl SharingWindow {
+    pub fn new() -> Self {
+        Self { shown: false }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+    }
+
+    /// *Returns:* Whether the policy or privacy level changed
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        policy: &mut SharingPolicy,
+        privacy_level: &mut PrivacyLevel,
+        unavailable_sensors: &HashMap<SensorOutputKind, String>,
+    ) -> bool {
+        let mut changed = false;
+        self.shown = show_centered_window(self.shown, "Sensor Sharing", ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("App privacy: ");
+                ComboBox::from_id_source("default_privacy_level_selector")
+                    .selected_text(privacy_level.to_string())
+                    .show_ui(ui, |ui| {
+                        for level in enum_iterator::all::<PrivacyLevel>() {
+                            changed |= ui
+                                .selectable_value(privacy_level, level, level.to_string())
+                                .changed();
+                        }
+                    });
+            });
+            ui.label("Circles can choose their own app privacy in File > Manage > Circles");
+            ui.separator();
+
+            ui.label("Sensors that are turned off here are never sent to any circle");
+            ui.separator();
+            for kind in SensorOutputKind::all_shareable() {
+                let mut shared = policy.is_shared(kind);
+                ui.horizontal(|ui| {
+                    if ui.checkbox(&mut shared, kind.to_string()).changed() {
+                        policy.set_shared(kind, shared);
+                        changed = true;
+                    }
+                    if let Some(reason) = unavailable_sensors.get(&kind) {
+                        ui.label(RichText::new("⚠ Unavailable").color(Color32::YELLOW))
+                            .on_hover_text(reason);
+                    }
+                });
+            }
+        });
+
+        changed
+    }
+}
@@ -0,0 +1,303 @@
+use super::widgets::show_centered_window;
+use crate::history::{
+    timeline::{build_timeline, TimelineSpan},
+    ActivityHistory, HistoryError, HistorySettings, HistoryUser,
+};
+use egui::{ecolor::Hsva, Align2, Color32, DragValue, FontId, Rect, RichText, Sense, Stroke, Vec2};
+use gwaihir_client_lib::chrono::{
+    DateTime, Days, Duration, Local, NaiveDate, NaiveTime, TimeZone, Utc,
+};
+use log_err::LogErrResult;
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    time::Instant,
+};
+
+const TIMELINE_WIDTH: f32 = 640.0;
+const LABEL_WIDTH: f32 = 60.0;
+const ROW_HEIGHT: f32 = 18.0;
+const AXIS_HEIGHT: f32 = 16.0;
+const ROWS: [&str; 3] = ["Online", "Active", "App"];
+/// New statuses keep being recorded while the window is open
+const RELOAD_INTERVAL_SECONDS: u64 = 30;
+
+struct LoadedTimeline {
+    user: HistoryUser,
+    day: NaiveDate,
+    loaded_at: Instant,
+    spans: Vec<TimelineSpan>,
+}
+
+pub struct TimelineWindow {
+    shown: bool,
+    selected_user: Option<HistoryUser>,
+    day: NaiveDate,
+    users: Vec<(HistoryUser, String)>,
+    loaded: Option<LoadedTimeline>,
+    error_msg: Option<String>,
+}
+
+impl TimelineWindow {
+    pub fn new() -> Self {
+        Self {
+            shown: false,
+            selected_user: None,
+            day: Local::now().date_naive(),
+            users: Vec::new(),
+            loaded: None,
+            error_msg: None,
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown = shown;
+        self.loaded = None;
+    }
+
+    /// *Returns:* Whether the history settings changed
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        history: Option<&mut ActivityHistory>,
+        settings: &mut HistorySettings,
+    ) -> bool {
+        let mut changed = false;
+        let shown = self.shown;
+        self.shown = show_centered_window(shown, "Activity Timeline", ctx, |ui| {
+            ui.label("Statuses are only recorded on this computer, and never shared.");
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .checkbox(&mut settings.enabled, "Record activity history")
+                    .changed();
+                ui.label("Keep for");
+                changed |= ui
+                    .add(
+                        DragValue::new(&mut settings.retention_days)
+                            .clamp_range(1..=3650)
+                            .suffix(" days"),
+                    )
+                    .changed();
+            });
+            ui.separator();
+
+            match history {
+                Some(history) => self.show_history(ui, history),
+                None => {
+                    ui.label("Activity history isn't being recorded");
+                }
+            }
+        });
+
+        changed
+    }
+
+    fn show_history(&mut self, ui: &mut egui::Ui, history: &mut ActivityHistory) {
+        ui.horizontal(|ui| {
+            let selected_text = self
+                .selected_user
+                .as_ref()
+                .and_then(|selected| self.users.iter().find(|(user, _)| user == selected))
+                .map_or("Choose a user".to_string(), |(user, name)| {
+                    user_display_name(user, name)
+                });
+            egui::ComboBox::from_id_source("timeline_user")
+                .selected_text(selected_text)
+                .show_ui(ui, |ui| {
+                    for (user, name) in self.users.iter() {
+                        ui.selectable_value(
+                            &mut self.selected_user,
+                            Some(user.clone()),
+                            user_display_name(user, name),
+                        );
+                    }
+                });
+
+            if ui.button("⏴").clicked() {
+                self.day = self.day.pred_opt().unwrap_or(self.day);
+            }
+            ui.label(self.day.format("%a %Y-%m-%d").to_string());
+            if ui.button("⏵").clicked() {
+                self.day = self.day.succ_opt().unwrap_or(self.day);
+            }
+            if ui.button("Today").clicked() {
+                self.day = Local::now().date_naive();
+            }
+
+            if ui.button("Delete all history").clicked() {
+                history.clear().log_unwrap();
+                self.loaded = None;
+            }
+        });
+
+        if let Err(e) = self.reload_if_needed(history) {
+            self.error_msg = Some(e.to_string());
+        }
+        if let Some(error_msg) = self.error_msg.as_ref() {
+            ui.label(RichText::new(error_msg).color(Color32::RED));
+        }
+
+        if let Some(loaded) = self.loaded.as_ref() {
+            let (from, to) = day_bounds(loaded.day);
+            show_timeline(ui, &loaded.spans, from, to);
+        }
+    }
+
+    fn reload_if_needed(&mut self, history: &ActivityHistory) -> Result<(), HistoryError> {
+        let up_to_date = self.loaded.as_ref().is_some_and(|loaded| {
+            Some(&loaded.user) == self.selected_user.as_ref()
+                && loaded.day == self.day
+                && loaded.loaded_at.elapsed().as_secs() < RELOAD_INTERVAL_SECONDS
+        });
+        if up_to_date {
+            return Ok(());
+        }
+
+        self.users = history.users()?;
+        self.loaded = match self.selected_user.clone() {
+            Some(user) => {
+                let (from, to) = day_bounds(self.day);
+                let entries = history.entries(&user, from, to)?;
+                Some(LoadedTimeline {
+                    user,
+                    day: self.day,
+                    loaded_at: Instant::now(),
+                    spans: build_timeline(&entries, from, to.min(Utc::now())),
+                })
+            }
+            None => None,
+        };
+        self.error_msg = None;
+        Ok(())
+    }
+}
+
+fn user_display_name(user: &HistoryUser, name: &str) -> String {
+    match user {
+        HistoryUser::Me => "You".to_string(),
+        HistoryUser::Other(user_id) if name.is_empty() => user_id.to_string(),
+        HistoryUser::Other(_) => name.to_string(),
+    }
+}
+
+/// *Returns:* When the local day starts and ends
+fn day_bounds(day: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    let start_of = |day: NaiveDate| {
+        let midnight = day.and_time(NaiveTime::MIN);
+        Local.from_local_datetime(&midnight).earliest().map_or_else(
+            || Utc.from_utc_datetime(&midnight),
+            |t| t.with_timezone(&Utc),
+        )
+    };
+    let next_day = day.checked_add_days(Days::new(1)).unwrap_or(day);
+    (start_of(day), start_of(next_day))
+}
+
+fn show_timeline(
+    ui: &mut egui::Ui,
+    spans: &[TimelineSpan],
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) {
+    let size = Vec2::new(TIMELINE_WIDTH, ROW_HEIGHT * ROWS.len() as f32 + AXIS_HEIGHT);
+    let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+    let painter = ui.painter_at(rect);
+    let text_color = ui.visuals().text_color();
+    let plot_left = rect.left() + LABEL_WIDTH;
+    let plot_width = rect.width() - LABEL_WIDTH;
+    let day_seconds = (to - from).num_seconds().max(1) as f32;
+    let x_of =
+        |t: DateTime<Utc>| plot_left + (t - from).num_seconds() as f32 / day_seconds * plot_width;
+    let row_rect = |row: usize, start: f32, end: f32| {
+        let top = rect.top() + row as f32 * ROW_HEIGHT;
+        Rect::from_x_y_ranges(start..=end, (top + 2.0)..=(top + ROW_HEIGHT - 2.0))
+    };
+
+    for (row, label) in ROWS.iter().enumerate() {
+        painter.text(
+            egui::pos2(rect.left(), rect.top() + (row as f32 + 0.5) * ROW_HEIGHT),
+            Align2::LEFT_CENTER,
+            label,
+            FontId::proportional(12.0),
+            text_color,
+        );
+        painter.rect_filled(
+            row_rect(row, plot_left, rect.right()),
+            0.0,
+            ui.visuals().faint_bg_color,
+        );
+    }
+
+    for span in spans {
+        let (start, end) = (x_of(span.start), x_of(span.end).max(x_of(span.start) + 1.0));
+        let online_color = if span.state.locked {
+            Color32::DARK_RED
+        } else {
+            Color32::DARK_GREEN
+        };
+        painter.rect_filled(row_rect(0, start, end), 0.0, online_color);
+        if let Some(active) = span.state.active {
+            let active_color = if active {
+                Color32::GOLD
+            } else {
+                Color32::DARK_GRAY
+            };
+            painter.rect_filled(row_rect(1, start, end), 0.0, active_color);
+        }
+        if let Some(app) = span.state.app.as_ref().filter(|_| !span.state.locked) {
+            painter.rect_filled(row_rect(2, start, end), 0.0, app_color(app));
+        }
+    }
+
+    let axis_top = rect.top() + ROW_HEIGHT * ROWS.len() as f32;
+    for hour in (0..=24).step_by(3) {
+        let x = x_of(from + Duration::hours(hour));
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, axis_top)],
+            Stroke::new(1.0, ui.visuals().weak_text_color().linear_multiply(0.3)),
+        );
+        painter.text(
+            egui::pos2(x, axis_top),
+            Align2::CENTER_TOP,
+            format!("{:02}:00", hour),
+            FontId::proportional(10.0),
+            text_color,
+        );
+    }
+
+    if let Some(pos) = response.hover_pos().filter(|p| p.x >= plot_left) {
+        let t = from + Duration::seconds((((pos.x - plot_left) / plot_width) * day_seconds) as i64);
+        let text = match spans.iter().find(|s| s.start <= t && t < s.end) {
+            Some(span) => describe_span(span),
+            None => "Offline".to_string(),
+        };
+        response.on_hover_text_at_pointer(format!(
+            "{}\n{}",
+            t.with_timezone(&Local).format("%H:%M"),
+            text
+        ));
+    }
+}
+
+fn describe_span(span: &TimelineSpan) -> String {
+    let mut lines = vec![if span.state.locked {
+        "Locked".to_string()
+    } else {
+        "Online".to_string()
+    }];
+    if let Some(active) = span.state.active {
+        lines.push(if active { "Active" } else { "Inactive" }.to_string());
+    }
+    if let Some(app) = span.state.app.as_ref() {
+        lines.push(format!("Using {}", app));
+    }
+    lines.join("\n")
+}
+
+/// Each app keeps the same color from day to day
+fn app_color(app: &str) -> Color32 {
+    let mut hasher = DefaultHasher::new();
+    app.hash(&mut hasher);
+    let hue = (hasher.finish() % 360) as f32 / 360.0;
+    Hsva::new(hue, 0.6, 0.7, 1.0).into()
+}
@@ -0,0 +1,50 @@
+use crate::availability::WeeklyPattern;
+use egui::{Color32, RichText, Widget};
+use gwaihir_client_lib::chrono::Local;
+
+/// Shows an availability score from 0-100, with what we've learned about the user on hover
+#[must_use = "You should put this widget in a ui with `ui.add(widget);`"]
+pub struct AvailabilityBadge<'a> {
+    score: f64,
+    pattern: Option<&'a WeeklyPattern>,
+}
+
+impl<'a> AvailabilityBadge<'a> {
+    pub fn new(score: f64, pattern: Option<&'a WeeklyPattern>) -> Self {
+        Self { score, pattern }
+    }
+
+    fn color(&self) -> Color32 {
+        match self.score {
+            s if s >= 70.0 => Color32::DARK_GREEN,
+            s if s >= 40.0 => Color32::from_rgb(160, 120, 0),
+            _ => Color32::DARK_RED,
+        }
+    }
+
+    fn hover_text(&self) -> String {
+        let mut lines = vec![format!("Availability score: {:.0}/100", self.score)];
+        if let Some(pattern) = self.pattern {
+            if let Some(expected) = pattern.expected_score(Local::now().naive_local()) {
+                lines.push(format!("Usually {:.0}/100 at this time", expected));
+            }
+            let meetings = pattern.usual_meetings();
+            if !meetings.is_empty() {
+                lines.push("Usually in meetings:".to_string());
+                lines.extend(meetings.into_iter().map(|m| format!("\t{}", m)));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+impl Widget for AvailabilityBadge<'_> {
+    fn ui(self, ui: &mut egui::Ui) -> egui::Response {
+        ui.label(
+            RichText::new(format!(" {:.0} ", self.score))
+                .color(Color32::WHITE)
+                .background_color(self.color()),
+        )
+        .on_hover_text_at_pointer(self.hover_text())
+    }
+}
@@ -1,4 +1,5 @@
 pub mod auto_launch_checkbox;
+pub mod availability_badge;
 
 /// Shows a window at the center of the screen, while avoiding mutability issues that would come
 /// from passing a mutable reference to a field into the `open` method.
@@ -0,0 +1,46 @@
+use flexi_logger::Logger;
+use gwaihir_relay::{protocol::DEFAULT_PORT, server::RelayServer};
+use log::{error, info};
+use std::{net::TcpListener, path::PathBuf, process::ExitCode};
+
+const USAGE: &str = "Usage: gwaihir-relay [--listen <address:port>] [--data-file <path>]";
+
+fn main() -> ExitCode {
+    Logger::try_with_env_or_str("info")
+        .unwrap()
+        .start()
+        .unwrap();
+
+    let mut listen_address = format!("0.0.0.0:{}", DEFAULT_PORT);
+    let mut data_file = None;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match (arg.as_str(), args.next()) {
+            ("--listen", Some(address)) => listen_address = address,
+            ("--data-file", Some(path)) => data_file = Some(PathBuf::from(path)),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    let server = match RelayServer::new(data_file) {
+        Ok(server) => server,
+        Err(e) => {
+            error!("Failed to start relay: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let listener = match TcpListener::bind(&listen_address) {
+        Ok(listener) => listener,
+        Err(e) => {
+            error!("Failed to listen on {}: {}", listen_address, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    info!("Gwaihir relay listening on {}", listen_address);
+    server.serve(listener);
+    ExitCode::SUCCESS
+}
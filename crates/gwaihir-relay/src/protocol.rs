@@ -0,0 +1,52 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The port the relay listens on unless told otherwise
+pub const DEFAULT_PORT: u16 = 43617;
+
+/// Mirrors the `User` table of the SpacetimeDB module
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct User {
+    pub identity: String,
+    pub name: Option<String>,
+    pub online: bool,
+    pub status: Option<String>,
+    pub last_status_update: Option<DateTime<Utc>>,
+}
+
+/// Handed out by the relay on first connection, and presented again on every reconnect
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Credentials {
+    pub identity: String,
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum ClientMessage {
+    /// Must be the first message sent on a new connection
+    Hello {
+        credentials: Option<Credentials>,
+    },
+    SetName {
+        name: String,
+    },
+    SetStatus {
+        status: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum ServerMessage {
+    Welcome {
+        credentials: Credentials,
+    },
+    /// Sent for every known user after `Welcome`, then whenever a user changes
+    UserUpdated {
+        user: User,
+    },
+    Error {
+        message: String,
+    },
+}
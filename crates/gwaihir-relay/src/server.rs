@@ -0,0 +1,606 @@
+// tungstenite::Error is large, but it's only ever returned once per connection
+#![allow(clippy::result_large_err)]
+
+use crate::protocol::{ClientMessage, Credentials, ServerMessage, User};
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io,
+    net::{TcpListener, TcpStream},
+    path::PathBuf,
+    sync::{
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use thiserror::Error;
+use tungstenite::Message;
+use uuid::Uuid;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+/// How long a new connection has to complete the handshake and say hello
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+/// The same limits as the SpacetimeDB module's
+const MAX_STATUS_BYTES: usize = 64 * 1024;
+/// How many names and statuses each user can set within `RATE_LIMIT_WINDOW`
+const MAX_CALLS_PER_RATE_LIMIT_WINDOW: u32 = 120;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+type ConnectionId = u64;
+
+#[derive(Error, Debug)]
+pub enum RelayError {
+    #[error("Unknown identity or invalid token")]
+    InvalidCredentials,
+    #[error("The first message on a connection must be a hello")]
+    ExpectedHello,
+    #[error("No hello was received in time")]
+    HelloTimeout,
+    #[error("Already said hello on this connection")]
+    UnexpectedHello,
+    #[error("Names must not be empty")]
+    EmptyName,
+    #[error("Statuses must be at most {MAX_STATUS_BYTES} bytes, but this one is {0} bytes")]
+    StatusTooLong(usize),
+    #[error(
+        "Too many updates: at most {} are allowed every {} seconds",
+        MAX_CALLS_PER_RATE_LIMIT_WINDOW,
+        RATE_LIMIT_WINDOW.as_secs()
+    )]
+    TooManyUpdates,
+    #[error("Malformed message: {0}")]
+    MalformedMessage(serde_json::Error),
+    #[error("Failed to access data file: {0}")]
+    DataFile(#[from] io::Error),
+    #[error("Failed to (de)serialize data file: {0}")]
+    DataFileFormat(#[from] serde_json::Error),
+}
+
+/// Accepts WebSocket connections and relays user updates between them
+pub struct RelayServer {
+    state: Arc<Mutex<RelayState>>,
+}
+
+impl RelayServer {
+    /// Users are remembered across restarts if a data file is given
+    pub fn new(data_file: Option<PathBuf>) -> Result<Self, RelayError> {
+        Ok(Self {
+            state: Arc::new(Mutex::new(RelayState::load(data_file)?)),
+        })
+    }
+
+    /// Blocks forever, handling each connection on its own thread
+    pub fn serve(&self, listener: TcpListener) {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let state = self.state.clone();
+                    thread::spawn(move || handle_connection(stream, state));
+                }
+                Err(e) => warn!("Failed to accept connection: {}", e),
+            }
+        }
+    }
+}
+
+/// Only identities, tokens and names are worth saving. Statuses change constantly and are soon
+/// replaced anyway, so they're forgotten on restart rather than rewriting the file every time.
+#[derive(Serialize, Deserialize)]
+struct StoredUser {
+    token: String,
+    user: User,
+}
+
+struct Connection {
+    identity: String,
+    tx: Sender<ServerMessage>,
+}
+
+/// How many names and statuses a user has set in their current window
+struct RateLimit {
+    window_start: Instant,
+    calls: u32,
+}
+
+struct RelayState {
+    users: HashMap<String, StoredUser>,
+    connections: HashMap<ConnectionId, Connection>,
+    /// By identity, so reconnecting doesn't start a new window
+    rate_limits: HashMap<String, RateLimit>,
+    next_connection_id: ConnectionId,
+    data_file: Option<PathBuf>,
+}
+
+impl RelayState {
+    fn load(data_file: Option<PathBuf>) -> Result<Self, RelayError> {
+        let mut users: HashMap<String, StoredUser> = match data_file.as_ref() {
+            Some(path) if path.exists() => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+            _ => HashMap::new(),
+        };
+        for stored in users.values_mut() {
+            stored.user.online = false;
+            stored.user.status = None;
+            stored.user.last_status_update = None;
+        }
+
+        Ok(Self {
+            users,
+            connections: HashMap::new(),
+            rate_limits: HashMap::new(),
+            next_connection_id: 0,
+            data_file,
+        })
+    }
+
+    fn save(&self) {
+        if let Some(path) = self.data_file.as_ref() {
+            let users = self
+                .users
+                .iter()
+                .map(|(identity, stored)| {
+                    let user = User {
+                        online: false,
+                        status: None,
+                        last_status_update: None,
+                        ..stored.user.clone()
+                    };
+                    let stored = StoredUser {
+                        token: stored.token.clone(),
+                        user,
+                    };
+                    (identity, stored)
+                })
+                .collect::<HashMap<_, _>>();
+            let result = serde_json::to_string(&users)
+                .map_err(RelayError::from)
+                .and_then(|json| Ok(std::fs::write(path, json)?));
+            if let Err(e) = result {
+                error!("Failed to save users: {}", e);
+            }
+        }
+    }
+
+    fn connect(
+        &mut self,
+        credentials: Option<Credentials>,
+        tx: Sender<ServerMessage>,
+    ) -> Result<ConnectionId, RelayError> {
+        let (credentials, is_new_user) = match credentials {
+            Some(credentials) => match self.users.get(&credentials.identity) {
+                Some(stored) if stored.token == credentials.token => (credentials, false),
+                Some(_) => return Err(RelayError::InvalidCredentials),
+                None => {
+                    // Most likely the relay lost its data, so treat them like a new user
+                    info!(
+                        "Unknown identity {}, issuing a new one",
+                        credentials.identity
+                    );
+                    (self.create_user(), true)
+                }
+            },
+            None => (self.create_user(), true),
+        };
+
+        let connection_id = self.next_connection_id;
+        self.next_connection_id += 1;
+        self.connections.insert(
+            connection_id,
+            Connection {
+                identity: credentials.identity.clone(),
+                tx: tx.clone(),
+            },
+        );
+
+        let user = &mut self.users.get_mut(&credentials.identity).unwrap().user;
+        user.online = true;
+        let user = user.clone();
+        tx.send(ServerMessage::Welcome { credentials }).ok();
+        for stored in self.users.values() {
+            tx.send(ServerMessage::UserUpdated {
+                user: stored.user.clone(),
+            })
+            .ok();
+        }
+        self.broadcast(user, Some(connection_id));
+        if is_new_user {
+            self.save();
+        }
+
+        Ok(connection_id)
+    }
+
+    fn disconnect(&mut self, connection_id: ConnectionId) {
+        let Some(connection) = self.connections.remove(&connection_id) else {
+            return;
+        };
+
+        let still_connected = self
+            .connections
+            .values()
+            .any(|c| c.identity == connection.identity);
+        if !still_connected {
+            self.update_user(&connection.identity, |user| user.online = false);
+        }
+    }
+
+    fn handle_message(
+        &mut self,
+        connection_id: ConnectionId,
+        message: ClientMessage,
+    ) -> Result<(), RelayError> {
+        let identity = self.connections[&connection_id].identity.clone();
+        match message {
+            ClientMessage::Hello { .. } => return Err(RelayError::UnexpectedHello),
+            ClientMessage::SetName { name } => {
+                let name = validate_name(name)?;
+                self.check_rate_limit(&identity)?;
+                self.update_user(&identity, |user| user.name = Some(name));
+                self.save();
+            }
+            ClientMessage::SetStatus { status } => {
+                if status.len() > MAX_STATUS_BYTES {
+                    return Err(RelayError::StatusTooLong(status.len()));
+                }
+                self.check_rate_limit(&identity)?;
+                self.update_user(&identity, |user| {
+                    user.status = Some(status);
+                    user.last_status_update = Some(Utc::now());
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Counts a message towards `identity`'s rate limit, failing once they've sent too many.
+    fn check_rate_limit(&mut self, identity: &str) -> Result<(), RelayError> {
+        let now = Instant::now();
+        let limit = self
+            .rate_limits
+            .entry(identity.to_string())
+            .or_insert(RateLimit {
+                window_start: now,
+                calls: 0,
+            });
+        if now.duration_since(limit.window_start) >= RATE_LIMIT_WINDOW {
+            *limit = RateLimit {
+                window_start: now,
+                calls: 0,
+            };
+        }
+        if limit.calls >= MAX_CALLS_PER_RATE_LIMIT_WINDOW {
+            return Err(RelayError::TooManyUpdates);
+        }
+
+        limit.calls += 1;
+        Ok(())
+    }
+
+    fn create_user(&mut self) -> Credentials {
+        let credentials = Credentials {
+            identity: Uuid::new_v4().simple().to_string(),
+            token: format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple()),
+        };
+        self.users.insert(
+            credentials.identity.clone(),
+            StoredUser {
+                token: credentials.token.clone(),
+                user: User {
+                    identity: credentials.identity.clone(),
+                    name: None,
+                    online: false,
+                    status: None,
+                    last_status_update: None,
+                },
+            },
+        );
+        info!("Created new user {}", credentials.identity);
+
+        credentials
+    }
+
+    fn update_user(&mut self, identity: &str, update: impl FnOnce(&mut User)) {
+        if let Some(stored) = self.users.get_mut(identity) {
+            update(&mut stored.user);
+            let user = stored.user.clone();
+            self.broadcast(user, None);
+        }
+    }
+
+    fn broadcast(&self, user: User, except: Option<ConnectionId>) {
+        for (id, connection) in self.connections.iter() {
+            if Some(*id) != except {
+                connection
+                    .tx
+                    .send(ServerMessage::UserUpdated { user: user.clone() })
+                    .ok();
+            }
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, state: Arc<Mutex<RelayState>>) {
+    let address = stream.peer_addr().ok();
+    match run_connection(stream, state) {
+        Ok(()) => debug!("Connection from {:?} closed", address),
+        Err(e) => warn!("Connection from {:?} closed with error: {}", address, e),
+    }
+}
+
+enum Incoming {
+    Message(ClientMessage),
+    Malformed(serde_json::Error),
+    Nothing,
+    Closed,
+}
+
+fn run_connection(
+    stream: TcpStream,
+    state: Arc<Mutex<RelayState>>,
+) -> Result<(), tungstenite::Error> {
+    // Otherwise connections that never say anything would keep this thread around forever
+    stream.set_read_timeout(Some(HELLO_TIMEOUT))?;
+    let mut socket = tungstenite::accept(stream).map_err(|e| match e {
+        tungstenite::HandshakeError::Failure(e) => e,
+        // The blocking stream only gets interrupted by the read timeout
+        tungstenite::HandshakeError::Interrupted(_) => {
+            tungstenite::Error::Io(io::ErrorKind::TimedOut.into())
+        }
+    })?;
+    let (tx, rx) = mpsc::channel();
+
+    let connection_id = match read_client_message(&mut socket)? {
+        Incoming::Message(ClientMessage::Hello { credentials }) => {
+            state.lock().unwrap().connect(credentials, tx.clone())
+        }
+        Incoming::Message(_) => Err(RelayError::ExpectedHello),
+        Incoming::Nothing => Err(RelayError::HelloTimeout),
+        Incoming::Malformed(e) => Err(RelayError::MalformedMessage(e)),
+        Incoming::Closed => return Ok(()),
+    };
+    let connection_id = match connection_id {
+        Ok(connection_id) => connection_id,
+        Err(e) => {
+            info!("Rejecting connection: {}", e);
+            let error = ServerMessage::Error {
+                message: e.to_string(),
+            };
+            send(&mut socket, &error)?;
+            return socket.close(None);
+        }
+    };
+
+    // Reading with a timeout lets this thread also forward updates from other users
+    socket.get_ref().set_read_timeout(Some(POLL_INTERVAL))?;
+    let result = loop {
+        let error = match read_client_message(&mut socket) {
+            Ok(Incoming::Message(message)) => state
+                .lock()
+                .unwrap()
+                .handle_message(connection_id, message)
+                .err(),
+            Ok(Incoming::Malformed(e)) => Some(RelayError::MalformedMessage(e)),
+            Ok(Incoming::Nothing) => None,
+            Ok(Incoming::Closed) => break Ok(()),
+            Err(e) => break Err(e),
+        };
+        if let Some(e) = error {
+            tx.send(ServerMessage::Error {
+                message: e.to_string(),
+            })
+            .ok();
+        }
+
+        if let Err(e) = rx
+            .try_iter()
+            .try_for_each(|message| send(&mut socket, &message))
+        {
+            break Err(e);
+        }
+    };
+
+    state.lock().unwrap().disconnect(connection_id);
+    result
+}
+
+fn read_client_message(
+    socket: &mut tungstenite::WebSocket<TcpStream>,
+) -> Result<Incoming, tungstenite::Error> {
+    match socket.read() {
+        Ok(Message::Text(text)) => Ok(match serde_json::from_str(&text) {
+            Ok(message) => Incoming::Message(message),
+            Err(e) => Incoming::Malformed(e),
+        }),
+        Ok(Message::Close(_)) => Ok(Incoming::Closed),
+        Ok(_) => Ok(Incoming::Nothing),
+        Err(tungstenite::Error::Io(e))
+            if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut =>
+        {
+            Ok(Incoming::Nothing)
+        }
+        Err(tungstenite::Error::ConnectionClosed | tungstenite::Error::AlreadyClosed) => {
+            Ok(Incoming::Closed)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn send(
+    socket: &mut tungstenite::WebSocket<TcpStream>,
+    message: &ServerMessage,
+) -> Result<(), tungstenite::Error> {
+    socket.send(Message::Text(serde_json::to_string(message).unwrap()))
+}
+
+/// Takes a name and checks if it's acceptable as a user's name.
+fn validate_name(name: String) -> Result<String, RelayError> {
+    if name.is_empty() {
+        Err(RelayError::EmptyName)
+    } else {
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::mpsc::Receiver;
+
+    fn connect(
+        state: &mut RelayState,
+        credentials: Option<Credentials>,
+    ) -> (ConnectionId, Credentials, Receiver<ServerMessage>) {
+        let (tx, rx) = mpsc::channel();
+        let connection_id = state.connect(credentials, tx).unwrap();
+        let credentials = match rx.recv().unwrap() {
+            ServerMessage::Welcome { credentials } => credentials,
+            other => panic!("Expected a welcome, got {:?}", other),
+        };
+        (connection_id, credentials, rx)
+    }
+
+    fn users_updated(rx: &Receiver<ServerMessage>) -> Vec<User> {
+        rx.try_iter()
+            .map(|message| match message {
+                ServerMessage::UserUpdated { user } => user,
+                other => panic!("Expected a user update, got {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    pub fn new_users_get_an_identity_and_see_everyone() {
+        let mut state = RelayState::load(None).unwrap();
+        let (_, alice, alice_rx) = connect(&mut state, None);
+        let (_, bob, bob_rx) = connect(&mut state, None);
+        assert_ne!(alice.identity, bob.identity);
+
+        let snapshot = users_updated(&bob_rx);
+        assert_eq!(snapshot.len(), 2);
+        assert!(snapshot.iter().all(|user| user.online));
+
+        let alice_updates = users_updated(&alice_rx);
+        assert_eq!(alice_updates.last().unwrap().identity, bob.identity);
+    }
+
+    #[test]
+    pub fn returning_users_must_present_their_token() {
+        let mut state = RelayState::load(None).unwrap();
+        let (connection_id, credentials, _rx) = connect(&mut state, None);
+        state.disconnect(connection_id);
+
+        let stolen = Credentials {
+            token: "guess".to_string(),
+            ..credentials.clone()
+        };
+        let (tx, _rx) = mpsc::channel();
+        assert!(matches!(
+            state.connect(Some(stolen), tx),
+            Err(RelayError::InvalidCredentials)
+        ));
+
+        let (_, returning, _rx) = connect(&mut state, Some(credentials.clone()));
+        assert_eq!(returning, credentials);
+    }
+
+    #[test]
+    pub fn updates_are_broadcast_and_disconnects_go_offline() {
+        let mut state = RelayState::load(None).unwrap();
+        let (alice_connection, alice, _alice_rx) = connect(&mut state, None);
+        let (_, _, bob_rx) = connect(&mut state, None);
+        users_updated(&bob_rx);
+
+        assert!(matches!(
+            state.handle_message(
+                alice_connection,
+                ClientMessage::SetName {
+                    name: String::new()
+                }
+            ),
+            Err(RelayError::EmptyName)
+        ));
+        state
+            .handle_message(
+                alice_connection,
+                ClientMessage::SetName {
+                    name: "Alice".to_string(),
+                },
+            )
+            .unwrap();
+        state
+            .handle_message(
+                alice_connection,
+                ClientMessage::SetStatus {
+                    status: "{}".to_string(),
+                },
+            )
+            .unwrap();
+        state.disconnect(alice_connection);
+
+        let updates = users_updated(&bob_rx);
+        assert_eq!(updates.len(), 3);
+        let last = updates.last().unwrap();
+        assert_eq!(last.identity, alice.identity);
+        assert_eq!(last.name.as_deref(), Some("Alice"));
+        assert_eq!(last.status.as_deref(), Some("{}"));
+        assert!(last.last_status_update.is_some());
+        assert!(!last.online);
+    }
+
+    #[test]
+    pub fn only_identities_and_names_are_saved() {
+        let path = std::env::temp_dir().join(format!("gwaihir-relay-{}.json", Uuid::new_v4()));
+        let mut state = RelayState::load(Some(path.clone())).unwrap();
+        let (connection_id, credentials, _rx) = connect(&mut state, None);
+        state
+            .handle_message(
+                connection_id,
+                ClientMessage::SetName {
+                    name: "Alice".to_string(),
+                },
+            )
+            .unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+
+        state
+            .handle_message(
+                connection_id,
+                ClientMessage::SetStatus {
+                    status: "{}".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(saved, std::fs::read_to_string(&path).unwrap());
+
+        let restarted = RelayState::load(Some(path.clone())).unwrap();
+        std::fs::remove_file(path).unwrap();
+        let user = &restarted.users[&credentials.identity].user;
+        assert_eq!(user.name.as_deref(), Some("Alice"));
+        assert!(user.status.is_none());
+    }
+
+    #[test]
+    pub fn oversized_and_too_frequent_statuses_are_rejected() {
+        let mut state = RelayState::load(None).unwrap();
+        let (connection_id, _, _rx) = connect(&mut state, None);
+        let set_status = |status: String| ClientMessage::SetStatus { status };
+
+        assert!(matches!(
+            state.handle_message(connection_id, set_status("x".repeat(MAX_STATUS_BYTES + 1))),
+            Err(RelayError::StatusTooLong(_))
+        ));
+        for _ in 0..MAX_CALLS_PER_RATE_LIMIT_WINDOW {
+            state
+                .handle_message(connection_id, set_status("{}".to_string()))
+                .unwrap();
+        }
+        assert!(matches!(
+            state.handle_message(connection_id, set_status("{}".to_string())),
+            Err(RelayError::TooManyUpdates)
+        ));
+    }
+}
@@ -0,0 +1,696 @@
+mod protocol;
+
+use std::{
+    collections::HashMap,
+    io::{self, BufReader},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpListener, TcpStream, UdpSocket},
+    sync::{
+        atomic::{self, AtomicBool},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use gwaihir_client_lib::{
+    chrono::Utc,
+    encryption::{decode_payload, encode_payload, SharedEncryption},
+    AcceptsOnlineStatus, NetworkInterface, NetworkInterfaceCreator, NetworkType, RemoteUpdate,
+    UniqueUserId, UserStatus, Username,
+};
+use log::{debug, error, info, warn};
+use protocol::{
+    write_heartbeat, write_message, Announcement, MessageReader, Received, StatusMessage,
+};
+use serde::{Deserialize, Serialize};
+
+/// The UDP port peers announce themselves on, unless configured otherwise
+pub const DEFAULT_DISCOVERY_PORT: u16 = 43616;
+
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(5);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+/// How often connected peers are sent a heartbeat, letting them know we're still there
+#[cfg(not(test))]
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+#[cfg(test)]
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(200);
+/// Peers we haven't heard from in this long are considered gone, e.g. after their laptop went
+/// to sleep without closing the connection
+const PEER_TIMEOUT: Duration = HEARTBEAT_INTERVAL.saturating_mul(3);
+
+/// Handles a status message received from a peer (or published by us),
+/// along with whether that peer is still connected
+type MessageHandler = Arc<Mutex<Box<dyn Fn(&StatusMessage, bool) + Send>>>;
+
+pub struct PeerToPeerInterface {
+    creation_parameters: PeerToPeerCreationParameters,
+    state: Arc<Mutex<SharedState>>,
+    handle_message: MessageHandler,
+    on_disconnect: Box<dyn FnMut() + Send>,
+    is_connected: Arc<AtomicBool>,
+    shutdown: Arc<AtomicBool>,
+    tcp_port: Option<u16>,
+}
+
+pub struct PeerToPeerCreationParameters {
+    /// Must stay the same across restarts so that peers keep recognizing us
+    pub user_id: UniqueUserId,
+    /// Only peers announcing the same group name are connected to
+    pub group_name: String,
+    pub discovery_port: u16,
+    pub encryption: SharedEncryption,
+}
+
+#[derive(Default)]
+struct SharedState {
+    username: String,
+    own_status: Option<StatusMessage>,
+    peers: HashMap<UniqueUserId, Peer>,
+    /// The connection each peer currently sends us their statuses over, so that an older one
+    /// closing after they reconnected doesn't mark them offline
+    incoming: HashMap<UniqueUserId, u64>,
+    next_connection_id: u64,
+    /// Where each peer last announced itself from. Statuses claiming to be from a peer are only
+    /// accepted over connections from that address.
+    announced: HashMap<UniqueUserId, IpAddr>,
+}
+
+/// An outgoing connection we push our own status over
+struct Peer {
+    address: SocketAddr,
+    stream: TcpStream,
+}
+
+impl<T> NetworkInterfaceCreator<T, PeerToPeerInterface, PeerToPeerCreationParameters>
+    for PeerToPeerInterface
+where
+    T: Serialize + for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    fn create(
+        update_callback: impl Fn(RemoteUpdate<T>) + Send + Clone + 'static,
+        on_disconnect_callback: impl FnMut() + Send + 'static,
+        creation_params: PeerToPeerCreationParameters,
+    ) -> Self {
+        let encryption = creation_params.encryption.clone();
+        let handle_message = move |message: &StatusMessage, online: bool| {
+            if let Some(update) = convert_to_remote_update::<T>(message, online, &encryption) {
+                update_callback(update);
+            }
+        };
+        let mut interface = Self {
+            creation_parameters: creation_params,
+            state: Default::default(),
+            handle_message: Arc::new(Mutex::new(Box::new(handle_message))),
+            on_disconnect: Box::new(on_disconnect_callback),
+            is_connected: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(AtomicBool::new(false)),
+            tcp_port: None,
+        };
+        <PeerToPeerInterface as NetworkInterface<T>>::try_reconnect(&mut interface);
+
+        interface
+    }
+}
+
+impl<T> NetworkInterface<T> for PeerToPeerInterface
+where
+    T: Serialize + for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    fn publish_update(&self, sensor_outputs: T) {
+        let json = serde_json::to_string(&sensor_outputs).unwrap();
+        let status = match encode_payload(&self.creation_parameters.encryption, json) {
+            Ok(status) => status,
+            Err(e) => {
+                error!("Failed to encrypt status, not publishing it: {}", e);
+                return;
+            }
+        };
+
+        let mut state = self.state.lock().unwrap();
+        let message = StatusMessage {
+            user_id: self.creation_parameters.user_id.clone(),
+            username: state.username.clone(),
+            last_update: Utc::now(),
+            status,
+        };
+        self.send_own_status(&mut state, message);
+    }
+
+    fn get_current_user_id(&self) -> Option<UniqueUserId> {
+        Some(self.creation_parameters.user_id.clone())
+    }
+
+    fn set_username(&self, name: String) {
+        let mut state = self.state.lock().unwrap();
+        state.username = name.clone();
+        if let Some(own_status) = state.own_status.clone() {
+            let message = StatusMessage {
+                username: name,
+                last_update: Utc::now(),
+                ..own_status
+            };
+            self.send_own_status(&mut state, message);
+        }
+    }
+
+    fn get_network_type(&self) -> NetworkType {
+        NetworkType::PeerToPeer
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected.load(atomic::Ordering::SeqCst)
+    }
+
+    fn try_reconnect(&mut self) -> bool {
+        if self.is_connected.load(atomic::Ordering::SeqCst) {
+            return true;
+        }
+
+        info!(
+            "Starting peer-to-peer networking for group '{}'",
+            self.creation_parameters.group_name
+        );
+        match self.start() {
+            Ok(()) => {
+                self.is_connected.store(true, atomic::Ordering::SeqCst);
+                true
+            }
+            Err(e) => {
+                warn!("Failed to start peer-to-peer networking: {}", e);
+                false
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        info!("Stopping peer-to-peer networking");
+        self.shutdown.store(true, atomic::Ordering::SeqCst);
+        self.is_connected.store(false, atomic::Ordering::SeqCst);
+        // Dropping the streams lets peers know we've gone offline
+        self.state.lock().unwrap().peers.clear();
+        (self.on_disconnect)();
+    }
+}
+
+impl Drop for PeerToPeerInterface {
+    fn drop(&mut self) {
+        self.shutdown.store(true, atomic::Ordering::SeqCst);
+    }
+}
+
+impl PeerToPeerInterface {
+    fn start(&mut self) -> io::Result<()> {
+        // Threads from a previous run hold on to the old flag, so they stay stopped
+        self.shutdown = Arc::new(AtomicBool::new(false));
+
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0))?;
+        listener.set_nonblocking(true)?;
+        let tcp_port = listener.local_addr()?.port();
+
+        let discovery_socket = UdpSocket::bind((
+            Ipv4Addr::UNSPECIFIED,
+            self.creation_parameters.discovery_port,
+        ))?;
+        discovery_socket.set_broadcast(true)?;
+        discovery_socket.set_read_timeout(Some(POLL_INTERVAL))?;
+
+        let state = self.state.clone();
+        let handle_message = self.handle_message.clone();
+        let shutdown = self.shutdown.clone();
+        thread::Builder::new()
+            .name("p2p_listener".to_string())
+            .spawn(move || accept_connections(listener, state, handle_message, shutdown))?;
+
+        let announcement = Announcement::new(
+            self.creation_parameters.group_name.clone(),
+            self.creation_parameters.user_id.clone(),
+            tcp_port,
+        );
+        let discovery_port = self.creation_parameters.discovery_port;
+        let state = self.state.clone();
+        let shutdown = self.shutdown.clone();
+        thread::Builder::new()
+            .name("p2p_discovery".to_string())
+            .spawn(move || {
+                discover_peers(
+                    discovery_socket,
+                    discovery_port,
+                    announcement,
+                    state,
+                    shutdown,
+                )
+            })?;
+
+        self.tcp_port = Some(tcp_port);
+        Ok(())
+    }
+
+    /// Pushes our status to every peer, dropping the ones we can no longer reach
+    fn send_own_status(&self, state: &mut SharedState, message: StatusMessage) {
+        state.peers.retain(
+            |user_id, peer| match write_message(&mut peer.stream, &message) {
+                Ok(()) => true,
+                Err(e) => {
+                    info!("Lost connection to peer {}: {}", user_id, e);
+                    false
+                }
+            },
+        );
+
+        // We are one of the users in the group as well
+        (self.handle_message.lock().unwrap())(&message, true);
+        state.own_status = Some(message);
+    }
+}
+
+fn accept_connections(
+    listener: TcpListener,
+    state: Arc<Mutex<SharedState>>,
+    handle_message: MessageHandler,
+    shutdown: Arc<AtomicBool>,
+) {
+    while !shutdown.load(atomic::Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, address)) => {
+                debug!("Accepted connection from {}", address);
+                let state = state.clone();
+                let handle_message = handle_message.clone();
+                let shutdown = shutdown.clone();
+                thread::spawn(move || receive_statuses(stream, state, handle_message, shutdown));
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                warn!("Failed to accept peer connection: {}", e);
+                thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+fn receive_statuses(
+    stream: TcpStream,
+    state: Arc<Mutex<SharedState>>,
+    handle_message: MessageHandler,
+    shutdown: Arc<AtomicBool>,
+) {
+    if let Err(e) = stream
+        .set_nonblocking(false)
+        .and_then(|_| stream.set_read_timeout(Some(POLL_INTERVAL)))
+    {
+        warn!("Failed to configure peer connection: {}", e);
+        return;
+    }
+
+    let peer_ip = match stream.peer_addr() {
+        Ok(address) => address.ip(),
+        Err(e) => {
+            warn!("Failed to get the address of a peer connection: {}", e);
+            return;
+        }
+    };
+    let mut reader = MessageReader::new(BufReader::new(stream));
+    let mut last_message = None;
+    let mut connection = None;
+    let mut last_received = Instant::now();
+    while !shutdown.load(atomic::Ordering::SeqCst) {
+        match reader.read_message() {
+            Ok(Some(received)) => {
+                last_received = Instant::now();
+                if let Received::Status(message) = received {
+                    // The first status decides who the connection belongs to
+                    let connected_for = match connection.as_ref() {
+                        Some((user_id, _)) => user_id,
+                        None if was_announced_from(&state, &message.user_id, peer_ip) => {
+                            &connection
+                                .insert(claim_connection(&state, &message.user_id))
+                                .0
+                        }
+                        None => {
+                            warn!(
+                                "Ignoring status for {} from {}, which it wasn't announced from",
+                                message.user_id, peer_ip
+                            );
+                            continue;
+                        }
+                    };
+                    if *connected_for != message.user_id {
+                        warn!(
+                            "Ignoring status for {} over {}'s connection",
+                            message.user_id, connected_for
+                        );
+                        continue;
+                    }
+                    (handle_message.lock().unwrap())(&message, true);
+                    last_message = Some(message);
+                }
+            }
+            Ok(None) => break,
+            Err(e)
+                if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut =>
+            {
+                if last_received.elapsed() > PEER_TIMEOUT {
+                    info!("Dropping peer connection after not hearing from it in a while");
+                    break;
+                }
+            }
+            Err(e) => {
+                warn!("Dropping peer connection after error: {}", e);
+                break;
+            }
+        }
+    }
+
+    let still_current = connection.is_some_and(|(user_id, id)| {
+        let mut state = state.lock().unwrap();
+        let still_current = state.incoming.get(&user_id) == Some(&id);
+        if still_current {
+            state.incoming.remove(&user_id);
+        }
+        still_current
+    });
+    if let Some(message) = last_message.filter(|_| still_current) {
+        (handle_message.lock().unwrap())(&message, false);
+    }
+}
+
+fn was_announced_from(state: &Mutex<SharedState>, user_id: &UniqueUserId, ip: IpAddr) -> bool {
+    state.lock().unwrap().announced.get(user_id) == Some(&ip)
+}
+
+/// Makes this the connection `user_id` sends us their statuses over
+///
+/// *Returns:* The user and an ID for the connection, to check whether it's still theirs later
+fn claim_connection(state: &Mutex<SharedState>, user_id: &UniqueUserId) -> (UniqueUserId, u64) {
+    let mut state = state.lock().unwrap();
+    let id = state.next_connection_id;
+    state.next_connection_id += 1;
+    state.incoming.insert(user_id.clone(), id);
+    (user_id.clone(), id)
+}
+
+fn discover_peers(
+    socket: UdpSocket,
+    discovery_port: u16,
+    announcement: Announcement,
+    state: Arc<Mutex<SharedState>>,
+    shutdown: Arc<AtomicBool>,
+) {
+    let announcement_bytes = serde_json::to_vec(&announcement).unwrap();
+    let mut last_announcement: Option<Instant> = None;
+    let mut last_heartbeat = Instant::now();
+    let mut buf = [0u8; 1024];
+    while !shutdown.load(atomic::Ordering::SeqCst) {
+        if last_heartbeat.elapsed() >= HEARTBEAT_INTERVAL {
+            send_heartbeats(&state);
+            last_heartbeat = Instant::now();
+        }
+
+        let announcement_due = match last_announcement {
+            Some(t) => t.elapsed() >= ANNOUNCE_INTERVAL,
+            None => true,
+        };
+        if announcement_due {
+            if let Err(e) =
+                socket.send_to(&announcement_bytes, (Ipv4Addr::BROADCAST, discovery_port))
+            {
+                warn!("Failed to announce ourselves to peers: {}", e);
+            }
+            last_announcement = Some(Instant::now());
+        }
+
+        match socket.recv_from(&mut buf) {
+            Ok((len, source)) => match serde_json::from_slice::<Announcement>(&buf[..len]) {
+                Ok(peer) if peer.is_from_peer(&announcement.group_name, &announcement.user_id) => {
+                    state
+                        .lock()
+                        .unwrap()
+                        .announced
+                        .insert(peer.user_id.clone(), source.ip());
+                    let address = SocketAddr::new(source.ip(), peer.tcp_port);
+                    connect_to_peer(&state, peer.user_id, address);
+                }
+                Ok(_) => (),
+                Err(e) => debug!("Ignoring malformed announcement from {}: {}", source, e),
+            },
+            Err(e)
+                if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+            }
+            Err(e) => warn!("Failed to receive peer announcements: {}", e),
+        }
+    }
+}
+
+fn send_heartbeats(state: &Mutex<SharedState>) {
+    state
+        .lock()
+        .unwrap()
+        .peers
+        .retain(|user_id, peer| match write_heartbeat(&mut peer.stream) {
+            Ok(()) => true,
+            Err(e) => {
+                info!("Lost connection to peer {}: {}", user_id, e);
+                false
+            }
+        });
+}
+
+fn connect_to_peer(state: &Mutex<SharedState>, user_id: UniqueUserId, address: SocketAddr) {
+    // A peer that restarted listens on a new port, so the address tells us whether to reconnect
+    if state
+        .lock()
+        .unwrap()
+        .peers
+        .get(&user_id)
+        .is_some_and(|peer| peer.address == address)
+    {
+        return;
+    }
+
+    let mut stream = match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+        Ok(stream) => stream,
+        Err(e) => {
+            debug!(
+                "Failed to connect to peer {} at {}: {}",
+                user_id, address, e
+            );
+            return;
+        }
+    };
+
+    let mut state = state.lock().unwrap();
+    if let Some(own_status) = state.own_status.as_ref() {
+        if let Err(e) = write_message(&mut stream, own_status) {
+            debug!("Failed to send status to peer {}: {}", user_id, e);
+            return;
+        }
+    }
+
+    info!("Connected to peer {} at {}", user_id, address);
+    state.peers.insert(user_id, Peer { address, stream });
+}
+
+fn convert_to_remote_update<T>(
+    message: &StatusMessage,
+    online: bool,
+    encryption: &SharedEncryption,
+) -> Option<RemoteUpdate<T>>
+where
+    T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    let status = match decode_payload(encryption, &message.status) {
+        Ok(status) => status,
+        Err(e) => {
+            // Peers that haven't shared their data with us should simply not show up
+            debug!(
+                "Unable to read status of user ({}, {}): {}",
+                message.username, message.user_id, e
+            );
+            return None;
+        }
+    };
+
+    match serde_json::from_str::<T>(&status) {
+        Ok(mut sensor_outputs) => {
+            sensor_outputs.set_online_status(online);
+            Some(RemoteUpdate::UserStatusUpdated(UserStatus {
+                user_id: message.user_id.clone(),
+                username: Username::new(message.username.clone()),
+                last_update: message.last_update,
+                sensor_outputs,
+            }))
+        }
+        Err(e) => {
+            error!(
+                "Failed to deserialize sensor data for user ({}, {}): {}",
+                message.username, message.user_id, e
+            );
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::mpsc::{self, Receiver};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct TestStatus {
+        text: String,
+        #[serde(skip)]
+        online: bool,
+    }
+
+    impl AcceptsOnlineStatus for TestStatus {
+        fn set_online_status(&mut self, online: bool) {
+            self.online = online;
+        }
+    }
+
+    fn create_peer(user_id: &str) -> (PeerToPeerInterface, Receiver<RemoteUpdate<TestStatus>>) {
+        let (tx, rx) = mpsc::channel();
+        let interface = PeerToPeerInterface::create(
+            move |update| tx.send(update).unwrap(),
+            || (),
+            PeerToPeerCreationParameters {
+                user_id: UniqueUserId::new(user_id.to_string()),
+                group_name: "test".to_string(),
+                // Let the OS pick a port so tests don't rely on broadcasts reaching us
+                discovery_port: 0,
+                encryption: Default::default(),
+            },
+        );
+        (interface, rx)
+    }
+
+    /// Stands in for `user_id` announcing itself to `peer` from this machine
+    fn announce(peer: &PeerToPeerInterface, user_id: &str) {
+        peer.state.lock().unwrap().announced.insert(
+            UniqueUserId::new(user_id.to_string()),
+            Ipv4Addr::LOCALHOST.into(),
+        );
+    }
+
+    fn next_status(rx: &Receiver<RemoteUpdate<TestStatus>>) -> UserStatus<TestStatus> {
+        match rx.recv_timeout(Duration::from_secs(5)).unwrap() {
+            RemoteUpdate::UserStatusUpdated(status) => status,
+            _ => unreachable!("peers only send their current status"),
+        }
+    }
+
+    #[test]
+    pub fn statuses_reach_connected_peers_until_they_disconnect() {
+        let (mut alice, alice_rx) = create_peer("alice");
+        let (bob, bob_rx) = create_peer("bob");
+        <PeerToPeerInterface as NetworkInterface<TestStatus>>::set_username(
+            &alice,
+            "Alice".to_string(),
+        );
+        alice.publish_update(TestStatus {
+            text: "hello".to_string(),
+            online: true,
+        });
+        assert_eq!(next_status(&alice_rx).sensor_outputs.text, "hello");
+
+        let bob_address = SocketAddr::from((Ipv4Addr::LOCALHOST, bob.tcp_port.unwrap()));
+        announce(&bob, "alice");
+        connect_to_peer(
+            &alice.state,
+            UniqueUserId::new("bob".to_string()),
+            bob_address,
+        );
+
+        let received = next_status(&bob_rx);
+        assert_eq!(received.display_name(), "Alice");
+        assert_eq!(
+            received.sensor_outputs,
+            TestStatus {
+                text: "hello".to_string(),
+                online: true
+            }
+        );
+
+        <PeerToPeerInterface as NetworkInterface<TestStatus>>::disconnect(&mut alice);
+        let received = next_status(&bob_rx);
+        assert_eq!(received.user_id, UniqueUserId::new("alice".to_string()));
+        assert!(!received.sensor_outputs.online);
+    }
+
+    fn status_from(user_id: &str, text: &str) -> StatusMessage {
+        let status = serde_json::to_string(&TestStatus {
+            text: text.to_string(),
+            online: true,
+        })
+        .unwrap();
+        StatusMessage {
+            user_id: UniqueUserId::new(user_id.to_string()),
+            username: user_id.to_string(),
+            last_update: Utc::now(),
+            status,
+        }
+    }
+
+    #[test]
+    pub fn peers_that_go_silent_are_marked_offline() {
+        let (bob, bob_rx) = create_peer("bob");
+        let bob_address = SocketAddr::from((Ipv4Addr::LOCALHOST, bob.tcp_port.unwrap()));
+        announce(&bob, "carol");
+
+        // Like a laptop going to sleep: a status, then nothing, without closing the connection
+        let mut silent_peer = TcpStream::connect(bob_address).unwrap();
+        write_message(&mut silent_peer, &status_from("carol", "sleepy")).unwrap();
+
+        assert!(next_status(&bob_rx).sensor_outputs.online);
+        let received = next_status(&bob_rx);
+        assert_eq!(received.sensor_outputs.text, "sleepy");
+        assert!(!received.sensor_outputs.online);
+    }
+
+    #[test]
+    pub fn old_connections_timing_out_after_reconnecting_keep_peers_online() {
+        let (bob, bob_rx) = create_peer("bob");
+        let bob_address = SocketAddr::from((Ipv4Addr::LOCALHOST, bob.tcp_port.unwrap()));
+        announce(&bob, "carol");
+
+        let mut old_connection = TcpStream::connect(bob_address).unwrap();
+        write_message(&mut old_connection, &status_from("carol", "before")).unwrap();
+        assert_eq!(next_status(&bob_rx).sensor_outputs.text, "before");
+        let mut new_connection = TcpStream::connect(bob_address).unwrap();
+        write_message(&mut new_connection, &status_from("carol", "after")).unwrap();
+        assert_eq!(next_status(&bob_rx).sensor_outputs.text, "after");
+
+        // Only the new connection keeps up its heartbeats
+        let deadline = Instant::now() + PEER_TIMEOUT * 2;
+        while Instant::now() < deadline {
+            write_heartbeat(&mut new_connection).unwrap();
+            thread::sleep(HEARTBEAT_INTERVAL);
+        }
+        assert!(bob_rx.try_recv().is_err());
+
+        drop(new_connection);
+        let received = next_status(&bob_rx);
+        assert_eq!(received.sensor_outputs.text, "after");
+        assert!(!received.sensor_outputs.online);
+        drop(old_connection);
+    }
+
+    #[test]
+    pub fn statuses_are_only_accepted_for_the_peer_a_connection_belongs_to() {
+        let (bob, bob_rx) = create_peer("bob");
+        let bob_address = SocketAddr::from((Ipv4Addr::LOCALHOST, bob.tcp_port.unwrap()));
+        announce(&bob, "carol");
+        let mut connection = TcpStream::connect(bob_address).unwrap();
+
+        write_message(&mut connection, &status_from("mallory", "unannounced")).unwrap();
+        write_message(&mut connection, &status_from("carol", "real")).unwrap();
+        announce(&bob, "dave");
+        write_message(&mut connection, &status_from("dave", "impersonated")).unwrap();
+        write_message(&mut connection, &status_from("carol", "still real")).unwrap();
+
+        assert_eq!(next_status(&bob_rx).sensor_outputs.text, "real");
+        let received = next_status(&bob_rx);
+        assert_eq!(received.user_id, UniqueUserId::new("carol".to_string()));
+        assert_eq!(received.sensor_outputs.text, "still real");
+    }
+}
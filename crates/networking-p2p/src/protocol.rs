@@ -0,0 +1,172 @@
+use gwaihir_client_lib::{
+    chrono::{DateTime, Utc},
+    UniqueUserId, APP_ID,
+};
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Read, Write};
+
+/// Statuses are capped at 64 KiB like the SpacetimeDB module does, leaving room for the rest of
+/// the message around them
+const MAX_LINE_BYTES: usize = 64 * 1024 + 4 * 1024;
+
+/// Broadcast over UDP so that peers on the same LAN can find each other
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct Announcement {
+    pub app_id: String,
+    pub group_name: String,
+    pub user_id: UniqueUserId,
+    pub tcp_port: u16,
+}
+
+impl Announcement {
+    pub fn new(group_name: String, user_id: UniqueUserId, tcp_port: u16) -> Self {
+        Self {
+            app_id: APP_ID.to_string(),
+            group_name,
+            user_id,
+            tcp_port,
+        }
+    }
+
+    /// Whether this announcement came from another member of our group
+    pub fn is_from_peer(&self, group_name: &str, own_user_id: &UniqueUserId) -> bool {
+        self.app_id == APP_ID && self.group_name == group_name && &self.user_id != own_user_id
+    }
+}
+
+/// Sent to every known peer over TCP, one JSON object per line
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StatusMessage {
+    pub user_id: UniqueUserId,
+    pub username: String,
+    pub last_update: DateTime<Utc>,
+    /// Serialized sensor outputs, possibly encrypted
+    pub status: String,
+}
+
+/// What a peer can send us over TCP
+#[derive(Debug, PartialEq)]
+pub enum Received {
+    Status(StatusMessage),
+    /// An empty line, sent regularly so we notice peers that vanished without closing the
+    /// connection
+    Heartbeat,
+}
+
+pub fn write_message(writer: &mut impl Write, message: &StatusMessage) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    writer.write_all(line.as_bytes())?;
+    writer.flush()
+}
+
+pub fn write_heartbeat(writer: &mut impl Write) -> io::Result<()> {
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+/// Reads newline-delimited status messages, keeping partially received lines
+/// around when the underlying stream times out. Lines longer than `MAX_LINE_BYTES` are an error,
+/// so a peer can't make us buffer forever.
+pub struct MessageReader<R> {
+    reader: R,
+    pending: Vec<u8>,
+}
+
+impl<R: BufRead> MessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Returns `Ok(None)` once the peer has closed the connection
+    pub fn read_message(&mut self) -> io::Result<Option<Received>> {
+        let limit = (MAX_LINE_BYTES - self.pending.len()) as u64;
+        if (&mut self.reader)
+            .take(limit)
+            .read_until(b'\n', &mut self.pending)?
+            == 0
+        {
+            return Ok(None);
+        }
+        if self.pending.len() >= MAX_LINE_BYTES && self.pending.last() != Some(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Message longer than {} bytes", MAX_LINE_BYTES),
+            ));
+        }
+
+        let received = if self.pending.iter().all(u8::is_ascii_whitespace) {
+            Ok(Received::Heartbeat)
+        } else {
+            serde_json::from_slice(&self.pending).map(Received::Status)
+        };
+        self.pending.clear();
+        Ok(Some(received?))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    pub fn status_messages_round_trip_through_a_stream() {
+        let first = StatusMessage {
+            user_id: UniqueUserId::new("a".to_string()),
+            username: "Alice".to_string(),
+            last_update: Utc::now(),
+            status: "{\"some\":\"json\"}".to_string(),
+        };
+        let second = StatusMessage {
+            username: "Alice B.".to_string(),
+            ..first.clone()
+        };
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &first).unwrap();
+        write_heartbeat(&mut buffer).unwrap();
+        write_message(&mut buffer, &second).unwrap();
+
+        let mut reader = MessageReader::new(BufReader::new(buffer.as_slice()));
+        assert_eq!(
+            reader.read_message().unwrap(),
+            Some(Received::Status(first))
+        );
+        assert_eq!(reader.read_message().unwrap(), Some(Received::Heartbeat));
+        assert_eq!(
+            reader.read_message().unwrap(),
+            Some(Received::Status(second))
+        );
+        assert_eq!(reader.read_message().unwrap(), None);
+    }
+
+    #[test]
+    pub fn overly_long_lines_are_an_error() {
+        let line = vec![b'x'; MAX_LINE_BYTES + 1];
+
+        let mut reader = MessageReader::new(BufReader::new(line.as_slice()));
+
+        assert_eq!(
+            reader.read_message().unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+
+    #[test]
+    pub fn announcements_from_ourselves_or_other_groups_are_ignored() {
+        let own_id = UniqueUserId::new("me".to_string());
+        let announcement = Announcement::new(
+            "office".to_string(),
+            UniqueUserId::new("you".to_string()),
+            1234,
+        );
+        assert!(announcement.is_from_peer("office", &own_id));
+        assert!(!announcement.is_from_peer("home", &own_id));
+
+        let own_announcement = Announcement::new("office".to_string(), own_id.clone(), 1234);
+        assert!(!own_announcement.is_from_peer("office", &own_id));
+    }
+}
@@ -0,0 +1,427 @@
+// tungstenite::Error is large, but it's only ever returned once per connection
+#![allow(clippy::result_large_err)]
+
+use std::{
+    collections::HashMap,
+    io,
+    net::TcpStream,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{self, AtomicBool},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use gwaihir_client_lib::{
+    encryption::{decode_payload, encode_payload, SharedEncryption},
+    AcceptsOnlineStatus, NetworkInterface, NetworkInterfaceCreator, NetworkType, RemoteUpdate,
+    UniqueUserId, UserStatus, Username, APP_ID,
+};
+use gwaihir_relay::protocol::{ClientMessage, Credentials, ServerMessage, User};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use tungstenite::{stream::MaybeTlsStream, Message, WebSocket};
+
+pub use gwaihir_relay::protocol::DEFAULT_PORT;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const CREDENTIALS_FILE_NAME: &str = "relay_credentials.json";
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+type UserHandler = Arc<Mutex<Box<dyn Fn(&User) + Send>>>;
+
+pub struct RelayInterface {
+    is_connected: Arc<AtomicBool>,
+    creation_parameters: RelayCreationParameters,
+    identity: Arc<Mutex<Option<String>>>,
+    outgoing: Option<Sender<Outgoing>>,
+    handle_user: UserHandler,
+    on_disconnect: Arc<Mutex<Box<dyn FnMut() + Send>>>,
+}
+
+pub struct RelayCreationParameters {
+    /// e.g. `ws://192.168.1.10:43617`
+    pub uri: String,
+    /// Where the identity handed out by each relay is remembered
+    pub credentials_dir: PathBuf,
+    pub encryption: SharedEncryption,
+}
+
+enum Outgoing {
+    Message(ClientMessage),
+    Close,
+}
+
+impl<T> NetworkInterfaceCreator<T, RelayInterface, RelayCreationParameters> for RelayInterface
+where
+    T: Serialize + for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    fn create(
+        update_callback: impl Fn(RemoteUpdate<T>) + Send + Clone + 'static,
+        on_disconnect_callback: impl FnMut() + Send + 'static,
+        creation_params: RelayCreationParameters,
+    ) -> Self {
+        let encryption = creation_params.encryption.clone();
+        let handle_user = move |user: &User| {
+            if let Some(update) = convert_to_remote_update::<T>(user, &encryption) {
+                update_callback(update);
+            }
+        };
+        let mut interface = Self {
+            is_connected: Arc::new(AtomicBool::new(false)),
+            creation_parameters: creation_params,
+            identity: Default::default(),
+            outgoing: None,
+            handle_user: Arc::new(Mutex::new(Box::new(handle_user))),
+            on_disconnect: Arc::new(Mutex::new(Box::new(on_disconnect_callback))),
+        };
+        <RelayInterface as NetworkInterface<T>>::try_reconnect(&mut interface);
+
+        interface
+    }
+}
+
+impl<T> NetworkInterface<T> for RelayInterface
+where
+    T: Serialize + for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    fn publish_update(&self, sensor_outputs: T) {
+        let json = serde_json::to_string(&sensor_outputs).unwrap();
+        match encode_payload(&self.creation_parameters.encryption, json) {
+            Ok(status) => self.send(ClientMessage::SetStatus { status }),
+            Err(e) => error!("Failed to encrypt status, not publishing it: {}", e),
+        }
+    }
+
+    fn get_current_user_id(&self) -> Option<UniqueUserId> {
+        Some(short_identity(self.identity.lock().unwrap().as_ref()?))
+    }
+
+    fn set_username(&self, name: String) {
+        self.send(ClientMessage::SetName { name })
+    }
+
+    fn get_network_type(&self) -> NetworkType {
+        NetworkType::Relay
+    }
+
+    fn is_connected(&self) -> bool {
+        self.is_connected.load(atomic::Ordering::SeqCst)
+    }
+
+    fn try_reconnect(&mut self) -> bool {
+        info!(
+            "Attempting to connect to relay at {}",
+            self.creation_parameters.uri
+        );
+        match self.connect() {
+            Ok(()) => {
+                info!("Successfully connected to relay");
+                true
+            }
+            Err(e) => {
+                warn!("Failed to connect to relay: {}", e);
+                false
+            }
+        }
+    }
+
+    fn disconnect(&mut self) {
+        info!("Disconnecting from relay");
+        if let Some(outgoing) = self.outgoing.take() {
+            outgoing.send(Outgoing::Close).ok();
+        }
+    }
+}
+
+impl RelayInterface {
+    fn connect(&mut self) -> Result<(), tungstenite::Error> {
+        let (mut socket, _) = tungstenite::connect(&self.creation_parameters.uri)?;
+        let credentials = load_credentials(
+            &self.creation_parameters.credentials_dir,
+            &self.creation_parameters.uri,
+        );
+        send(&mut socket, &ClientMessage::Hello { credentials })?;
+
+        let credentials = match read_server_message(&mut socket)? {
+            Some(ServerMessage::Welcome { credentials }) => credentials,
+            Some(ServerMessage::Error { message }) => {
+                return Err(io::Error::new(io::ErrorKind::PermissionDenied, message).into())
+            }
+            _ => return Err(io::Error::from(io::ErrorKind::InvalidData).into()),
+        };
+        if let Err(e) = save_credentials(
+            &self.creation_parameters.credentials_dir,
+            &self.creation_parameters.uri,
+            &credentials,
+        ) {
+            error!("Failed to save relay credentials: {:?}", e);
+        }
+        *self.identity.lock().unwrap() = Some(credentials.identity);
+
+        // Reading with a timeout lets the connection thread also send our own messages
+        if let MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            stream.set_read_timeout(Some(POLL_INTERVAL))?;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.outgoing = Some(tx);
+        self.is_connected.store(true, atomic::Ordering::SeqCst);
+        let handle_user = self.handle_user.clone();
+        let is_connected = self.is_connected.clone();
+        let on_disconnect = self.on_disconnect.clone();
+        thread::Builder::new()
+            .name("relay_connection".to_string())
+            .spawn(move || {
+                run_connection(socket, rx, handle_user);
+                info!("Disconnected from relay!");
+                is_connected.store(false, atomic::Ordering::SeqCst);
+                (on_disconnect.lock().unwrap())();
+            })?;
+
+        Ok(())
+    }
+
+    fn send(&self, message: ClientMessage) {
+        match self.outgoing.as_ref() {
+            Some(outgoing) if self.is_connected.load(atomic::Ordering::SeqCst) => {
+                outgoing.send(Outgoing::Message(message)).ok();
+            }
+            _ => debug!("Not connected to the relay, dropping {:?}", message),
+        }
+    }
+}
+
+fn run_connection(mut socket: Socket, rx: Receiver<Outgoing>, handle_user: UserHandler) {
+    loop {
+        match read_server_message(&mut socket) {
+            Ok(Some(ServerMessage::UserUpdated { user })) => (handle_user.lock().unwrap())(&user),
+            Ok(Some(ServerMessage::Error { message })) => {
+                warn!("Relay rejected a message: {}", message)
+            }
+            Ok(Some(ServerMessage::Welcome { .. })) | Ok(None) => (),
+            Err(tungstenite::Error::Io(e))
+                if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
+            }
+            Err(tungstenite::Error::ConnectionClosed) => return,
+            Err(e) => {
+                warn!("Relay connection failed: {}", e);
+                return;
+            }
+        }
+
+        loop {
+            let result = match rx.try_recv() {
+                Ok(Outgoing::Message(message)) => send(&mut socket, &message),
+                Ok(Outgoing::Close) | Err(TryRecvError::Disconnected) => {
+                    socket.close(None).ok();
+                    socket.flush().ok();
+                    return;
+                }
+                Err(TryRecvError::Empty) => break,
+            };
+            if let Err(e) = result {
+                warn!("Failed to send message to relay: {}", e);
+                return;
+            }
+        }
+    }
+}
+
+/// Returns `Ok(None)` for anything other than a server message, e.g. pings
+fn read_server_message(socket: &mut Socket) -> Result<Option<ServerMessage>, tungstenite::Error> {
+    match socket.read()? {
+        Message::Text(text) => match serde_json::from_str(&text) {
+            Ok(message) => Ok(Some(message)),
+            Err(e) => {
+                warn!("Ignoring malformed message from relay: {}", e);
+                Ok(None)
+            }
+        },
+        Message::Close(_) => Err(tungstenite::Error::ConnectionClosed),
+        _ => Ok(None),
+    }
+}
+
+fn send(socket: &mut Socket, message: &ClientMessage) -> Result<(), tungstenite::Error> {
+    socket.send(Message::Text(serde_json::to_string(message).unwrap()))
+}
+
+fn short_identity(identity: &str) -> UniqueUserId {
+    UniqueUserId::new(identity.chars().take(16).collect::<String>())
+}
+
+fn convert_to_remote_update<T>(
+    user: &User,
+    encryption: &SharedEncryption,
+) -> Option<RemoteUpdate<T>>
+where
+    T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    let (payload, last_update) = (user.status.as_ref()?, user.last_status_update?);
+    let status = match decode_payload(encryption, payload) {
+        Ok(status) => status,
+        Err(e) => {
+            // Users that haven't shared their data with us should simply not show up
+            debug!(
+                "Unable to read status of user ({:?}, {}): {}",
+                user.name, user.identity, e
+            );
+            return None;
+        }
+    };
+
+    match serde_json::from_str::<T>(&status) {
+        Ok(mut sensor_outputs) => {
+            sensor_outputs.set_online_status(user.online);
+            Some(RemoteUpdate::UserStatusUpdated(UserStatus {
+                user_id: short_identity(&user.identity),
+                username: Username::new(user.name.clone().unwrap_or_default()),
+                last_update,
+                sensor_outputs,
+            }))
+        }
+        Err(e) => {
+            error!(
+                "Failed to deserialize sensor data for user ({:?}, {}): {}",
+                user.name, user.identity, e
+            );
+            None
+        }
+    }
+}
+
+pub fn default_credentials_dir() -> PathBuf {
+    PathBuf::from(format!(".{}", APP_ID))
+}
+
+fn load_credentials(dir: &Path, uri: &str) -> Option<Credentials> {
+    let json = std::fs::read_to_string(dir.join(CREDENTIALS_FILE_NAME)).ok()?;
+    let mut credentials: HashMap<String, Credentials> = serde_json::from_str(&json).ok()?;
+    credentials.remove(uri)
+}
+
+fn save_credentials(
+    dir: &Path,
+    uri: &str,
+    new_credentials: &Credentials,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = dir.join(CREDENTIALS_FILE_NAME);
+    let mut credentials: HashMap<String, Credentials> = std::fs::read_to_string(&path)
+        .ok()
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default();
+    credentials.insert(uri.to_string(), new_credentials.clone());
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(path, serde_json::to_string(&credentials)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use gwaihir_relay::server::RelayServer;
+    use std::net::TcpListener;
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct TestStatus {
+        text: String,
+        #[serde(skip)]
+        online: bool,
+    }
+
+    impl AcceptsOnlineStatus for TestStatus {
+        fn set_online_status(&mut self, online: bool) {
+            self.online = online;
+        }
+    }
+
+    fn start_relay() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let uri = format!("ws://{}", listener.local_addr().unwrap());
+        thread::spawn(move || RelayServer::new(None).unwrap().serve(listener));
+        uri
+    }
+
+    fn create_client(
+        uri: &str,
+        name: &str,
+    ) -> (RelayInterface, Receiver<RemoteUpdate<TestStatus>>) {
+        let (tx, rx) = mpsc::channel();
+        let credentials_dir = std::env::temp_dir().join(format!(
+            "gwaihir-relay-test-{}-{}",
+            std::process::id(),
+            name
+        ));
+        std::fs::remove_dir_all(&credentials_dir).ok();
+        let interface = RelayInterface::create(
+            move |update| tx.send(update).unwrap(),
+            || (),
+            RelayCreationParameters {
+                uri: uri.to_string(),
+                credentials_dir,
+                encryption: Default::default(),
+            },
+        );
+        (interface, rx)
+    }
+
+    fn wait_for_status(
+        rx: &Receiver<RemoteUpdate<TestStatus>>,
+        matches: impl Fn(&UserStatus<TestStatus>) -> bool,
+    ) -> UserStatus<TestStatus> {
+        loop {
+            let RemoteUpdate::UserStatusUpdated(status) =
+                rx.recv_timeout(Duration::from_secs(5)).unwrap()
+            else {
+                continue;
+            };
+            if matches(&status) {
+                return status;
+            }
+        }
+    }
+
+    #[test]
+    pub fn statuses_are_relayed_between_clients() {
+        let uri = start_relay();
+        let (mut alice, _alice_rx) = create_client(&uri, "alice");
+        let (bob, bob_rx) = create_client(&uri, "bob");
+        assert!(<RelayInterface as NetworkInterface<TestStatus>>::is_connected(&bob));
+        let alice_id =
+            <RelayInterface as NetworkInterface<TestStatus>>::get_current_user_id(&alice).unwrap();
+
+        <RelayInterface as NetworkInterface<TestStatus>>::set_username(&alice, "Alice".to_string());
+        alice.publish_update(TestStatus {
+            text: "hello".to_string(),
+            online: true,
+        });
+        let received = wait_for_status(&bob_rx, |status| status.user_id == alice_id);
+        assert_eq!(received.display_name(), "Alice");
+        assert_eq!(received.sensor_outputs.text, "hello");
+        assert!(received.sensor_outputs.online);
+
+        <RelayInterface as NetworkInterface<TestStatus>>::disconnect(&mut alice);
+        wait_for_status(&bob_rx, |status| {
+            status.user_id == alice_id && !status.sensor_outputs.online
+        });
+    }
+
+    #[test]
+    pub fn identities_are_kept_across_reconnects() {
+        let uri = start_relay();
+        let (mut client, _rx) = create_client(&uri, "returning");
+        let first_id =
+            <RelayInterface as NetworkInterface<TestStatus>>::get_current_user_id(&client);
+        <RelayInterface as NetworkInterface<TestStatus>>::disconnect(&mut client);
+
+        assert!(client.connect().is_ok());
+        assert_eq!(
+            <RelayInterface as NetworkInterface<TestStatus>>::get_current_user_id(&client),
+            first_id
+        );
+    }
+}
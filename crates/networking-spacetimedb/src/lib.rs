@@ -1,16 +1,22 @@
+// Not every table and reducer in the module is used by the client
+#[allow(unused_imports)]
 mod module_bindings;
+mod status_payload;
 
 use std::sync::{
     atomic::{self, AtomicBool},
-    Arc,
+    Arc, Mutex,
 };
 
 use gwaihir_client_lib::{
-    chrono::{NaiveDateTime, TimeZone, Utc},
-    AcceptsOnlineStatus, NetworkInterface, NetworkInterfaceCreator, RemoteUpdate, UniqueUserId,
-    UserStatus, Username, APP_ID,
+    chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc},
+    encryption::{
+        decode_payload, encode_payload, encode_payload_for_user, EncryptionError, SharedEncryption,
+    },
+    AcceptsOnlineStatus, FriendRequest, FriendshipAction, Friendships, NetworkInterface,
+    NetworkInterfaceCreator, RemoteUpdate, UniqueUserId, UserStatus, Username, APP_ID,
 };
-use log::{error, info, warn};
+use log::{debug, error, info, warn};
 use module_bindings::*;
 use serde::{Deserialize, Serialize};
 use spacetimedb_sdk::{
@@ -18,21 +24,48 @@ use spacetimedb_sdk::{
     identity::{
         identity, load_credentials, once_on_connect, save_credentials, Credentials, Identity,
     },
-    on_disconnect, subscribe,
+    on_disconnect,
+    reducer::Status,
+    subscribe_owned,
     table::{TableType, TableWithPrimaryKey},
     Address,
 };
 
-/// The URL of the SpacetimeDB instance hosting our chat module.
-const SPACETIMEDB_URI: &str = "https://testnet.spacetimedb.com";
+/// The URL of the SpacetimeDB instance hosting our module, unless configured otherwise.
+pub const DEFAULT_SPACETIMEDB_URI: &str = "https://testnet.spacetimedb.com";
+
+/// How far back the statuses our friends set while we were away are fetched on connecting,
+/// unless configured otherwise
+pub const DEFAULT_STATUS_BACKFILL_MINUTES: u32 = 60;
 
 pub struct SpacetimeDBInterface {
     is_connected: Arc<AtomicBool>,
+    subscriptions: Arc<Mutex<Subscriptions>>,
+    /// Saves clearing recipient statuses over and over when there aren't any
+    has_recipient_statuses: AtomicBool,
     creation_parameters: SpacetimeDBCreationParameters,
 }
 
 pub struct SpacetimeDBCreationParameters {
+    pub host_uri: String,
     pub db_name: String,
+    /// Where the credentials handed out by SpacetimeDB are stored
+    pub creds_dir: String,
+    pub encryption: SharedEncryption,
+    /// How far back the statuses our friends set while we were away are fetched on connecting.
+    /// The server may not keep them for this long, and statuses shared with us in particular
+    /// aren't kept at all.
+    pub status_backfill_minutes: u32,
+}
+
+/// What we're subscribed to, which changes as we learn who we are and who our friends are
+struct Subscriptions {
+    status_backfill: Duration,
+    /// The status history is only fetched up to when we connected, so statuses set later aren't
+    /// received twice
+    connected_at: DateTime<Utc>,
+    own_identity: Option<Identity>,
+    friends: Vec<Identity>,
 }
 
 impl<T> NetworkInterfaceCreator<T, SpacetimeDBInterface, SpacetimeDBCreationParameters>
@@ -45,16 +78,33 @@ where
         mut on_disconnect_callback: impl FnMut() + Send + 'static,
         creation_params: SpacetimeDBCreationParameters,
     ) -> Self {
+        let subscriptions = Subscriptions {
+            status_backfill: Duration::minutes(creation_params.status_backfill_minutes.into()),
+            connected_at: Utc::now(),
+            own_identity: None,
+            friends: Vec::new(),
+        };
         let mut interface = Self {
             is_connected: Arc::new(AtomicBool::new(false)),
+            subscriptions: Arc::new(Mutex::new(subscriptions)),
+            has_recipient_statuses: AtomicBool::new(false),
             creation_parameters: creation_params,
         };
         let is_connected_clone = interface.is_connected.clone();
-        register_callbacks(update_callback, move || {
-            info!("Disconnected from SpacetimeDB!");
-            is_connected_clone.store(false, atomic::Ordering::SeqCst);
-            on_disconnect_callback();
-        });
+        let encryption = interface.creation_parameters.encryption.clone();
+        let creds_dir = interface.creation_parameters.creds_dir.clone();
+        let subscriptions = interface.subscriptions.clone();
+        register_callbacks(
+            update_callback,
+            encryption,
+            creds_dir,
+            subscriptions,
+            move || {
+                info!("Disconnected from SpacetimeDB!");
+                is_connected_clone.store(false, atomic::Ordering::SeqCst);
+                on_disconnect_callback();
+            },
+        );
         <SpacetimeDBInterface as NetworkInterface<T>>::try_reconnect(&mut interface);
 
         interface
@@ -66,8 +116,49 @@ where
     T: Serialize + for<'a> Deserialize<'a> + AcceptsOnlineStatus,
 {
     fn publish_update(&self, sensor_outputs: T) {
-        let json = serde_json::to_string(&sensor_outputs).unwrap();
-        set_status(json);
+        match encode_status(&sensor_outputs, &self.creation_parameters.encryption) {
+            Ok(status) => set_status(status),
+            Err(e) => error!("Failed to encode status, not publishing it: {}", e),
+        }
+    }
+
+    fn publish_recipient_updates(&self, updates: Vec<(UniqueUserId, T)>) {
+        let had_recipient_statuses = self
+            .has_recipient_statuses
+            .swap(!updates.is_empty(), atomic::Ordering::SeqCst);
+        if updates.is_empty() && !had_recipient_statuses {
+            return;
+        }
+
+        let updates = updates
+            .into_iter()
+            .filter_map(|(user_id, sensor_outputs)| {
+                let Some(recipient) = find_identity(&user_id) else {
+                    debug!("Not publishing a status for unknown user {}", user_id);
+                    return None;
+                };
+                let encryption = &self.creation_parameters.encryption;
+                match encode_recipient_status(&sensor_outputs, &user_id, encryption) {
+                    Ok(status) => Some(RecipientStatusUpdate { recipient, status }),
+                    // Everyone else in the circle could read it otherwise
+                    Err(EncryptionError::NoKeyForUser(_)) => {
+                        warn!(
+                            "Not publishing a status for {} since we don't have their key",
+                            user_id
+                        );
+                        None
+                    }
+                    Err(e) => {
+                        error!(
+                            "Failed to encode status for {}, not publishing it: {}",
+                            user_id, e
+                        );
+                        None
+                    }
+                }
+            })
+            .collect();
+        replace_recipient_statuses(updates);
     }
 
     fn get_current_user_id(&self) -> Option<UniqueUserId> {
@@ -78,6 +169,19 @@ where
         set_name(name)
     }
 
+    fn update_friendship(&self, user_id: &UniqueUserId, action: FriendshipAction) {
+        let Some(identity) = find_identity(user_id) else {
+            warn!("Can't change friendship with unknown user {}", user_id);
+            return;
+        };
+        match action {
+            FriendshipAction::Request => request_friend(identity),
+            FriendshipAction::Accept => accept_friend(identity),
+            FriendshipAction::Remove => remove_friend(identity),
+            FriendshipAction::Block => block(identity),
+        }
+    }
+
     fn get_network_type(&self) -> gwaihir_client_lib::NetworkType {
         gwaihir_client_lib::NetworkType::SpacetimeDB
     }
@@ -91,7 +195,10 @@ where
         let connected = self.connect_to_db();
         if connected {
             info!("Successfully reconnected");
-            subscribe_to_tables();
+            self.subscriptions
+                .lock()
+                .unwrap()
+                .on_connected(identity().ok());
         }
         connected
     }
@@ -105,9 +212,10 @@ where
 impl SpacetimeDBInterface {
     fn connect_to_db(&mut self) -> bool {
         match connect(
-            SPACETIMEDB_URI,
+            &self.creation_parameters.host_uri,
             &self.creation_parameters.db_name,
-            load_credentials(&creds_dir()).expect("Error reading stored credentials"),
+            load_credentials(&self.creation_parameters.creds_dir)
+                .expect("Error reading stored credentials"),
         ) {
             Ok(_) => {
                 self.is_connected.store(true, atomic::Ordering::SeqCst);
@@ -124,38 +232,291 @@ impl SpacetimeDBInterface {
 /// Register all the callbacks our app will use to respond to database events.
 fn register_callbacks<T>(
     update_callback: impl Fn(RemoteUpdate<T>) + Send + Clone + 'static,
+    encryption: SharedEncryption,
+    creds_dir: String,
+    subscriptions: Arc<Mutex<Subscriptions>>,
     on_disconnect_callback: impl FnMut() + Send + 'static,
 ) where
     T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
 {
     // // When we receive our `Credentials`, save them to a file.
-    once_on_connect(on_connected);
+    let subscriptions_clone = subscriptions.clone();
+    once_on_connect(move |creds, address| {
+        on_connected(&creds_dir, creds, address, &subscriptions_clone)
+    });
     on_disconnect(on_disconnect_callback);
 
+    // The server checks what we send, and the user should know when it refuses something
+    let callback_clone = update_callback.clone();
+    on_set_name(move |caller, _, status, _| on_reducer_finished(&callback_clone, caller, status));
+    let callback_clone = update_callback.clone();
+    on_set_status(move |caller, _, status, _| on_reducer_finished(&callback_clone, caller, status));
+    let callback_clone = update_callback.clone();
+    on_replace_recipient_statuses(move |caller, _, status, _| {
+        on_reducer_finished(&callback_clone, caller, status)
+    });
+
     let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
     User::on_insert(move |a, _| {
-        if let Some(update) = convert_to_remote_update(a) {
+        if let Some(update) = convert_to_remote_update(a, &encryption_clone) {
             callback_clone(update);
         }
     });
 
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
     User::on_update(move |a, b, c| {
-        if let Some(update) = on_user_updated(a, b, c) {
+        if let Some(update) = on_user_updated(a, b, c, &encryption_clone) {
+            callback_clone(update);
+        }
+    });
+
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
+    RecipientStatus::on_insert(move |status, _| {
+        if let Some(update) = on_recipient_status_changed(status, &encryption_clone) {
+            callback_clone(update);
+        }
+    });
+
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
+    RecipientStatus::on_update(move |_, status, _| {
+        if let Some(update) = on_recipient_status_changed(status, &encryption_clone) {
+            callback_clone(update);
+        }
+    });
+
+    // The sender stopped sharing with us specifically, so we're back to their public status
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
+    RecipientStatus::on_delete(move |status, _| {
+        if let Some(update) = User::filter_by_identity(status.sender.clone())
+            .and_then(|sender| convert_to_remote_update(&sender, &encryption_clone))
+        {
+            callback_clone(update);
+        }
+    });
+
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
+    let subscriptions_clone = subscriptions.clone();
+    Friendship::on_insert(move |_, _| {
+        on_friendships_changed(&callback_clone, &encryption_clone, &subscriptions_clone)
+    });
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
+    let subscriptions_clone = subscriptions.clone();
+    Friendship::on_update(move |_, _, _| {
+        on_friendships_changed(&callback_clone, &encryption_clone, &subscriptions_clone)
+    });
+    let callback_clone = update_callback.clone();
+    let encryption_clone = encryption.clone();
+    Friendship::on_delete(move |_, _| {
+        on_friendships_changed(&callback_clone, &encryption_clone, &subscriptions)
+    });
+
+    StatusHistory::on_insert(move |status, reducer_event| {
+        // Statuses set while we're connected already arrived through `User`
+        if reducer_event.is_some() {
+            return;
+        }
+        if let Some(update) = on_past_status_received(status, &encryption) {
             update_callback(update);
         }
     });
 }
 
-/// Register subscriptions for all rows of both tables.
-fn subscribe_to_tables() {
-    subscribe(&["SELECT * FROM User;"]).unwrap();
+impl Subscriptions {
+    /// Every connection needs subscribing afresh. We don't know who we are until SpacetimeDB has
+    /// given us an identity, so this is repeated once it has.
+    fn on_connected(&mut self, own_identity: Option<Identity>) {
+        self.connected_at = Utc::now();
+        self.own_identity = own_identity;
+        self.subscribe();
+    }
+
+    /// Fetches what new friends sent while we were away
+    fn on_friends_changed(&mut self, mut friends: Vec<Identity>) {
+        friends.sort_by(|a, b| a.bytes().cmp(b.bytes()));
+        if friends != self.friends {
+            self.friends = friends;
+            self.subscribe();
+        }
+    }
+
+    fn subscribe(&self) {
+        subscribe_owned(self.queries()).unwrap();
+    }
+
+    /// Every user, but only the recipient statuses and friendships involving us, and only our
+    /// friends' status history
+    fn queries(&self) -> Vec<String> {
+        let mut queries = vec!["SELECT * FROM User;".to_string()];
+        let Some(identity) = self.own_identity.as_ref() else {
+            return queries;
+        };
+        let identity = hex::encode(identity.bytes());
+        queries.push(format!(
+            "SELECT * FROM RecipientStatus WHERE recipient = 0x{};",
+            identity
+        ));
+        queries.push(format!(
+            "SELECT * FROM Friendship WHERE user_identity = 0x{};",
+            identity
+        ));
+        queries.push(format!(
+            "SELECT * FROM Friendship WHERE friend_identity = 0x{};",
+            identity
+        ));
+
+        let backfill_from = self.connected_at - self.status_backfill;
+        for friend in self.friends.iter() {
+            queries.push(format!(
+                "SELECT * FROM StatusHistory WHERE sender = 0x{} AND sent_at >= {} AND sent_at < {};",
+                hex::encode(friend.bytes()),
+                backfill_from.timestamp_micros(),
+                self.connected_at.timestamp_micros()
+            ));
+        }
+        queries
+    }
 }
 
 /// Our `on_connect` callback: save our credentials to a file.
-fn on_connected(creds: &Credentials, _address: Address) {
-    if let Err(e) = save_credentials(&creds_dir(), creds) {
+fn on_connected(
+    creds_dir: &str,
+    creds: &Credentials,
+    _address: Address,
+    subscriptions: &Mutex<Subscriptions>,
+) {
+    if let Err(e) = save_credentials(creds_dir, creds) {
         error!("Failed to save credentials: {:?}", e);
     }
+    subscriptions
+        .lock()
+        .unwrap()
+        .on_connected(Some(creds.identity.clone()));
+}
+
+fn on_reducer_finished<T>(
+    update_callback: &impl Fn(RemoteUpdate<T>),
+    caller: &Identity,
+    status: &Status,
+) {
+    if let Status::Failed(reason) = status {
+        if identity().is_ok_and(|own_identity| own_identity == *caller) {
+            update_callback(RemoteUpdate::UpdateRejected(reason.clone()));
+        }
+    }
+}
+
+/// Serializes, encrypts and wraps a status the way `set_status` expects
+fn encode_status<T: Serialize>(
+    sensor_outputs: &T,
+    encryption: &SharedEncryption,
+) -> Result<String, String> {
+    let json = serde_json::to_string(sensor_outputs).map_err(|e| e.to_string())?;
+    let payload = encode_payload(encryption, json).map_err(|e| e.to_string())?;
+    status_payload::wrap(&payload).map_err(|e| e.to_string())
+}
+
+/// Like `encode_status`, but sealed so that only `user_id` and ourselves can read it
+fn encode_recipient_status<T: Serialize>(
+    sensor_outputs: &T,
+    user_id: &UniqueUserId,
+    encryption: &SharedEncryption,
+) -> Result<String, EncryptionError> {
+    let json = serde_json::to_string(sensor_outputs)?;
+    let payload = encode_payload_for_user(encryption, user_id, json)?;
+    Ok(status_payload::wrap(&payload)?)
+}
+
+fn find_identity(user_id: &UniqueUserId) -> Option<Identity> {
+    User::iter()
+        .find(|u| *user_id.as_ref() == identity_leading_hex(&u.identity))
+        .map(|u| u.identity)
+}
+
+/// Sends our friendships, and the statuses of any users that just became friends
+fn on_friendships_changed<T>(
+    update_callback: &impl Fn(RemoteUpdate<T>),
+    encryption: &SharedEncryption,
+    subscriptions: &Mutex<Subscriptions>,
+) where
+    T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    let Ok(own_identity) = identity() else {
+        return;
+    };
+    let friendships = get_friendships(
+        &own_identity,
+        &Friendship::filter_by_user_identity(own_identity.clone()).collect::<Vec<_>>(),
+        &Friendship::filter_by_friend_identity(own_identity.clone()).collect::<Vec<_>>(),
+        |identity| User::filter_by_identity(identity.clone()).and_then(|u| u.name),
+    );
+    let friends = User::iter()
+        .filter(|u| friendships.is_friend(&UniqueUserId::new(identity_leading_hex(&u.identity))))
+        .collect::<Vec<_>>();
+    update_callback(RemoteUpdate::FriendshipsUpdated(Box::new(friendships)));
+    subscriptions
+        .lock()
+        .unwrap()
+        .on_friends_changed(friends.iter().map(|f| f.identity.clone()).collect());
+    for friend in friends {
+        if let Some(update) = convert_to_remote_update(&friend, encryption) {
+            update_callback(update);
+        }
+    }
+}
+
+/// Works out our friendships from `ours`, the rows where we're the user, and `theirs`, the rows
+/// where we're the friend
+fn get_friendships(
+    own_identity: &Identity,
+    ours: &[Friendship],
+    theirs: &[Friendship],
+    get_name: impl Fn(&Identity) -> Option<String>,
+) -> Friendships {
+    let mut friendships = Friendships::default();
+    for friendship in ours.iter().filter(|f| f.user_identity == *own_identity) {
+        let user_id = UniqueUserId::new(identity_leading_hex(&friendship.friend_identity));
+        let agreed = theirs
+            .iter()
+            .any(|t| t.user_identity == friendship.friend_identity && !t.blocked);
+        if friendship.blocked {
+            friendships.blocked.insert(user_id);
+        } else if agreed {
+            friendships.friends.insert(user_id);
+        } else {
+            friendships.outgoing_requests.insert(user_id);
+        }
+    }
+    for request in theirs
+        .iter()
+        .filter(|t| t.friend_identity == *own_identity && !t.blocked)
+        .filter(|t| !ours.iter().any(|o| o.friend_identity == t.user_identity))
+    {
+        friendships.incoming_requests.push(FriendRequest {
+            user_id: UniqueUserId::new(identity_leading_hex(&request.user_identity)),
+            username: Username::new(get_name(&request.user_identity).unwrap_or_default()),
+        });
+    }
+
+    friendships
+}
+
+/// Statuses are only surfaced once both users have agreed to see each other
+fn is_visible(user: &Identity) -> bool {
+    let Ok(own_identity) = identity() else {
+        return false;
+    };
+    let agreed = |user: &Identity, friend: &Identity| {
+        Friendship::filter_by_user_identity(user.clone())
+            .any(|f| f.friend_identity == *friend && !f.blocked)
+    };
+    *user == own_identity || (agreed(&own_identity, user) && agreed(user, &own_identity))
 }
 
 fn identity_leading_hex(id: &Identity) -> String {
@@ -164,7 +525,12 @@ fn identity_leading_hex(id: &Identity) -> String {
 
 /// Our `User::on_update` callback:
 /// print a notification about name and status changes.
-fn on_user_updated<T>(old: &User, new: &User, _: Option<&ReducerEvent>) -> Option<RemoteUpdate<T>>
+fn on_user_updated<T>(
+    old: &User,
+    new: &User,
+    _: Option<&ReducerEvent>,
+    encryption: &SharedEncryption,
+) -> Option<RemoteUpdate<T>>
 where
     T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
 {
@@ -172,47 +538,230 @@ where
         || old.name != new.name
         || old.online != new.online
     {
-        return convert_to_remote_update(new);
+        return convert_to_remote_update(new, encryption);
     }
 
     None
 }
 
-fn convert_to_remote_update<T>(new: &User) -> Option<RemoteUpdate<T>>
+fn on_recipient_status_changed<T>(
+    status: &RecipientStatus,
+    encryption: &SharedEncryption,
+) -> Option<RemoteUpdate<T>>
+where
+    T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    let sender = User::filter_by_identity(status.sender.clone())?;
+    decode_status(
+        &sender,
+        &status.status,
+        status.last_status_update,
+        encryption,
+    )
+    .map(RemoteUpdate::UserStatusUpdated)
+}
+
+fn on_past_status_received<T>(
+    status: &StatusHistory,
+    encryption: &SharedEncryption,
+) -> Option<RemoteUpdate<T>>
+where
+    T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    let sender = User::filter_by_identity(status.sender.clone())?;
+    let mut status: UserStatus<T> =
+        decode_status(&sender, &status.status, status.sent_at, encryption)?;
+    // They must have been online to send it
+    status.sensor_outputs.set_online_status(true);
+    Some(RemoteUpdate::PastStatusReceived(status))
+}
+
+/// A status addressed to us takes the place of the one everyone sees
+fn convert_to_remote_update<T>(new: &User, encryption: &SharedEncryption) -> Option<RemoteUpdate<T>>
+where
+    T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
+{
+    let status = match RecipientStatus::filter_by_sender(new.identity.clone()).next() {
+        Some(status) => decode_status(new, &status.status, status.last_status_update, encryption),
+        None => decode_status(
+            new,
+            new.status.as_ref()?,
+            new.last_status_update?,
+            encryption,
+        ),
+    };
+    status.map(RemoteUpdate::UserStatusUpdated)
+}
+
+fn decode_status<T>(
+    new: &User,
+    payload: &str,
+    last_status_update: u64,
+    encryption: &SharedEncryption,
+) -> Option<UserStatus<T>>
 where
     T: for<'a> Deserialize<'a> + AcceptsOnlineStatus,
 {
-    if let Some(status) = new.status.clone() {
-        match serde_json::from_str::<T>(&status) {
-            Ok(mut sensor_data) => {
-                let last_update = Utc.from_utc_datetime(
-                    &NaiveDateTime::from_timestamp_micros(
-                        new.last_status_update.unwrap().try_into().unwrap(),
-                    )
+    if !is_visible(&new.identity) {
+        return None;
+    }
+
+    let decoded = status_payload::unwrap(payload)
+        .and_then(|payload| decode_payload(encryption, &payload).map_err(|e| e.to_string()));
+    let status = match decoded {
+        Ok(status) => status,
+        Err(e) => {
+            // Users that haven't shared their data with us should simply not show up
+            debug!(
+                "Unable to read status of user ({:?}, {}): {}",
+                new.name,
+                identity_leading_hex(&new.identity),
+                e
+            );
+            return None;
+        }
+    };
+
+    match serde_json::from_str::<T>(&status) {
+        Ok(mut sensor_data) => {
+            let last_update = Utc.from_utc_datetime(
+                &NaiveDateTime::from_timestamp_micros(last_status_update.try_into().unwrap())
                     .unwrap(),
-                );
-                sensor_data.set_online_status(new.online);
-                return Some(RemoteUpdate::UserStatusUpdated(UserStatus {
-                    user_id: UniqueUserId::new(identity_leading_hex(&new.identity)),
-                    username: Username::new(new.name.clone().unwrap_or_default()),
-                    sensor_outputs: sensor_data,
-                    last_update,
-                }));
-            }
-            Err(e) => {
-                error!(
-                    "Failed to deserialize sensor data for user ({:?}, {}): {}",
-                    new.name,
-                    identity_leading_hex(&new.identity),
-                    e
-                );
-            }
+            );
+            sensor_data.set_online_status(new.online);
+            Some(UserStatus {
+                user_id: UniqueUserId::new(identity_leading_hex(&new.identity)),
+                username: Username::new(new.name.clone().unwrap_or_default()),
+                sensor_outputs: sensor_data,
+                last_update,
+            })
+        }
+        Err(e) => {
+            error!(
+                "Failed to deserialize sensor data for user ({:?}, {}): {}",
+                new.name,
+                identity_leading_hex(&new.identity),
+                e
+            );
+            None
         }
     }
-
-    None
 }
 
-fn creds_dir() -> String {
+pub fn default_creds_dir() -> String {
     format!(".{}", APP_ID)
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use gwaihir_client_lib::encryption::{EncryptionMode, GroupKey, KeyPair, PayloadEncryption};
+    use std::{collections::HashMap, sync::RwLock};
+
+    fn identity(byte: u8) -> Identity {
+        Identity::from_bytes(vec![byte; 32])
+    }
+
+    fn friendship(user: u8, friend: u8, blocked: bool) -> Friendship {
+        Friendship {
+            id: 0,
+            user_identity: identity(user),
+            friend_identity: identity(friend),
+            blocked,
+        }
+    }
+
+    fn user_id(byte: u8) -> UniqueUserId {
+        UniqueUserId::new(identity_leading_hex(&identity(byte)))
+    }
+
+    #[test]
+    pub fn only_mutual_unblocked_friendships_count() {
+        let me = identity(0);
+        let ours = [
+            friendship(0, 1, false),
+            friendship(0, 2, false),
+            friendship(0, 3, true),
+        ];
+        let theirs = [
+            friendship(1, 0, false),
+            friendship(3, 0, false),
+            friendship(4, 0, false),
+            friendship(5, 0, true),
+        ];
+
+        let friendships = get_friendships(&me, &ours, &theirs, |_| Some("Bob".to_string()));
+
+        assert!(friendships.is_friend(&user_id(1)));
+        assert_eq!(friendships.friends.len(), 1);
+        assert!(friendships.outgoing_requests.contains(&user_id(2)));
+        assert!(friendships.blocked.contains(&user_id(3)));
+        // Blocked users' requests and users blocking us don't show up as requests
+        assert_eq!(friendships.incoming_requests.len(), 1);
+        assert_eq!(friendships.incoming_requests[0].user_id, user_id(4));
+        assert_eq!(friendships.incoming_requests[0].username.as_ref(), "Bob");
+    }
+
+    #[test]
+    pub fn recipient_statuses_can_only_be_read_by_their_recipient() {
+        let bob = PayloadEncryption::new(KeyPair::generate());
+        let carol = PayloadEncryption::new(KeyPair::generate());
+        let sender = PayloadEncryption {
+            recipients: vec![bob.key_pair.public_key(), carol.key_pair.public_key()],
+            keys_by_user: HashMap::from([
+                (user_id(1), bob.key_pair.public_key()),
+                (user_id(2), carol.key_pair.public_key()),
+            ]),
+            group_key: Some(GroupKey::generate()),
+            mode: EncryptionMode::Group,
+            ..PayloadEncryption::new(KeyPair::generate())
+        };
+        let shared = |encryption| Arc::new(RwLock::new(Some(encryption)));
+        let sender = shared(sender);
+        let decode = |encryption: &SharedEncryption, status: &str| {
+            decode_payload(encryption, &status_payload::unwrap(status).unwrap())
+        };
+
+        let status = encode_recipient_status(&"extra sensors", &user_id(1), &sender).unwrap();
+
+        assert!(decode(&shared(bob), &status).is_ok());
+        assert!(decode(&sender, &status).is_ok());
+        assert!(matches!(
+            decode(&shared(carol), &status),
+            Err(EncryptionError::NotARecipient)
+        ));
+        assert!(matches!(
+            encode_recipient_status(&"extra sensors", &user_id(3), &sender),
+            Err(EncryptionError::NoKeyForUser(_))
+        ));
+    }
+
+    #[test]
+    pub fn only_friends_status_history_is_fetched() {
+        let connected_at = Utc::now();
+        let mut subscriptions = Subscriptions {
+            status_backfill: Duration::minutes(30),
+            connected_at,
+            own_identity: None,
+            friends: vec![identity(1)],
+        };
+        assert_eq!(subscriptions.queries(), vec!["SELECT * FROM User;"]);
+
+        subscriptions.own_identity = Some(identity(0));
+        let history_queries = subscriptions
+            .queries()
+            .into_iter()
+            .filter(|q| q.contains("StatusHistory"))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            history_queries,
+            vec![format!(
+                "SELECT * FROM StatusHistory WHERE sender = 0x{} AND sent_at >= {} AND sent_at < {};",
+                hex::encode(identity(1).bytes()),
+                (connected_at - Duration::minutes(30)).timestamp_micros(),
+                connected_at.timestamp_micros()
+            )]
+        );
+    }
+}
@@ -20,12 +20,38 @@ use spacetimedb_sdk::{
 };
 use std::sync::Arc;
 
+pub mod accept_friend_reducer;
+pub mod block_reducer;
+pub mod config;
+pub mod friendship;
+pub mod prune_status_history_reducer;
+pub mod rate_limit;
+pub mod recipient_status;
+pub mod recipient_status_update;
+pub mod remove_friend_reducer;
+pub mod replace_recipient_statuses_reducer;
+pub mod request_friend_reducer;
 pub mod set_name_reducer;
+pub mod set_status_history_retention_reducer;
 pub mod set_status_reducer;
+pub mod status_history;
 pub mod user;
 
+pub use accept_friend_reducer::*;
+pub use block_reducer::*;
+pub use config::*;
+pub use friendship::*;
+pub use prune_status_history_reducer::*;
+pub use rate_limit::*;
+pub use recipient_status::*;
+pub use recipient_status_update::*;
+pub use remove_friend_reducer::*;
+pub use replace_recipient_statuses_reducer::*;
+pub use request_friend_reducer::*;
 pub use set_name_reducer::*;
+pub use set_status_history_retention_reducer::*;
 pub use set_status_reducer::*;
+pub use status_history::*;
 pub use user::*;
 
 #[allow(unused)]
@@ -33,6 +59,13 @@ pub use user::*;
 pub enum ReducerEvent {
     SetName(set_name_reducer::SetNameArgs),
     SetStatus(set_status_reducer::SetStatusArgs),
+    ReplaceRecipientStatuses(replace_recipient_statuses_reducer::ReplaceRecipientStatusesArgs),
+    RequestFriend(request_friend_reducer::RequestFriendArgs),
+    AcceptFriend(accept_friend_reducer::AcceptFriendArgs),
+    RemoveFriend(remove_friend_reducer::RemoveFriendArgs),
+    Block(block_reducer::BlockArgs),
+    SetStatusHistoryRetention(set_status_history_retention_reducer::SetStatusHistoryRetentionArgs),
+    PruneStatusHistory(prune_status_history_reducer::PruneStatusHistoryArgs),
 }
 
 #[allow(unused)]
@@ -48,6 +81,28 @@ impl SpacetimeModule for Module {
         match table_name {
             "User" => client_cache
                 .handle_table_update_with_primary_key::<user::User>(callbacks, table_update),
+            "RecipientStatus" => client_cache
+                .handle_table_update_with_primary_key::<recipient_status::RecipientStatus>(
+                    callbacks,
+                    table_update,
+                ),
+            "Friendship" => client_cache
+                .handle_table_update_with_primary_key::<friendship::Friendship>(
+                    callbacks,
+                    table_update,
+                ),
+            "StatusHistory" => client_cache
+                .handle_table_update_with_primary_key::<status_history::StatusHistory>(
+                    callbacks,
+                    table_update,
+                ),
+            "Config" => client_cache
+                .handle_table_update_with_primary_key::<config::Config>(callbacks, table_update),
+            "RateLimit" => client_cache
+                .handle_table_update_with_primary_key::<rate_limit::RateLimit>(
+                    callbacks,
+                    table_update,
+                ),
             _ => {
                 spacetimedb_sdk::log::error!("TableRowOperation on unknown table {:?}", table_name)
             }
@@ -61,6 +116,15 @@ impl SpacetimeModule for Module {
         state: &Arc<ClientCache>,
     ) {
         reminders.invoke_callbacks::<user::User>(worker, &reducer_event, state);
+        reminders.invoke_callbacks::<recipient_status::RecipientStatus>(
+            worker,
+            &reducer_event,
+            state,
+        );
+        reminders.invoke_callbacks::<friendship::Friendship>(worker, &reducer_event, state);
+        reminders.invoke_callbacks::<status_history::StatusHistory>(worker, &reducer_event, state);
+        reminders.invoke_callbacks::<config::Config>(worker, &reducer_event, state);
+        reminders.invoke_callbacks::<rate_limit::RateLimit>(worker, &reducer_event, state);
     }
     fn handle_event(
         &self,
@@ -86,6 +150,45 @@ impl SpacetimeModule for Module {
                     _state,
                     ReducerEvent::SetStatus,
                 ),
+            "replace_recipient_statuses" => _reducer_callbacks
+                .handle_event_of_type::<
+                    replace_recipient_statuses_reducer::ReplaceRecipientStatusesArgs,
+                    ReducerEvent,
+                >(event, _state, ReducerEvent::ReplaceRecipientStatuses),
+            "request_friend" => _reducer_callbacks
+                .handle_event_of_type::<request_friend_reducer::RequestFriendArgs, ReducerEvent>(
+                    event,
+                    _state,
+                    ReducerEvent::RequestFriend,
+                ),
+            "accept_friend" => _reducer_callbacks
+                .handle_event_of_type::<accept_friend_reducer::AcceptFriendArgs, ReducerEvent>(
+                    event,
+                    _state,
+                    ReducerEvent::AcceptFriend,
+                ),
+            "remove_friend" => _reducer_callbacks
+                .handle_event_of_type::<remove_friend_reducer::RemoveFriendArgs, ReducerEvent>(
+                    event,
+                    _state,
+                    ReducerEvent::RemoveFriend,
+                ),
+            "block" => _reducer_callbacks
+                .handle_event_of_type::<block_reducer::BlockArgs, ReducerEvent>(
+                    event,
+                    _state,
+                    ReducerEvent::Block,
+                ),
+            "set_status_history_retention" => _reducer_callbacks
+                .handle_event_of_type::<
+                    set_status_history_retention_reducer::SetStatusHistoryRetentionArgs,
+                    ReducerEvent,
+                >(event, _state, ReducerEvent::SetStatusHistoryRetention),
+            "prune_status_history" => _reducer_callbacks
+                .handle_event_of_type::<
+                    prune_status_history_reducer::PruneStatusHistoryArgs,
+                    ReducerEvent,
+                >(event, _state, ReducerEvent::PruneStatusHistory),
             unknown => {
                 spacetimedb_sdk::log::error!("Event on an unknown reducer: {:?}", unknown);
                 None
@@ -101,6 +204,19 @@ impl SpacetimeModule for Module {
         let table_name = &new_subs.table_name[..];
         match table_name {
             "User" => client_cache.handle_resubscribe_for_type::<user::User>(callbacks, new_subs),
+            "RecipientStatus" => client_cache
+                .handle_resubscribe_for_type::<recipient_status::RecipientStatus>(
+                    callbacks, new_subs,
+                ),
+            "Friendship" => client_cache
+                .handle_resubscribe_for_type::<friendship::Friendship>(callbacks, new_subs),
+            "StatusHistory" => client_cache
+                .handle_resubscribe_for_type::<status_history::StatusHistory>(callbacks, new_subs),
+            "Config" => {
+                client_cache.handle_resubscribe_for_type::<config::Config>(callbacks, new_subs)
+            }
+            "RateLimit" => client_cache
+                .handle_resubscribe_for_type::<rate_limit::RateLimit>(callbacks, new_subs),
             _ => {
                 spacetimedb_sdk::log::error!("TableRowOperation on unknown table {:?}", table_name)
             }
@@ -0,0 +1,56 @@
+use serde::{Deserialize, Serialize};
+
+/// Must match the version the server module accepts in `set_status`
+const STATUS_SCHEMA_VERSION: u32 = 1;
+
+/// The shape the server module checks statuses against. `status` is either the serialized
+/// sensor outputs, or an encrypted payload.
+#[derive(Serialize, Deserialize)]
+struct StatusPayload {
+    schema_version: u32,
+    status: serde_json::Value,
+}
+
+/// Wraps a payload from `encode_payload` the way the server module expects
+pub fn wrap(payload: &str) -> Result<String, serde_json::Error> {
+    serde_json::to_string(&StatusPayload {
+        schema_version: STATUS_SCHEMA_VERSION,
+        status: serde_json::from_str(payload)?,
+    })
+}
+
+/// *Returns:* The payload to pass on to `decode_payload`
+pub fn unwrap(payload: &str) -> Result<String, String> {
+    match serde_json::from_str::<StatusPayload>(payload) {
+        Ok(wrapped) if wrapped.schema_version == STATUS_SCHEMA_VERSION => {
+            Ok(wrapped.status.to_string())
+        }
+        Ok(wrapped) => Err(format!(
+            "Unsupported status schema version {}",
+            wrapped.schema_version
+        )),
+        // Statuses set before they had a schema version are the payload itself
+        Err(_) => Ok(payload.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAYLOAD: &str = r#"{"outputs":[]}"#;
+
+    #[test]
+    pub fn payloads_round_trip() {
+        let wrapped = wrap(PAYLOAD).unwrap();
+
+        assert_eq!(r#"{"schema_version":1,"status":{"outputs":[]}}"#, wrapped);
+        assert_eq!(PAYLOAD, unwrap(&wrapped).unwrap());
+    }
+
+    #[test]
+    pub fn unwrapping_handles_old_and_future_statuses() {
+        assert_eq!(PAYLOAD, unwrap(PAYLOAD).unwrap());
+        assert!(unwrap(r#"{"schema_version":2,"status":{"outputs":[]}}"#).is_err());
+    }
+}
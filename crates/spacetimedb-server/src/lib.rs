@@ -1,4 +1,22 @@
-use spacetimedb::{spacetimedb, Identity, ReducerContext, Timestamp};
+use serde::Deserialize;
+use spacetimedb::{spacetimedb, Address, Identity, ReducerContext, SpacetimeType, Timestamp};
+use std::time::Duration;
+
+/// The `id` of the only row in `Config`
+const CONFIG_ID: u32 = 0;
+const DEFAULT_STATUS_HISTORY_RETENTION_MINUTES: u64 = 24 * 60;
+const STATUS_HISTORY_PRUNE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// The version of `StatusPayload` clients must send. Clients check it too, so bump it whenever
+/// its shape changes.
+const STATUS_SCHEMA_VERSION: u32 = 1;
+const MAX_STATUS_BYTES: usize = 64 * 1024;
+const MAX_NAME_CHARS: usize = 32;
+/// How many of `set_name`, `set_status` and `replace_recipient_statuses` each user can call
+/// within `RATE_LIMIT_WINDOW`. Clients publish whenever one of their sensors changes, which
+/// happens in bursts while they're in use, so this leaves room for that but not for flooding.
+const MAX_CALLS_PER_RATE_LIMIT_WINDOW: u32 = 120;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
 
 #[spacetimedb(table)]
 pub struct User {
@@ -10,10 +28,95 @@ pub struct User {
     last_status_update: Option<Timestamp>,
 }
 
+/// A status meant only for `recipient`, who sees it instead of the sender's status in `User`.
+/// Clients only subscribe to the rows addressed to them, but SpacetimeDB can't stop them from
+/// reading others, so these should still be encrypted.
+#[spacetimedb(table)]
+pub struct RecipientStatus {
+    #[primarykey]
+    #[autoinc]
+    id: u64,
+    sender: Identity,
+    recipient: Identity,
+    status: String,
+    last_status_update: Timestamp,
+}
+
+/// Who `user_identity` has agreed to see, or blocked. Clients only show users once both have
+/// agreed to see each other.
+#[spacetimedb(table)]
+pub struct Friendship {
+    #[primarykey]
+    #[autoinc]
+    id: u64,
+    user_identity: Identity,
+    friend_identity: Identity,
+    blocked: bool,
+}
+
+/// Every status set through `set_status`, so clients connecting later can catch up on what they
+/// missed. Rows older than the configured retention are pruned periodically.
+#[spacetimedb(table)]
+pub struct StatusHistory {
+    #[primarykey]
+    #[autoinc]
+    id: u64,
+    sender: Identity,
+    status: String,
+    sent_at: Timestamp,
+}
+
+/// Settings for the whole module, kept in a single row
+#[spacetimedb(table)]
+pub struct Config {
+    #[primarykey]
+    id: u32,
+    /// Whoever published the module, who is the only one allowed to change the settings
+    admin: Identity,
+    /// The module's own address. Only automatic reducers, like scheduled ones, are called with it.
+    module_address: Option<Address>,
+    status_history_retention_minutes: u64,
+}
+
+/// How many rate-limited reducers each user has called in their current window
+#[spacetimedb(table)]
+pub struct RateLimit {
+    #[primarykey]
+    identity: Identity,
+    window_start: Timestamp,
+    calls: u32,
+}
+
+/// The shape every status is checked against. The status itself is either a list of sensor
+/// outputs, or an encrypted payload the server can't look inside.
+#[derive(Deserialize)]
+struct StatusPayload {
+    schema_version: u32,
+    status: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(SpacetimeType)]
+pub struct RecipientStatusUpdate {
+    recipient: Identity,
+    status: String,
+}
+
+#[spacetimedb(init)]
+pub fn init(ctx: ReducerContext) {
+    create_config_if_missing(ctx);
+}
+
+#[spacetimedb(update)]
+// Called when the module is republished, which doesn't run `init` for existing databases
+pub fn update(ctx: ReducerContext) {
+    create_config_if_missing(ctx);
+}
+
 #[spacetimedb(reducer)]
 /// Clients invoke this reducer to set their user names.
 pub fn set_name(ctx: ReducerContext, name: String) -> Result<(), String> {
     let name = validate_name(name)?;
+    check_rate_limit(ctx.sender)?;
     if let Some(user) = User::filter_by_identity(&ctx.sender) {
         User::update_by_identity(
             &ctx.sender,
@@ -30,21 +133,175 @@ pub fn set_name(ctx: ReducerContext, name: String) -> Result<(), String> {
 
 #[spacetimedb(reducer)]
 pub fn set_status(ctx: ReducerContext, status: String) -> Result<(), String> {
+    validate_status(&status)?;
     if let Some(user) = User::filter_by_identity(&ctx.sender) {
+        check_rate_limit(ctx.sender)?;
+        let now = Timestamp::now();
         User::update_by_identity(
             &ctx.sender,
             User {
-                status: Some(status),
-                last_status_update: Some(Timestamp::now()),
+                status: Some(status.clone()),
+                last_status_update: Some(now),
                 ..user
             },
         );
-        Ok(())
+        StatusHistory::insert(StatusHistory {
+            id: 0,
+            sender: ctx.sender,
+            status,
+            sent_at: now,
+        })
+        .map(|_| ())
+        .map_err(|e| e.to_string())
     } else {
         Err("Cannot set status for unknown user".to_string())
     }
 }
 
+#[spacetimedb(reducer)]
+/// Replaces all of the sender's recipient-specific statuses. Recipients left out of `updates`
+/// go back to seeing the sender's status in `User`.
+pub fn replace_recipient_statuses(
+    ctx: ReducerContext,
+    updates: Vec<RecipientStatusUpdate>,
+) -> Result<(), String> {
+    if User::filter_by_identity(&ctx.sender).is_none() {
+        return Err("Cannot set status for unknown user".to_string());
+    }
+    for update in updates.iter() {
+        validate_status(&update.status)?;
+    }
+    check_rate_limit(ctx.sender)?;
+
+    let now = Timestamp::now();
+    let mut existing: Vec<RecipientStatus> =
+        RecipientStatus::filter_by_sender(&ctx.sender).collect();
+    for update in updates {
+        // Updating rows in place lets recipients tell a new status from a new recipient
+        match existing
+            .iter()
+            .position(|s| s.recipient == update.recipient)
+        {
+            Some(index) => {
+                let previous = existing.swap_remove(index);
+                RecipientStatus::update_by_id(
+                    &previous.id,
+                    RecipientStatus {
+                        status: update.status,
+                        last_status_update: now,
+                        ..previous
+                    },
+                );
+            }
+            None => {
+                RecipientStatus::insert(RecipientStatus {
+                    id: 0,
+                    sender: ctx.sender,
+                    recipient: update.recipient,
+                    status: update.status,
+                    last_status_update: now,
+                })
+                .map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    for no_longer_shared in existing {
+        RecipientStatus::delete_by_id(&no_longer_shared.id);
+    }
+
+    Ok(())
+}
+
+#[spacetimedb(reducer)]
+/// Asks `friend` to agree to see each other, or agrees to their request if they sent one first.
+pub fn request_friend(ctx: ReducerContext, friend: Identity) -> Result<(), String> {
+    if friend == ctx.sender {
+        return Err("Cannot befriend yourself".to_string());
+    }
+    if User::filter_by_identity(&friend).is_none() {
+        return Err("Cannot befriend unknown user".to_string());
+    }
+    if find_friendship(&friend, &ctx.sender).is_some_and(|f| f.blocked) {
+        // The request simply goes unanswered, as if it had been ignored
+        return Ok(());
+    }
+    set_friendship(ctx.sender, friend, false)
+}
+
+#[spacetimedb(reducer)]
+/// Agrees to a request `friend` sent.
+pub fn accept_friend(ctx: ReducerContext, friend: Identity) -> Result<(), String> {
+    match find_friendship(&friend, &ctx.sender) {
+        Some(request) if !request.blocked => set_friendship(ctx.sender, friend, false),
+        _ => Err("No friend request to accept".to_string()),
+    }
+}
+
+#[spacetimedb(reducer)]
+/// Withdraws or declines a request, ends a friendship, or lifts a block.
+pub fn remove_friend(ctx: ReducerContext, friend: Identity) -> Result<(), String> {
+    if let Some(ours) = find_friendship(&ctx.sender, &friend) {
+        Friendship::delete_by_id(&ours.id);
+    }
+    // Otherwise their side of the friendship would turn back into a request
+    if let Some(theirs) = find_friendship(&friend, &ctx.sender).filter(|f| !f.blocked) {
+        Friendship::delete_by_id(&theirs.id);
+    }
+    Ok(())
+}
+
+#[spacetimedb(reducer)]
+/// Ends any friendship with `user` and stops them sending more requests.
+pub fn block(ctx: ReducerContext, user: Identity) -> Result<(), String> {
+    if let Some(theirs) = find_friendship(&user, &ctx.sender).filter(|f| !f.blocked) {
+        Friendship::delete_by_id(&theirs.id);
+    }
+    set_friendship(ctx.sender, user, true)
+}
+
+#[spacetimedb(reducer)]
+/// Changes how long `StatusHistory` is kept for. Only the module's admin may do this.
+pub fn set_status_history_retention(ctx: ReducerContext, minutes: u64) -> Result<(), String> {
+    match Config::filter_by_id(&CONFIG_ID) {
+        Some(config) if config.admin == ctx.sender => {
+            Config::update_by_id(
+                &CONFIG_ID,
+                Config {
+                    status_history_retention_minutes: minutes,
+                    ..config
+                },
+            );
+            Ok(())
+        }
+        _ => Err("Only the module's admin can change the status history retention".to_string()),
+    }
+}
+
+#[spacetimedb(reducer)]
+/// Deletes statuses older than the retention, then schedules itself to run again.
+/// Clients calling this are refused rather than being able to start extra pruning loops.
+pub fn prune_status_history(ctx: ReducerContext) -> Result<(), String> {
+    let retention_minutes = match Config::filter_by_id(&CONFIG_ID) {
+        Some(config) if is_called_by_module(&config, ctx.address) => {
+            config.status_history_retention_minutes
+        }
+        _ => return Err("Only the module itself prunes the status history".to_string()),
+    };
+    let retention = Duration::from_secs(retention_minutes.saturating_mul(60));
+    if let Some(cutoff) = Timestamp::now().checked_sub(retention) {
+        let expired: Vec<u64> = StatusHistory::iter()
+            .filter(|s| s.sent_at < cutoff)
+            .map(|s| s.id)
+            .collect();
+        for id in expired {
+            StatusHistory::delete_by_id(&id);
+        }
+    }
+
+    spacetimedb::schedule!(STATUS_HISTORY_PRUNE_INTERVAL, prune_status_history(_));
+    Ok(())
+}
+
 #[spacetimedb(connect)]
 // Called when a client connects to the SpacetimeDB
 pub fn identity_connected(ctx: ReducerContext) {
@@ -93,11 +350,198 @@ pub fn identity_disconnected(ctx: ReducerContext) {
     }
 }
 
+/// Also starts pruning the status history, which only needs doing once since pruning keeps
+/// rescheduling itself. `ctx` must be that of `init` or `update`, which are called by whoever
+/// published the module, with the module's own address.
+fn create_config_if_missing(ctx: ReducerContext) {
+    if Config::filter_by_id(&CONFIG_ID).is_some() {
+        return;
+    }
+
+    Config::insert(Config {
+        id: CONFIG_ID,
+        admin: ctx.sender,
+        module_address: ctx.address,
+        status_history_retention_minutes: DEFAULT_STATUS_HISTORY_RETENTION_MINUTES,
+    })
+    .unwrap();
+    spacetimedb::schedule!(STATUS_HISTORY_PRUNE_INTERVAL, prune_status_history(_));
+}
+
+/// Whether a reducer was called by the module itself, rather than by a client
+fn is_called_by_module(config: &Config, caller_address: Option<Address>) -> bool {
+    caller_address.is_some() && caller_address == config.module_address
+}
+
+fn find_friendship(user_identity: &Identity, friend_identity: &Identity) -> Option<Friendship> {
+    Friendship::filter_by_user_identity(user_identity)
+        .find(|f| f.friend_identity == *friend_identity)
+}
+
+fn set_friendship(
+    user_identity: Identity,
+    friend_identity: Identity,
+    blocked: bool,
+) -> Result<(), String> {
+    match find_friendship(&user_identity, &friend_identity) {
+        Some(existing) => {
+            Friendship::update_by_id(
+                &existing.id,
+                Friendship {
+                    blocked,
+                    ..existing
+                },
+            );
+            Ok(())
+        }
+        None => Friendship::insert(Friendship {
+            id: 0,
+            user_identity,
+            friend_identity,
+            blocked,
+        })
+        .map(|_| ())
+        .map_err(|e| e.to_string()),
+    }
+}
+
 /// Takes a name and checks if it's acceptable as a user's name.
 fn validate_name(name: String) -> Result<String, String> {
-    if name.is_empty() {
+    let length = name.chars().count();
+    if name.trim().is_empty() {
         Err("Names must not be empty".to_string())
+    } else if length > MAX_NAME_CHARS {
+        Err(format!(
+            "Names must be at most {} characters long, but this one is {}",
+            MAX_NAME_CHARS, length
+        ))
+    } else if name.trim() != name {
+        Err("Names must not start or end with spaces".to_string())
+    } else if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_alphanumeric() || " -_.'".contains(*c)))
+    {
+        Err(format!(
+            "Names may only contain letters, numbers, spaces and -_.' but this one contains {:?}",
+            c
+        ))
     } else {
         Ok(name)
     }
 }
+
+/// Checks that a status is one clients will be able to read, without looking inside encrypted
+/// ones.
+fn validate_status(status: &str) -> Result<(), String> {
+    if status.len() > MAX_STATUS_BYTES {
+        return Err(format!(
+            "Statuses must be at most {} bytes, but this one is {} bytes",
+            MAX_STATUS_BYTES,
+            status.len()
+        ));
+    }
+
+    let payload = serde_json::from_str::<StatusPayload>(status)
+        .map_err(|e| format!("Status isn't in the expected format: {}", e))?;
+    if payload.schema_version != STATUS_SCHEMA_VERSION {
+        return Err(format!(
+            "Status has schema version {}, but this server only accepts version {}. Gwaihir or the server module needs updating",
+            payload.schema_version, STATUS_SCHEMA_VERSION
+        ));
+    }
+
+    let is_sensor_outputs = payload
+        .status
+        .get("outputs")
+        .is_some_and(|outputs| outputs.is_array());
+    let is_encrypted = payload
+        .status
+        .get("encryption")
+        .is_some_and(|encryption| encryption.is_string());
+    if is_sensor_outputs || is_encrypted {
+        Ok(())
+    } else {
+        Err(
+            "Status must contain either a list of sensor outputs or an encrypted payload"
+                .to_string(),
+        )
+    }
+}
+
+/// Counts a call towards `identity`'s rate limit, failing once they've made too many.
+fn check_rate_limit(identity: Identity) -> Result<(), String> {
+    let now = Timestamp::now();
+    let Some(limit) = RateLimit::filter_by_identity(&identity) else {
+        return RateLimit::insert(RateLimit {
+            identity,
+            window_start: now,
+            calls: 1,
+        })
+        .map(|_| ())
+        .map_err(|e| e.to_string());
+    };
+
+    let window_over = now
+        .duration_since(limit.window_start)
+        .is_ok_and(|elapsed| elapsed >= RATE_LIMIT_WINDOW);
+    if window_over {
+        RateLimit::update_by_identity(
+            &identity,
+            RateLimit {
+                window_start: now,
+                calls: 1,
+                ..limit
+            },
+        );
+        Ok(())
+    } else if limit.calls >= MAX_CALLS_PER_RATE_LIMIT_WINDOW {
+        Err(format!(
+            "Too many updates: at most {} are allowed every {} seconds",
+            MAX_CALLS_PER_RATE_LIMIT_WINDOW,
+            RATE_LIMIT_WINDOW.as_secs()
+        ))
+    } else {
+        RateLimit::update_by_identity(
+            &identity,
+            RateLimit {
+                calls: limit.calls + 1,
+                ..limit
+            },
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_module_address(module_address: Option<Address>) -> Config {
+        Config {
+            id: CONFIG_ID,
+            admin: Identity::__dummy(),
+            module_address,
+            status_history_retention_minutes: DEFAULT_STATUS_HISTORY_RETENTION_MINUTES,
+        }
+    }
+
+    #[test]
+    fn the_module_itself_may_prune_the_status_history() {
+        let module_address = Address::from_arr(&[1; 16]);
+        let config = config_with_module_address(Some(module_address));
+        assert!(is_called_by_module(&config, Some(module_address)));
+    }
+
+    #[test]
+    fn clients_may_not_prune_the_status_history() {
+        let config = config_with_module_address(Some(Address::from_arr(&[1; 16])));
+        assert!(!is_called_by_module(
+            &config,
+            Some(Address::from_arr(&[2; 16]))
+        ));
+        assert!(!is_called_by_module(&config, None));
+
+        let config_without_address = config_with_module_address(None);
+        assert!(!is_called_by_module(&config_without_address, None));
+    }
+}